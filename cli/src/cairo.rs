@@ -3,6 +3,10 @@ use clap::Parser;
 use ityfuzz::cairo::config::CairoFuzzConfig;
 use ityfuzz::cairo::input::CairoInput;
 use ityfuzz::cairo::input::ConciseCairoInput;
+use ityfuzz::cairo::oracle::{
+    ArrayBoundsOracle, AssertFailureOracle, FeltRangeCheckOracle, IgnoredReturnOracle,
+    TypedBugOracle,
+};
 use ityfuzz::cairo::types::CairoAddress;
 use ityfuzz::cairo::types::CairoFuzzState;
 use ityfuzz::cairo::vm::CairoState;
@@ -26,12 +30,48 @@ pub struct CairoArgs {
     /// random seed
     #[arg(long, default_value = "1667840158231589000")]
     seed: u64,
+
+    /// Enable the catch-all oracle reporting every crash regardless of
+    /// detector tag (assert failures, range-check failures, ...)
+    #[arg(long, default_value = "true")]
+    typed_bug_oracle: bool,
+
+    /// Enable the oracle that reports only reachable assert/panic failures
+    #[arg(long, default_value = "false")]
+    assert_failure_oracle: bool,
+
+    /// Enable the oracle that reports only range-check builtin failures
+    /// (the observable symptom of felt252 arithmetic wrapping the field
+    /// modulus outside an asserted range)
+    #[arg(long, default_value = "false")]
+    felt_range_check_oracle: bool,
+
+    /// Enable the oracle that reports out-of-range array/Span index
+    /// accesses and bounded-integer range-check failures
+    #[arg(long, default_value = "false")]
+    array_bounds_oracle: bool,
+
+    /// Enable the (currently no-op) oracle meant to flag calls whose
+    /// return value is ignored across a contract boundary
+    #[arg(long, default_value = "false")]
+    ignored_return_oracle: bool,
+
+    /// Felt252 dictionary file (decimal or `0x`-hex literals, one per
+    /// line), merged into the constants scraped from the compiled program
+    #[arg(long)]
+    dict: Option<String>,
+
+    /// JSON file of `{ "function", "args", "comment" }` test vectors,
+    /// decoded into corpus seeds for the fuzzed function before fuzzing
+    /// begins
+    #[arg(long)]
+    corpus_seed_file: Option<String>,
 }
 
 pub fn cairo_main(args: CairoArgs) {
     println!("Start fuzzing Cairo input file: {}", args.input_file);
 
-    let oracles: Vec<
+    let mut oracles: Vec<
         Rc<
             RefCell<
                 dyn Oracle<
@@ -48,6 +88,22 @@ pub fn cairo_main(args: CairoArgs) {
         >,
     > = vec![];
 
+    if args.typed_bug_oracle {
+        oracles.push(Rc::new(RefCell::new(TypedBugOracle::new())));
+    }
+    if args.assert_failure_oracle {
+        oracles.push(Rc::new(RefCell::new(AssertFailureOracle::new())));
+    }
+    if args.felt_range_check_oracle {
+        oracles.push(Rc::new(RefCell::new(FeltRangeCheckOracle::new())));
+    }
+    if args.array_bounds_oracle {
+        oracles.push(Rc::new(RefCell::new(ArrayBoundsOracle::new())));
+    }
+    if args.ignored_return_oracle {
+        oracles.push(Rc::new(RefCell::new(IgnoredReturnOracle::new())));
+    }
+
     let config: CairoFuzzConfig<
         CairoState,
         usize,
@@ -62,6 +118,8 @@ pub fn cairo_main(args: CairoArgs) {
         input: args.input_file,
         func_name: args.function,
         work_dir: "work_dir".to_string(),
+        dict: args.dict,
+        corpus_seed_file: args.corpus_seed_file,
     };
 
     let mut state: CairoFuzzState = FuzzState::new(args.seed);
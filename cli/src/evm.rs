@@ -2,6 +2,7 @@ use clap::Parser;
 use ityfuzz::evm::config::{Config, FuzzerTypes};
 use ityfuzz::evm::contract_utils::ContractLoader;
 use ityfuzz::evm::input::{ConciseEVMInput, EVMInput};
+use ityfuzz::evm::onchain::endpoints::{Chain, OnChainConfig};
 use ityfuzz::evm::types::{EVMAddress, EVMFuzzState, EVMU256};
 use ityfuzz::evm::vm::EVMState;
 use ityfuzz::fuzzers::evm_fuzzer::evm_fuzzer;
@@ -123,6 +124,14 @@ pub struct EvmArgs {
     #[arg(long, default_value = "Exact")]
     state_comp_matching: String,
 
+    /// Enable gas usage / gas griefing oracles
+    #[arg(long, default_value = "true")]
+    gas_oracle: bool,
+
+    /// Absolute per-transaction gas ceiling the gas usage oracle flags above
+    #[arg(long, default_value = "1000000")]
+    gas_threshold: u64,
+
     /// Replay?
     #[arg(long)]
     replay_file: Option<String>,
@@ -172,6 +181,12 @@ pub struct EvmArgs {
     /// Offchain Config File. If specified, will deploy based on offchain config file.
     #[arg(long, default_value = "")]
     offchain_config_file: String,
+
+    /// JSON file of `{ "function", "args", "comment" }` test vectors,
+    /// decoded into corpus seeds (PoC transactions, boundary values, ...)
+    /// before fuzzing begins
+    #[arg(long)]
+    corpus_seed_file: Option<String>,
 }
 
 enum EVMTargetType {
@@ -230,7 +245,43 @@ pub fn evm_main(args: EvmArgs) {
                 &proxy_deploy_codes,
                 &constructor_args_map,
             ),
-            _ => panic!("Not supported"),
+            EVMTargetType::Address => {
+                let chain = match &args.chain_type {
+                    Some(chain_type) => {
+                        Chain::from_str(chain_type).expect("unknown onchain chain type")
+                    }
+                    None => Chain::ETH,
+                };
+                let mut onchain = OnChainConfig::new_raw(
+                    match &args.onchain_url {
+                        Some(url) => vec![url.clone()],
+                        None => chain.get_chain_rpc_list(),
+                    },
+                    args.onchain_chain_id.unwrap_or_else(|| chain.get_chain_id()),
+                    args.onchain_block_number.unwrap_or(0),
+                    args.onchain_explorer_url
+                        .clone()
+                        .unwrap_or_else(|| chain.get_chain_etherscan_base()),
+                    args.onchain_chain_name
+                        .clone()
+                        .unwrap_or_else(|| chain.to_lowercase()),
+                );
+                if let Some(key) = &args.onchain_etherscan_api_key {
+                    onchain.add_etherscan_api_key(key.clone());
+                }
+                let local_proxy_addr = args
+                    .onchain_local_proxy_addr
+                    .as_ref()
+                    .map(|addr| EVMAddress::from_str(addr).expect("invalid onchain local proxy address"));
+                let replacements = ContractLoader::parse_replacements_file(&args.onchain_replacements_file);
+                ContractLoader::from_address(
+                    args.target.as_str(),
+                    &mut state,
+                    &mut onchain,
+                    local_proxy_addr,
+                    &replacements,
+                )
+            }
         },
         only_fuzz: if args.only_fuzz.len() > 0 {
             args.only_fuzz
@@ -264,6 +315,9 @@ pub fn evm_main(args: EvmArgs) {
         typed_bug: args.typed_bug_oracle,
         selfdestruct_bug: args.selfdestruct_oracle,
         arbitrary_external_call: args.arbitrary_external_call_oracle,
+        gas_oracle: args.gas_oracle,
+        gas_threshold: args.gas_threshold,
+        corpus_seed_file: args.corpus_seed_file,
     };
 
     match config.fuzzer_type {
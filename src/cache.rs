@@ -0,0 +1,68 @@
+/// A generic keyed blob cache. Used to persist onchain RPC / block-explorer
+/// responses across runs so repeated fuzzing campaigns don't keep re-fetching
+/// the same data.
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+pub trait Cache {
+    fn load(&self, key: &str) -> Result<String, String>;
+    fn save(&self, key: &str, value: &str) -> Result<(), String>;
+}
+
+/// Caches values on the local filesystem, one file per key. Each entry is
+/// stored as `<integrity hash>\n<body>` so a truncated or corrupted file is
+/// detected (and evicted) on load rather than being returned blindly.
+#[derive(Clone, Debug)]
+pub struct FileSystemCache {
+    dir: String,
+}
+
+impl FileSystemCache {
+    pub fn new(dir: &str) -> Self {
+        fs::create_dir_all(dir).expect("failed to create cache dir");
+        Self {
+            dir: dir.to_string(),
+        }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        PathBuf::from(&self.dir).join(key)
+    }
+}
+
+fn integrity_hash(body: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+impl Cache for FileSystemCache {
+    fn load(&self, key: &str) -> Result<String, String> {
+        let path = self.path_for(key);
+        let raw = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let Some((stored_hash, body)) = raw.split_once('\n') else {
+            // no integrity header at all: evict it and force a re-fetch
+            let _ = fs::remove_file(&path);
+            return Err("corrupt cache entry (missing integrity header)".to_string());
+        };
+        if integrity_hash(body) != stored_hash {
+            // the entry is corrupted or truncated: evict it and force a re-fetch
+            let _ = fs::remove_file(&path);
+            return Err(format!("cache entry {} failed integrity check", key));
+        }
+        Ok(body.to_string())
+    }
+
+    fn save(&self, key: &str, value: &str) -> Result<(), String> {
+        let path = self.path_for(key);
+        let contents = format!("{}\n{}", integrity_hash(value), value);
+        // write to a sibling temp file and rename so a run interrupted
+        // mid-write never leaves a partial (and therefore corrupt-looking)
+        // entry behind for a future record/replay run to trip over
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents).map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+}
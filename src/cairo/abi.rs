@@ -0,0 +1,648 @@
+use felt::Felt252;
+use num_traits::Num;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::input::Rng;
+
+/// A Cairo function argument's shape, recursively resolved from the
+/// `cairo_type` strings `Function::type_args` carries (see
+/// `crate::fuzzers::cairo_fuzzer::get_type_args`) against the compiled
+/// program's `identifiers` table. Laying generation and mutation out
+/// against this instead of treating `CairoInput.felts` as untyped bytes
+/// keeps every produced input the same shape cairo-rs's own argument
+/// deserialization expects, the same way hand-rolling `Arbitrary` for a
+/// config struct keeps a Cairo fuzzer's generated inputs from being
+/// rejected before they ever reach the target. It mirrors how a
+/// `FromStr`-style conversion registry maps named types to concrete
+/// decode/encode behavior: `Conversion::resolve` is that registry, mapping
+/// a `cairo_type` name to how many felts it occupies and how to
+/// generate/mutate it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Conversion {
+    /// A single unconstrained field element.
+    Felt,
+    /// A `felt` constrained to `{0, 1}` (Cairo's `bool`).
+    Bool,
+    /// A range-checked unsigned integer of `bits` width. `bits <= 128` is a
+    /// single felt value-checked below `2^bits` (`u8`/`u16`/`u32`/`u64`/
+    /// `u128`); wider values are represented as two felts (low, high),
+    /// each bounded below `2^128`, the way cairo-rs's own `Uint256` struct
+    /// lays them out.
+    Uint(u32),
+    /// A `ContractAddress`: one felt, encoded and mutated identically to
+    /// `Felt`, but rendered as a `0x`-prefixed hex address rather than a
+    /// decimal number since that's how every Cairo contract address is
+    /// actually written and compared.
+    Address,
+    /// A short-string-literal felt (Cairo's ASCII-packed `'hello'` string
+    /// convention: the string's bytes big-endian-packed into one felt).
+    /// Encoded/mutated like `Felt`, but rendered as the decoded ASCII text.
+    ShortString,
+    /// A named struct: its fields laid out back to back in member-offset
+    /// order.
+    Struct(Vec<Conversion>),
+    /// A compile-time-sized array (`T[N]`): exactly `N` `T`s back to back,
+    /// no length prefix.
+    FixedArray(Box<Conversion>, usize),
+    /// A `T*` pointer argument: a length felt followed by that many `T`s.
+    Span(Box<Conversion>),
+    /// A type annotation `FromStr` didn't recognize, carried along
+    /// verbatim for diagnostics (`Self::resolve` falls back to a bare
+    /// `Felt` instead and never produces this). Encoded/mutated/generated
+    /// identically to `Felt` - always a safe (if imprecise)
+    /// approximation.
+    Raw(String),
+}
+
+impl std::str::FromStr for Conversion {
+    type Err = std::convert::Infallible;
+
+    /// Parses a bare Cairo type-annotation string (e.g. `"felt252"`,
+    /// `"u128"`, `"ContractAddress"`) into a `Conversion`, independently of
+    /// `Self::resolve`'s `identifiers`-driven struct/array resolution -
+    /// the cheap path for a primitive annotation with no compiled
+    /// program's `identifiers` table around to resolve structs/arrays
+    /// against. Unknown names fall back to `Raw` (keeping the original
+    /// string) rather than silently becoming `Felt`, so the annotation
+    /// isn't lost for diagnostics.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        Ok(match trimmed {
+            "felt" | "felt252" => Conversion::Felt,
+            "bool" => Conversion::Bool,
+            "u8" => Conversion::Uint(8),
+            "u16" => Conversion::Uint(16),
+            "u32" => Conversion::Uint(32),
+            "u64" => Conversion::Uint(64),
+            "u128" => Conversion::Uint(128),
+            "Uint256" | "u256" => Conversion::Uint(256),
+            "ContractAddress" => Conversion::Address,
+            "ShortString" => Conversion::ShortString,
+            other => Conversion::Raw(other.to_string()),
+        })
+    }
+}
+
+/// `2^bits`, computed by repeated doubling so every width (not just the
+/// `u128`/`Uint256` case the old hardcoded bound covered) gets an exact
+/// range-check bound without needing a `Felt252::pow`.
+fn bound_for_bits(bits: u32) -> Felt252 {
+    let mut bound = Felt252::from(1u32);
+    for _ in 0..bits {
+        bound = bound.clone() + bound.clone();
+    }
+    bound
+}
+
+impl Conversion {
+    /// Best-effort resolution of a `cairo_type` string as reported by the
+    /// compiled program's `identifiers` JSON (e.g. `"felt"`, `"felt*"`,
+    /// `"Uint256"`, `"MyStruct"`, `"MyStruct[3]"`). Named structs are
+    /// looked up in `identifiers` and their members resolved recursively,
+    /// in declaration order, so nested structs/arrays get their own real
+    /// layout instead of the whole argument falling back to a bare
+    /// `Felt`. Anything still unrecognized after that falls back to
+    /// `Felt`, which is always a safe (if imprecise) approximation.
+    pub fn resolve(cairo_type: &str, identifiers: &Value) -> Self {
+        let cairo_type = cairo_type.trim();
+
+        if let Some(inner) = cairo_type.strip_suffix('*') {
+            return Conversion::Span(Box::new(Conversion::resolve(inner, identifiers)));
+        }
+
+        if let Some(open) = cairo_type.rfind('[') {
+            if cairo_type.ends_with(']') {
+                let inner = &cairo_type[..open];
+                if let Ok(len) = cairo_type[open + 1..cairo_type.len() - 1].parse::<usize>() {
+                    return Conversion::FixedArray(
+                        Box::new(Conversion::resolve(inner, identifiers)),
+                        len,
+                    );
+                }
+            }
+        }
+
+        match cairo_type {
+            "felt" | "felt252" => return Conversion::Felt,
+            "bool" => return Conversion::Bool,
+            "Uint256" | "u256" => return Conversion::Uint(256),
+            "u128" => return Conversion::Uint(128),
+            "u64" => return Conversion::Uint(64),
+            "u32" => return Conversion::Uint(32),
+            "u16" => return Conversion::Uint(16),
+            "u8" => return Conversion::Uint(8),
+            "ContractAddress" => return Conversion::Address,
+            "ShortString" => return Conversion::ShortString,
+            _ => {}
+        }
+
+        // Not a primitive: look it up in `identifiers` as a struct and
+        // recursively resolve each member.
+        if let Some(def) = identifiers.get(cairo_type) {
+            if def.get("type").and_then(|t| t.as_str()) == Some("struct") {
+                if let Some(members) = def.get("members").and_then(|m| m.as_object()) {
+                    let mut fields: Vec<(i64, Conversion)> = members
+                        .values()
+                        .map(|member| {
+                            let offset = member
+                                .get("offset")
+                                .and_then(|o| o.as_i64())
+                                .unwrap_or(0);
+                            let member_type = member
+                                .get("cairo_type")
+                                .and_then(|t| t.as_str())
+                                .unwrap_or("felt");
+                            (offset, Conversion::resolve(member_type, identifiers))
+                        })
+                        .collect();
+                    fields.sort_by_key(|(offset, _)| *offset);
+                    return Conversion::Struct(fields.into_iter().map(|(_, c)| c).collect());
+                }
+            }
+        }
+
+        Conversion::Felt
+    }
+
+    /// This type's felt-width with every `Span` counted at zero elements
+    /// (just its length prefix) - a lower bound on what `generate`
+    /// produces, since `generate` gives each `Span` a random length.
+    /// Used by `CairoCorpusInitializer::initialize` to size the very
+    /// first input before there's a concrete `felts` vector to measure
+    /// [`Self::layout_len`] against.
+    pub fn felt_width(&self) -> usize {
+        match self {
+            Conversion::Felt | Conversion::Bool | Conversion::Address | Conversion::ShortString | Conversion::Raw(_) => 1,
+            Conversion::Uint(bits) if *bits > 128 => 2,
+            Conversion::Uint(_) => 1,
+            Conversion::Struct(fields) => fields.iter().map(Conversion::felt_width).sum(),
+            Conversion::FixedArray(elem, len) => elem.felt_width() * len,
+            // Just the length prefix; the body is however many elements
+            // the concrete `felts` it's read from actually carries.
+            Conversion::Span(_) => 1,
+        }
+    }
+
+    /// The number of felts this argument occupies, given the felts
+    /// starting at `offset`. `Span`s read their own length prefix out of
+    /// `felts`, so callers must walk a signature left to right rather
+    /// than computing every argument's length up front.
+    fn layout_len(&self, felts: &[Felt252], offset: usize) -> usize {
+        match self {
+            Conversion::Felt | Conversion::Bool | Conversion::Address | Conversion::ShortString | Conversion::Raw(_) => 1,
+            Conversion::Uint(bits) if *bits > 128 => 2,
+            Conversion::Uint(_) => 1,
+            Conversion::Struct(fields) => {
+                let mut total = 0;
+                let mut pos = offset;
+                for field in fields {
+                    let field_len = field.layout_len(felts, pos);
+                    total += field_len;
+                    pos += field_len;
+                }
+                total
+            }
+            Conversion::FixedArray(elem, len) => {
+                let mut total = 0;
+                let mut pos = offset;
+                for _ in 0..*len {
+                    let elem_len = elem.layout_len(felts, pos);
+                    total += elem_len;
+                    pos += elem_len;
+                }
+                total
+            }
+            Conversion::Span(elem) => {
+                let Some(len_felt) = felts.get(offset) else {
+                    return 1;
+                };
+                let len = felt_to_usize(len_felt);
+                let mut total = 1;
+                let mut pos = offset + 1;
+                for _ in 0..len {
+                    let elem_len = elem.layout_len(felts, pos);
+                    total += elem_len;
+                    pos += elem_len;
+                }
+                total
+            }
+        }
+    }
+
+    fn generate(&self, rng: &mut Rng, out: &mut Vec<Felt252>) {
+        match self {
+            Conversion::Felt | Conversion::Raw(_) => out.push(Felt252::from(rng.rand(0, 255))),
+            Conversion::Bool => out.push(Felt252::from(rng.rand(0, 1))),
+            Conversion::Address => out.push(Felt252::from(rng.rand(0, 255))),
+            Conversion::ShortString => out.push(pack_short_string(SHORT_STRING_SAMPLES[
+                rng.rand(0, SHORT_STRING_SAMPLES.len() - 1)
+            ])),
+            Conversion::Uint(bits) if *bits > 128 => {
+                out.push(Felt252::from(rng.rand(0, 255))); // low
+                out.push(Felt252::from(0)); // high
+            }
+            Conversion::Uint(_) => out.push(Felt252::from(rng.rand(0, 255))),
+            Conversion::Struct(fields) => {
+                for field in fields {
+                    field.generate(rng, out);
+                }
+            }
+            Conversion::FixedArray(elem, len) => {
+                for _ in 0..*len {
+                    elem.generate(rng, out);
+                }
+            }
+            Conversion::Span(elem) => {
+                let len = rng.rand_exp(0, 8);
+                out.push(Felt252::from(len));
+                for _ in 0..len {
+                    elem.generate(rng, out);
+                }
+            }
+        }
+    }
+
+    /// Mutate this argument in place, starting at `offset` in `felts`,
+    /// without disturbing the layout of anything after it: a `Span`
+    /// grows or shrinks by splicing whole elements and rewriting its own
+    /// length prefix rather than ever splitting one, a `FixedArray` keeps
+    /// its compile-time length and only ever recurses into one of its
+    /// existing elements, `Uint` values are clamped back under `2^bits`
+    /// after perturbation, `Bool` is kept in `{0, 1}`, and `Struct`
+    /// recurses into a single random field (keeping its field count
+    /// fixed) so most mutations stay local.
+    fn mutate(&self, rng: &mut Rng, felts: &mut Vec<Felt252>, offset: usize) {
+        match self {
+            Conversion::Felt | Conversion::Address | Conversion::Raw(_) => {
+                felts[offset] = Felt252::from(rng.rand(0, 255)) + felts[offset].clone();
+            }
+            Conversion::Bool => {
+                felts[offset] = Felt252::from(rng.rand(0, 1));
+            }
+            Conversion::ShortString => {
+                felts[offset] =
+                    pack_short_string(SHORT_STRING_SAMPLES[rng.rand(0, SHORT_STRING_SAMPLES.len() - 1)]);
+            }
+            Conversion::Uint(bits) => {
+                let low_bits = (*bits).min(128);
+                let bound = bound_for_bits(low_bits);
+                let delta = Felt252::from(rng.rand(0, 255));
+                felts[offset] = (felts[offset].clone() + delta) % bound;
+                if *bits > 128 {
+                    let high_bound = bound_for_bits(128);
+                    let high_delta = Felt252::from(rng.rand(0, 255));
+                    felts[offset + 1] =
+                        (felts[offset + 1].clone() + high_delta) % high_bound;
+                }
+            }
+            Conversion::Struct(fields) => {
+                if fields.is_empty() {
+                    return;
+                }
+                let idx = rng.rand(0, fields.len() - 1);
+                let mut pos = offset;
+                for field in &fields[..idx] {
+                    pos += field.layout_len(felts, pos);
+                }
+                fields[idx].mutate(rng, felts, pos);
+            }
+            Conversion::FixedArray(elem, len) => {
+                if *len == 0 {
+                    return;
+                }
+                let idx = rng.rand(0, len - 1);
+                let mut pos = offset;
+                for _ in 0..idx {
+                    pos += elem.layout_len(felts, pos);
+                }
+                elem.mutate(rng, felts, pos);
+            }
+            Conversion::Span(elem) => {
+                let len_offset = offset;
+                let len = felt_to_usize(&felts[len_offset]);
+                let body_start = offset + 1;
+
+                // Flip a coin between growing, shrinking, and mutating an
+                // existing element in place so spans don't only ever grow.
+                match rng.rand(0, 2) {
+                    0 if len > 0 => {
+                        // Shrink: drop the last element and fix the prefix.
+                        let last_start = {
+                            let mut pos = body_start;
+                            for _ in 0..len - 1 {
+                                pos += elem.layout_len(felts, pos);
+                            }
+                            pos
+                        };
+                        let last_len = elem.layout_len(felts, last_start);
+                        felts.drain(last_start..last_start + last_len);
+                        felts[len_offset] = Felt252::from(len - 1);
+                    }
+                    1 => {
+                        // Grow: append a freshly generated element and fix
+                        // the prefix.
+                        let mut new_elem = Vec::new();
+                        elem.generate(rng, &mut new_elem);
+                        let mut insert_at = body_start;
+                        for _ in 0..len {
+                            insert_at += elem.layout_len(felts, insert_at);
+                        }
+                        felts.splice(insert_at..insert_at, new_elem);
+                        felts[len_offset] = Felt252::from(len + 1);
+                    }
+                    _ if len > 0 => {
+                        // Mutate: recurse into a random existing element.
+                        let idx = rng.rand(0, len - 1);
+                        let mut pos = body_start;
+                        for _ in 0..idx {
+                            pos += elem.layout_len(felts, pos);
+                        }
+                        elem.mutate(rng, felts, pos);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl Conversion {
+    /// Decodes one structured JSON call argument (as carried by a corpus
+    /// seed file's `args` array, see `decode_json`) against this
+    /// `Conversion`, appending the resulting felt(s) to `out`. Mirrors
+    /// [`Self::generate`]'s recursion shape, but pulls concrete values out
+    /// of `value` instead of the RNG, so a user-authored test vector
+    /// produces the exact felts they wrote rather than a random seed of
+    /// the right shape. Returns an error (rather than panicking) on any
+    /// shape mismatch so the caller can skip just this one malformed
+    /// entry.
+    fn decode_value(&self, value: &Value, out: &mut Vec<Felt252>) -> Result<(), String> {
+        match self {
+            Conversion::Felt | Conversion::Address | Conversion::Raw(_) => out.push(json_to_felt(value)?),
+            Conversion::ShortString => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| "expected a short-string literal".to_string())?;
+                out.push(pack_short_string(s));
+            }
+            Conversion::Bool => {
+                let b = match value {
+                    Value::Bool(b) => *b,
+                    Value::Number(n) => n.as_u64().map(|v| v != 0).unwrap_or(true),
+                    _ => return Err("expected a bool".to_string()),
+                };
+                out.push(Felt252::from(b as u8));
+            }
+            Conversion::Uint(bits) => {
+                out.push(json_to_felt(value)?);
+                if *bits > 128 {
+                    // Low felt only; the seed file has no way to express a
+                    // high limb distinct from zero, which covers every
+                    // practical Uint256 boundary value anyway.
+                    out.push(Felt252::from(0));
+                }
+            }
+            Conversion::Struct(fields) => {
+                let members = value
+                    .as_array()
+                    .ok_or_else(|| "expected an array of struct members".to_string())?;
+                if members.len() != fields.len() {
+                    return Err(format!(
+                        "struct expects {} member(s), got {}",
+                        fields.len(),
+                        members.len()
+                    ));
+                }
+                for (field, member) in fields.iter().zip(members) {
+                    field.decode_value(member, out)?;
+                }
+            }
+            Conversion::FixedArray(elem, len) => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| "expected a fixed-size array".to_string())?;
+                if items.len() != *len {
+                    return Err(format!(
+                        "array expects {} element(s), got {}",
+                        len,
+                        items.len()
+                    ));
+                }
+                for item in items {
+                    elem.decode_value(item, out)?;
+                }
+            }
+            Conversion::Span(elem) => {
+                let items = value
+                    .as_array()
+                    .ok_or_else(|| "expected a span array".to_string())?;
+                out.push(Felt252::from(items.len()));
+                for item in items {
+                    elem.decode_value(item, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A handful of short, human-readable sample strings `Conversion::ShortString`
+/// generation/mutation picks from, rather than packing random bytes that
+/// would almost always decode back out as unprintable garbage.
+const SHORT_STRING_SAMPLES: &[&str] = &["", "a", "ok", "test", "hello", "cairo", "ityfuzz"];
+
+/// Packs `s` (at most 31 ASCII bytes, Cairo's short-string limit) into a
+/// single felt, big-endian, the same layout `decode_short_string` reverses.
+fn pack_short_string(s: &str) -> Felt252 {
+    let bytes = &s.as_bytes()[..s.len().min(31)];
+    bytes
+        .iter()
+        .fold(Felt252::from(0), |acc, b| acc * Felt252::from(256) + Felt252::from(*b))
+}
+
+/// Reverses `pack_short_string`: strips `felt`'s leading zero bytes and
+/// lossily decodes the remainder as ASCII, for rendering a `ShortString`
+/// argument in a trace dump.
+fn decode_short_string(felt: &Felt252) -> String {
+    let bytes = felt.to_be_bytes();
+    let first_nonzero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[first_nonzero..]).into_owned()
+}
+
+/// `0x`-prefixed hex rendering of `felt`, trimmed of leading zero nibbles
+/// (but never down to an empty string), for rendering `Address` arguments.
+fn felt_hex(felt: &Felt252) -> String {
+    let hex = hex::encode(felt.to_be_bytes());
+    let trimmed = hex.trim_start_matches('0');
+    format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+}
+
+impl Conversion {
+    /// Renders this argument's felts (starting at `offset`) as a
+    /// human-meaningful `serde_json::Value` instead of a raw felt dump:
+    /// `Bool` as `true`/`false`, `ShortString` as decoded ASCII text,
+    /// `Address` as a `0x`-prefixed hex string, `Uint`/`Felt`/`Raw` as a
+    /// decimal-string number, and `Struct`/`FixedArray`/`Span`
+    /// recursively as JSON arrays. Returns the rendered value and how
+    /// many felts it consumed, mirroring `layout_len`, so a caller can
+    /// walk a whole signature left to right.
+    pub fn render(&self, felts: &[Felt252], offset: usize) -> (Value, usize) {
+        match self {
+            Conversion::Felt | Conversion::Raw(_) => (
+                Value::String(felts.get(offset).map(|f| f.to_string()).unwrap_or_default()),
+                1,
+            ),
+            Conversion::Bool => (
+                Value::Bool(felts.get(offset).map(|f| *f != Felt252::from(0)).unwrap_or(false)),
+                1,
+            ),
+            Conversion::Address => (
+                Value::String(felts.get(offset).map(felt_hex).unwrap_or_default()),
+                1,
+            ),
+            Conversion::ShortString => (
+                Value::String(felts.get(offset).map(decode_short_string).unwrap_or_default()),
+                1,
+            ),
+            Conversion::Uint(bits) if *bits > 128 => (
+                Value::String(felts.get(offset).map(|f| f.to_string()).unwrap_or_default()),
+                2,
+            ),
+            Conversion::Uint(_) => (
+                Value::String(felts.get(offset).map(|f| f.to_string()).unwrap_or_default()),
+                1,
+            ),
+            Conversion::Struct(fields) => {
+                let mut values = Vec::new();
+                let mut pos = offset;
+                for field in fields {
+                    let (value, len) = field.render(felts, pos);
+                    values.push(value);
+                    pos += len;
+                }
+                (Value::Array(values), pos - offset)
+            }
+            Conversion::FixedArray(elem, len) => {
+                let mut values = Vec::new();
+                let mut pos = offset;
+                for _ in 0..*len {
+                    let (value, elem_len) = elem.render(felts, pos);
+                    values.push(value);
+                    pos += elem_len;
+                }
+                (Value::Array(values), pos - offset)
+            }
+            Conversion::Span(elem) => {
+                let Some(len_felt) = felts.get(offset) else {
+                    return (Value::Array(vec![]), 1);
+                };
+                let len = felt_to_usize(len_felt);
+                let mut values = Vec::new();
+                let mut pos = offset + 1;
+                for _ in 0..len {
+                    let (value, elem_len) = elem.render(felts, pos);
+                    values.push(value);
+                    pos += elem_len;
+                }
+                (Value::Array(values), pos - offset)
+            }
+        }
+    }
+}
+
+/// Renders `felts` against `signature` argument-by-argument (see
+/// `Conversion::render`), falling back to a flat array of decimal-string
+/// felts when the signature's declared layout doesn't account for every
+/// felt actually present (e.g. no signature was resolved for this call).
+pub fn render_args(signature: &[Conversion], felts: &[Felt252]) -> Value {
+    let mut values = Vec::new();
+    let mut pos = 0;
+    for conversion in signature {
+        if pos >= felts.len() {
+            break;
+        }
+        let (value, len) = conversion.render(felts, pos);
+        values.push(value);
+        pos += len;
+    }
+    if pos != felts.len() {
+        return Value::Array(felts.iter().map(|f| Value::String(f.to_string())).collect());
+    }
+    Value::Array(values)
+}
+
+/// Parses a single scalar JSON value (a number, or a decimal/`0x`-hex
+/// string, matching `CairoDictMetadata::from_file`'s literal syntax) into a
+/// felt.
+fn json_to_felt(value: &Value) -> Result<Felt252, String> {
+    match value {
+        Value::Number(n) => {
+            if let Some(v) = n.as_u64() {
+                Ok(Felt252::from(v))
+            } else if let Some(v) = n.as_i64() {
+                Ok(Felt252::from(v))
+            } else {
+                Err(format!("number {} is out of range", n))
+            }
+        }
+        Value::String(s) => {
+            let s = s.trim();
+            let parsed = match s.strip_prefix("0x") {
+                Some(hex) => Felt252::from_str_radix(hex, 16),
+                None => Felt252::from_str_radix(s, 10),
+            };
+            parsed.map_err(|e| format!("invalid felt literal \"{}\": {}", s, e))
+        }
+        _ => Err("expected a number or a numeric string".to_string()),
+    }
+}
+
+/// Decodes a corpus-seed test vector's `args` array against `signature`
+/// (the fuzzed function's resolved `Function::type_args`), one JSON value
+/// per top-level argument, into a concrete `felts` vector ready to drop
+/// straight into `CairoInput::felts`.
+pub fn decode_json(signature: &[Conversion], args: &[Value]) -> Result<Vec<Felt252>, String> {
+    if signature.len() != args.len() {
+        return Err(format!(
+            "expected {} argument(s), got {}",
+            signature.len(),
+            args.len()
+        ));
+    }
+    let mut out = Vec::new();
+    for (conversion, arg) in signature.iter().zip(args) {
+        conversion.decode_value(arg, &mut out)?;
+    }
+    Ok(out)
+}
+
+fn felt_to_usize(felt: &Felt252) -> usize {
+    felt.to_string().parse::<usize>().unwrap_or(0)
+}
+
+/// Lays out a fresh `felts` vector matching `signature`, e.g. for seeding
+/// the initial corpus entry once the target function is known.
+pub fn generate(signature: &[Conversion], rng: &mut Rng) -> Vec<Felt252> {
+    let mut out = Vec::new();
+    for arg in signature {
+        arg.generate(rng, &mut out);
+    }
+    out
+}
+
+/// Mutates `felts` in place so it keeps matching `signature`: picks one
+/// top-level argument at random and mutates only that one, so span
+/// length prefixes and struct/array arity elsewhere in the vector stay
+/// correct.
+pub fn mutate_structured(signature: &[Conversion], felts: &mut Vec<Felt252>, rng: &mut Rng) {
+    if signature.is_empty() {
+        return;
+    }
+    let idx = rng.rand(0, signature.len() - 1);
+    let mut offset = 0;
+    for arg in &signature[..idx] {
+        offset += arg.layout_len(felts, offset);
+    }
+    signature[idx].mutate(rng, felts, offset);
+}
@@ -5,5 +5,17 @@ use crate::oracle::Oracle;
 pub struct CairoFuzzConfig<VS, Addr, Code, By, Out, I, S, CI> {
     pub oracles: Vec<Rc<RefCell<dyn Oracle<VS, Addr, Code, By, Out, I, S, CI>>>>,
     pub input: String,
+    /// Name of the function to fuzz, as it appears in the compiled
+    /// program's `identifiers` table.
+    pub func_name: String,
     pub work_dir: String,
+    /// Optional user-supplied dictionary file of felt literals (decimal or
+    /// `0x`-hex, one per line), merged into `CairoDictMetadata` alongside
+    /// the constants scraped from the compiled program.
+    pub dict: Option<String>,
+    /// Optional JSON file of `{ "function", "args", "comment" }` test
+    /// vectors (see `CairoCorpusInitializer::seed_from_file`), decoded
+    /// into concrete `CairoInput` seeds added to the corpus before fuzzing
+    /// begins.
+    pub corpus_seed_file: Option<String>,
 }
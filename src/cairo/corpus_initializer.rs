@@ -1,17 +1,32 @@
 use libafl::prelude::Corpus;
 use libafl::schedulers::Scheduler;
+use libafl::state::{HasMetadata, HasRand};
 use libafl::{corpus::Testcase, state::HasCorpus};
 
+use serde::Deserialize;
 use std::time::Duration;
 
 use crate::state_input::StagedVMState;
 
 use super::{
-    input::{CairoInput, ConciseCairoInput},
+    abi::{self, Conversion},
+    input::{CairoDictMetadata, CairoInput, ConciseCairoInput},
     types::{CairoFuzzState, CairoInfantStateState, CairoStagedVMState},
     vm::{CairoExecutor, CairoState},
 };
 
+/// One entry of a corpus-seed file (see
+/// `CairoCorpusInitializer::seed_from_file`): a named test case whose
+/// `args` decode against the fuzzed function's signature.
+#[derive(Debug, Deserialize)]
+struct CairoTestVector {
+    function: String,
+    #[serde(default)]
+    args: Vec<serde_json::Value>,
+    #[serde(default)]
+    comment: Option<String>,
+}
+
 pub struct CairoCorpusInitializer<'a> {
     pub executor: &'a mut CairoExecutor<CairoInput, CairoFuzzState, CairoState, ConciseCairoInput>,
     pub scheduler: &'a dyn Scheduler<CairoInput, CairoFuzzState>,
@@ -56,17 +71,36 @@ impl<'a> CairoCorpusInitializer<'a> {
         }
     }
 
-    pub fn initialize(&mut self) {
+    pub fn initialize(&mut self, corpus_seed_file: Option<&str>) {
+        let mut dict = CairoDictMetadata::new();
+        dict.extend(self.executor.program_constants());
+        self.state.metadata_mut().insert(dict);
+
+        let function = self.executor.function().clone();
+        let mut rng = super::input::Rng::new(self.state.rand_mut().next());
+        let felts = abi::generate(&function.type_args, &mut rng);
+
+        // The typed signature already knows each argument's felt-width
+        // (see `Conversion::felt_width`), so the very first seed is sized
+        // to actually hold it instead of relying on the flat default.
+        let min_input_size: usize = function.type_args.iter().map(Conversion::felt_width).sum();
+
         let input = CairoInput {
             repeat: 1,
+            func_name: function.name.clone(),
             sstate: StagedVMState::new_uninitialized(),
             sstate_idx: 0,
-            felts: vec![],
-            max_input_size: 1024,
+            felts,
+            type_args: function.type_args.clone(),
+            max_input_size: 1024.max(min_input_size),
         };
 
         add_input_to_corpus!(self.state, self.scheduler, input);
 
+        if let Some(path) = corpus_seed_file {
+            self.seed_from_file(path);
+        }
+
         let mut tc = Testcase::new(StagedVMState::new_with_state(CairoState::default()));
         tc.set_exec_time(Duration::from_secs(0));
         let idx = self
@@ -79,4 +113,66 @@ impl<'a> CairoCorpusInitializer<'a> {
             .on_add(&mut self.state.infant_states_state, idx)
             .expect("failed to call infant scheduler on_add");
     }
+
+    /// Loads `path` as a JSON array of `{ "function", "args", "comment" }`
+    /// test vectors and decodes every entry naming the fuzzed function
+    /// into a concrete `CairoInput` seed, via the same typed `Conversion`
+    /// layer `generate`/`mutate_structured` already use, added to the
+    /// corpus alongside the generated seed above. This lets a user
+    /// pre-load regression cases, PoC inputs, or boundary values (e.g.
+    /// `u8::MAX`, zero) instead of starting every campaign from an empty
+    /// argument list. A malformed entry (wrong arity, an unreadable file,
+    /// non-JSON content) is logged and skipped rather than aborting the
+    /// run.
+    fn seed_from_file(&mut self, path: &str) {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("warning: could not read corpus seed file {}: {}", path, e);
+                return;
+            }
+        };
+        let vectors: Vec<CairoTestVector> = match serde_json::from_str(&contents) {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                eprintln!("warning: corpus seed file {} is not valid JSON: {}", path, e);
+                return;
+            }
+        };
+
+        let function = self.executor.function().clone();
+        for vector in vectors {
+            if vector.function != function.name {
+                continue;
+            }
+
+            let felts = match abi::decode_json(&function.type_args, &vector.args) {
+                Ok(felts) => felts,
+                Err(e) => {
+                    eprintln!(
+                        "warning: skipping seed for {}{}: {}",
+                        vector.function,
+                        vector
+                            .comment
+                            .as_deref()
+                            .map(|c| format!(" ({})", c))
+                            .unwrap_or_default(),
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let input = CairoInput {
+                repeat: 1,
+                func_name: function.name.clone(),
+                sstate: StagedVMState::new_uninitialized(),
+                sstate_idx: 0,
+                felts,
+                type_args: function.type_args.clone(),
+                max_input_size: 1024,
+            };
+            add_input_to_corpus!(self.state, self.scheduler, input);
+        }
+    }
 }
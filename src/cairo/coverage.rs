@@ -0,0 +1,242 @@
+/// Instruction-coverage tracking for the Cairo VM, mirroring
+/// `evm::middlewares::coverage::Coverage`: a compiled `Program`'s valid PCs
+/// are enumerated once up front, each `execute()`'s relocated `(pc, fp)`
+/// trace is folded into a per-function covered set, and a text + JSON report
+/// can be dumped into a `coverage/` work dir. Cairo has no EVM-style
+/// mid-step hook to observe taken/untaken branches, so unlike the EVM
+/// `Coverage` middleware this only tracks instruction coverage, not branch
+/// coverage.
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cairo_rs::types::program::Program;
+use cairo_rs::vm::errors::vm_exception::get_location;
+use cairo_rs::vm::runners::cairo_runner::CairoRunner;
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::types::Function;
+
+/// A resolved source line for a single uncovered PC.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq, Hash)]
+pub struct CairoSourceLine {
+    pub file: String,
+    pub line: u32,
+    pub col: u32,
+    pub snippet: String,
+}
+
+/// Returns every offset into `program`'s compiled data as an instruction PC.
+/// This is a superset - a Cairo instruction whose encoding carries an
+/// immediate occupies two consecutive cells, so not every offset is
+/// actually a decoded instruction's start. Execution never lands on an
+/// immediate cell, so those cells are always reported as uncovered and the
+/// instruction coverage figure is a lower bound; in exchange no real
+/// instruction is left out by guessing wrong about instruction boundaries
+/// without a full decoder.
+pub fn instructions_pc(program: &Program) -> HashSet<usize> {
+    (0..program.data.len()).collect()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CairoCoverageResult {
+    pub instruction_coverage: usize,
+    pub total_instructions: usize,
+    pub uncovered_pc: Vec<usize>,
+    pub uncovered: HashSet<CairoSourceLine>,
+}
+
+impl CairoCoverageResult {
+    pub fn new() -> Self {
+        Self {
+            instruction_coverage: 0,
+            total_instructions: 0,
+            uncovered_pc: vec![],
+            uncovered: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CairoCoverageReport {
+    pub coverage: HashMap<String, CairoCoverageResult>,
+}
+
+impl CairoCoverageReport {
+    pub fn new() -> Self {
+        Self {
+            coverage: HashMap::new(),
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut s = String::new();
+        for (name, cov) in &self.coverage {
+            s.push_str(&format!("Function: {}\n", name));
+            s.push_str(&format!(
+                "Instruction Coverage: {}/{} ({:.2}%) \n",
+                cov.instruction_coverage,
+                cov.total_instructions,
+                (cov.instruction_coverage * 100) as f64 / cov.total_instructions as f64
+            ));
+            if cov.uncovered.len() > 0 {
+                s.push_str("Uncovered Code:\n");
+                for uncovered in &cov.uncovered {
+                    s.push_str(&format!(
+                        "{}:{}:{} | {}\n",
+                        uncovered.file, uncovered.line, uncovered.col, uncovered.snippet
+                    ));
+                }
+            }
+            s.push_str(&format!("Uncovered PCs: {:?}\n", cov.uncovered_pc));
+            s.push_str("--------------------------------\n");
+        }
+        s
+    }
+
+    pub fn dump_file(&self, work_dir: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+
+        let mut text_file = OpenOptions::new()
+            .write(true)
+            .append(false)
+            .create(true)
+            .truncate(true)
+            .open(format!("{}/cov_{}.txt", work_dir, timestamp))
+            .unwrap();
+        text_file.write_all(self.to_string().as_bytes()).unwrap();
+        text_file.flush().unwrap();
+
+        let mut json_file = OpenOptions::new()
+            .write(true)
+            .append(false)
+            .create(true)
+            .truncate(true)
+            .open(format!("{}/cov_{}.json", work_dir, timestamp))
+            .unwrap();
+        json_file
+            .write_all(serde_json::to_string(self).unwrap().as_bytes())
+            .unwrap();
+        json_file.flush().unwrap();
+    }
+
+    pub fn summarize(&self) {
+        println!("============= Cairo Coverage Summary =============");
+        for (name, cov) in &self.coverage {
+            println!(
+                "{}: {:.2}% Instruction Covered",
+                name,
+                (cov.instruction_coverage * 100) as f64 / cov.total_instructions as f64
+            );
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CairoCoverage {
+    pub pc_coverage: HashMap<Function, HashSet<usize>>,
+    pub total_instr_set: HashMap<Function, HashSet<usize>>,
+    pub pc_info: HashMap<(Function, usize), CairoSourceLine>,
+    resolved: HashSet<Function>,
+    pub work_dir: String,
+}
+
+impl CairoCoverage {
+    pub fn new(work_dir: String) -> Self {
+        let work_dir = format!("{}/coverage", work_dir);
+        if !Path::new(&work_dir).exists() {
+            fs::create_dir_all(&work_dir).unwrap();
+        }
+
+        Self {
+            pc_coverage: HashMap::new(),
+            total_instr_set: HashMap::new(),
+            pc_info: HashMap::new(),
+            resolved: HashSet::new(),
+            work_dir,
+        }
+    }
+
+    /// Registers `function`'s valid instruction PCs on program load, the
+    /// Cairo analogue of `Coverage::on_insert` decoding a contract's
+    /// bytecode once it's deployed.
+    pub fn on_load(&mut self, function: &Function, program: &Program) {
+        self.total_instr_set
+            .insert(function.clone(), instructions_pc(program));
+    }
+
+    /// Folds a relocated `(pc, fp)` execution trace into `function`'s
+    /// covered-PC set, and - the first time this function is seen - resolves
+    /// every one of its instruction PCs to a source `Location` via
+    /// `get_location` while `cairo_runner` (and its `debug_info`) is still
+    /// alive, since a fresh `CairoRunner` only lives for the duration of one
+    /// `execute()` call.
+    pub fn record(&mut self, function: &Function, cairo_runner: &CairoRunner, trace: &[(u32, u32)]) {
+        let covered = self.pc_coverage.entry(function.clone()).or_default();
+        for (pc, _fp) in trace {
+            covered.insert(*pc as usize);
+        }
+
+        if self.resolved.contains(function) {
+            return;
+        }
+        if let Some(total_pcs) = self.total_instr_set.get(function).cloned() {
+            for pc in total_pcs {
+                if let Some(location) = get_location(pc, cairo_runner) {
+                    let snippet = std::fs::read_to_string(&location.input_file.filename)
+                        .ok()
+                        .and_then(|src| {
+                            src.lines()
+                                .nth(location.start_line.saturating_sub(1) as usize)
+                                .map(str::to_string)
+                        })
+                        .unwrap_or_default();
+                    self.pc_info.insert(
+                        (function.clone(), pc),
+                        CairoSourceLine {
+                            file: location.input_file.filename.clone(),
+                            line: location.start_line,
+                            col: location.start_col,
+                            snippet,
+                        },
+                    );
+                }
+            }
+        }
+        self.resolved.insert(function.clone());
+    }
+
+    pub fn record_instruction_coverage(&mut self) {
+        let mut report = CairoCoverageReport::new();
+
+        for (function, all_pcs) in &self.total_instr_set {
+            let covered = self.pc_coverage.get(function).cloned().unwrap_or_default();
+            let uncovered_pc = all_pcs.difference(&covered).cloned().collect_vec();
+
+            let mut result = CairoCoverageResult {
+                instruction_coverage: all_pcs.intersection(&covered).count(),
+                total_instructions: all_pcs.len(),
+                uncovered_pc: uncovered_pc.clone(),
+                uncovered: HashSet::new(),
+            };
+
+            for pc in uncovered_pc {
+                if let Some(source_line) = self.pc_info.get(&(function.clone(), pc)) {
+                    result.uncovered.insert(source_line.clone());
+                }
+            }
+
+            report.coverage.insert(function.name.clone(), result);
+        }
+
+        report.dump_file(&self.work_dir);
+        report.summarize();
+    }
+}
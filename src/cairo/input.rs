@@ -1,6 +1,7 @@
 use super::{
+    abi::{self, Conversion},
     types::CairoAddress,
-    vm::{CairoState, HasCairoInput},
+    vm::{CairoState, HasCairoInput, HasFunctionName},
 };
 use crate::{
     generic_vm::vm_executor::ExecutionResult,
@@ -10,13 +11,67 @@ use crate::{
 };
 use felt::Felt252;
 use libafl::{
+    impl_serdeany,
     prelude::{Input, MutationResult},
     state::{HasMaxSize, HasMetadata, HasRand, State},
 };
+use num_traits::Num;
 use serde::{Deserialize, Serialize};
 
 use std::fmt::Debug;
 
+/// Felt-valued "interesting constants" dictionary for `Strategy`'s
+/// `dict_overwrite`/`dict_insert` mutations: integer cells (immediate
+/// operands and encoded instruction words alike) scraped from the compiled
+/// program (see `CairoExecutor::program_constants`) plus
+/// whatever a user-supplied dictionary file adds via [`Self::from_file`].
+/// The Cairo analogue of `evm::bytecode_analyzer::ConstantPoolMetadata`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CairoDictMetadata {
+    pub felts: Vec<Felt252>,
+}
+
+impl_serdeany!(CairoDictMetadata);
+
+impl CairoDictMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge `constants` in, skipping ones already present.
+    pub fn extend(&mut self, constants: impl IntoIterator<Item = Felt252>) {
+        for constant in constants {
+            if !self.felts.contains(&constant) {
+                self.felts.push(constant);
+            }
+        }
+    }
+
+    /// Parses a dictionary file of one felt literal (decimal, or hex
+    /// prefixed with `0x`) per line, in the spirit of a Cairo fuzzer's
+    /// `--dict=json.dict` token dictionary.
+    pub fn from_file(path: &str) -> Self {
+        let mut dict = Self::default();
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return dict;
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = match line.strip_prefix("0x") {
+                Some(hex) => Felt252::from_str_radix(hex, 16),
+                None => Felt252::from_str_radix(line, 10),
+            };
+            if let Ok(felt) = parsed {
+                dict.felts.push(felt);
+            }
+        }
+        dict
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct CairoInput {
     pub repeat: usize,
@@ -25,6 +80,13 @@ pub struct CairoInput {
 
     pub felts: Vec<Felt252>,
 
+    /// The target function's argument signature (`Function::type_args`),
+    /// when known. Non-empty here switches `mutate` over to ABI-aware
+    /// structured generation (see `super::abi`) instead of treating `felts`
+    /// as an untyped blob.
+    #[serde(default)]
+    pub type_args: Vec<Conversion>,
+
     /// Staged VM state
     #[serde(skip_deserializing)]
     pub sstate: StagedVMState<CairoAddress, CairoState, ConciseCairoInput>,
@@ -39,7 +101,18 @@ pub struct CairoInput {
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct ConciseCairoInput {
+    pub func_name: String,
+    pub repeat: usize,
     pub felts: Vec<Felt252>,
+
+    /// The argument signature `felts` was generated/mutated against, if
+    /// any (see `HasCairoInput::get_type_args`), used only to render
+    /// `serialize_string` type-aware. Not part of `ConciseSerde`'s binary
+    /// format: a replay only needs `felts` itself, and the binary format
+    /// is already load-bearing for on-disk crash reproducers, so it isn't
+    /// worth revving just to carry a rendering hint.
+    #[serde(default)]
+    pub type_args: Vec<Conversion>,
 }
 
 impl ConciseCairoInput {
@@ -48,25 +121,90 @@ impl ConciseCairoInput {
         _execution_result: &ExecutionResult<CairoAddress, CairoState, Out, ConciseCairoInput>,
     ) -> Self
     where
-        I: VMInputT<CairoState, CairoAddress, ConciseCairoInput> + HasCairoInput,
+        I: VMInputT<CairoState, CairoAddress, ConciseCairoInput> + HasCairoInput + HasFunctionName,
         Out: Default,
     {
         Self {
+            func_name: input.get_function(),
+            repeat: input.get_repeat(),
             felts: input.get_felts(),
+            type_args: input.get_type_args(),
         }
     }
 }
+
 impl ConciseSerde for ConciseCairoInput {
+    /// Length-prefixed binary encoding so a crashing input can be written to
+    /// disk and replayed later: `func_name` (u32 LE length + UTF-8 bytes),
+    /// `repeat` (u64 LE), then the felts (u32 LE count, each felt as its
+    /// 32-byte big-endian representation).
     fn serialize_concise(&self) -> Vec<u8> {
-        todo!()
+        let mut out = Vec::new();
+
+        let name_bytes = self.func_name.as_bytes();
+        out.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+        out.extend_from_slice(name_bytes);
+
+        out.extend_from_slice(&(self.repeat as u64).to_le_bytes());
+
+        out.extend_from_slice(&(self.felts.len() as u32).to_le_bytes());
+        for felt in &self.felts {
+            out.extend_from_slice(&felt.to_be_bytes());
+        }
+
+        out
     }
 
-    fn deserialize_concise(_data: &[u8]) -> Self {
-        todo!()
+    fn deserialize_concise(data: &[u8]) -> Self {
+        let mut cursor = 0usize;
+
+        let name_len = u32::from_le_bytes(data[cursor..cursor + 4].try_into().unwrap()) as usize;
+        cursor += 4;
+        let func_name = String::from_utf8(data[cursor..cursor + name_len].to_vec())
+            .expect("serialized func_name is not valid utf8");
+        cursor += name_len;
+
+        let repeat = u64::from_le_bytes(data[cursor..cursor + 8].try_into().unwrap()) as usize;
+        cursor += 8;
+
+        let felt_count = u32::from_le_bytes(data[cursor..cursor + 4].try_into().unwrap()) as usize;
+        cursor += 4;
+        let mut felts = Vec::with_capacity(felt_count);
+        for _ in 0..felt_count {
+            felts.push(Felt252::from_bytes_be(&data[cursor..cursor + 32]));
+            cursor += 32;
+        }
+
+        Self {
+            func_name,
+            repeat,
+            felts,
+            type_args: Vec::new(),
+        }
     }
 
+    /// Human-readable dump of a finding, analogous to the self-contained
+    /// JSON program a Cairo fuzzer writes out per crash. When `type_args`
+    /// is known and accounts for every felt, each argument is rendered via
+    /// `Conversion::render` (a decoded short string, a hex address, ...)
+    /// instead of a flat list of decimal felts.
     fn serialize_string(&self) -> String {
-        todo!()
+        let felts = if self.type_args.is_empty() {
+            format!(
+                "[{}]",
+                self.felts
+                    .iter()
+                    .map(|f| f.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        } else {
+            abi::render_args(&self.type_args, &self.felts).to_string()
+        };
+        format!(
+            "{{\"func_name\": \"{}\", \"repeat\": {}, \"felts\": {}}}",
+            self.func_name, self.repeat, felts
+        )
     }
 }
 
@@ -86,14 +224,21 @@ macro_rules! byte_corruptor {
     };
 }
 
-struct Rng {
+pub(crate) struct Rng {
     seed: u64,
     exp_disabled: bool,
 }
 
 impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            exp_disabled: false,
+        }
+    }
+
     #[inline]
-    fn next(&mut self) -> u64 {
+    pub(crate) fn next(&mut self) -> u64 {
         let val = self.seed;
         self.seed ^= self.seed << 13;
         self.seed ^= self.seed >> 17;
@@ -102,7 +247,7 @@ impl Rng {
     }
 
     #[inline]
-    fn rand(&mut self, min: usize, max: usize) -> usize {
+    pub(crate) fn rand(&mut self, min: usize, max: usize) -> usize {
         assert!(max >= min, "Bad range specified for rand()");
         if min == max {
             return min;
@@ -114,7 +259,7 @@ impl Rng {
     }
 
     #[inline]
-    fn rand_exp(&mut self, min: usize, max: usize) -> usize {
+    pub(crate) fn rand_exp(&mut self, min: usize, max: usize) -> usize {
         if self.exp_disabled {
             return self.rand(min, max);
         }
@@ -128,11 +273,32 @@ impl Rng {
     }
 }
 
+/// Corner-case values for the STARK prime `P = 2^251 + 17*2^192 + 1` that
+/// `Felt252`'s range-check and overflow/underflow assertions tend to branch
+/// on: zero/one, the top of the field (`P-1` == `-1`, `P-2` == `-2`), the
+/// range-check bound `2^128` and the field's other natural power-of-two
+/// boundaries (`2^192`, `2^251`), and the small negative values just past
+/// the `2^128` wrap point. Plain byte-level arithmetic almost never lands
+/// on any of these exactly.
+const FIELD_EDGE_CASES: &[&str] = &[
+    "0",
+    "1",
+    "3618502788666131213697322783095070105623107215331596699973092056135872020480", // P-1 == -1
+    "3618502788666131213697322783095070105623107215331596699973092056135872020479", // P-2 == -2
+    "340282366920938463463374607431768211456", // 2^128, the range-check bound
+    "6277101735386680763835789423207666416102355444464034512896", // 2^192
+    "3618502788666131106986593281521497120414687020801267626233049500247285301248", // 2^251
+    "3618502788666131213697322783095070105282824848410658236509717448704103809025", // -2^128
+    "3618502788666131213697322783095070105282824848410658236509717448704103809024", // -(2^128 + 1)
+];
+
 pub struct Strategy {
     pub input: Vec<Felt252>,
     pub accessed: Vec<usize>,
     rng: Rng,
     max_input_size: usize,
+    /// "Interesting constants" dictionary fed to `dict_overwrite`/`dict_insert`.
+    dict: Vec<Felt252>,
 }
 
 impl Strategy {
@@ -167,6 +333,41 @@ impl Strategy {
         );
     }
 
+    /// Overwrite a random felt in the input with a random entry from the
+    /// dictionary, so magic-value comparisons pure arithmetic mutation is
+    /// unlikely to stumble onto get hit directly.
+    pub fn dict_overwrite(&mut self) {
+        if self.dict.is_empty() || self.input.is_empty() {
+            return;
+        }
+        let offset = self.rand_offset();
+        let entry = self.dict[self.rng.rand(0, self.dict.len() - 1)].clone();
+        self.input[offset] = entry;
+    }
+
+    /// Splice a random dictionary entry into the input at a random offset.
+    pub fn dict_insert(&mut self) {
+        if self.dict.is_empty() || self.input.len() >= self.max_input_size {
+            return;
+        }
+        let offset = self.rand_offset_int(true);
+        let entry = self.dict[self.rng.rand(0, self.dict.len() - 1)].clone();
+        self.input.insert(offset, entry);
+    }
+
+    /// Overwrite a random felt with one of `FIELD_EDGE_CASES`, to directly
+    /// hit the range-check/overflow boundaries the STARK prime creates
+    /// instead of hoping arithmetic noise walks into them.
+    pub fn field_edge(&mut self) {
+        if self.input.is_empty() {
+            return;
+        }
+        let offset = self.rand_offset();
+        let case = FIELD_EDGE_CASES[self.rng.rand(0, FIELD_EDGE_CASES.len() - 1)];
+        self.input[offset] =
+            Felt252::from_str_radix(case, 10).expect("FIELD_EDGE_CASES entries are valid decimal");
+    }
+
     byte_corruptor!(dec_byte, |_: &mut Self, x: Felt252| -> Felt252 {
         x - Felt252::from(1)
     });
@@ -184,19 +385,24 @@ impl Strategy {
             1..=1 => 1,
             2..=3 => 1 << self.rng.rand(0, 1),
             4..=7 => 1 << self.rng.rand(0, 2),
-            8..=core::usize::MAX => 1 << self.rng.rand(0, 3),
+            8..=15 => 1 << self.rng.rand(0, 3),
+            // Widest bucket: a delta of up to +/-2^38 (see `range` below),
+            // still far below the field's 251-bit size, so `Felt252`'s
+            // wraparound only comes into play for values near 0 or `P`.
+            16..=core::usize::MAX => 1 << self.rng.rand(0, 4),
             _ => unreachable!(),
         };
 
-        let range = match intsize {
+        let range: i64 = match intsize {
             1 => 16,
             2 => 4096,
             4 => 1024 * 1024,
             8 => 256 * 1024 * 1024,
+            16 => 256 * 1024 * 1024 * 1024,
             _ => unreachable!(),
         };
 
-        let delta = self.rng.rand(0, range * 2) as i32 - range as i32;
+        let delta = self.rng.rand(0, (range * 2) as usize) as i64 - range;
 
         /// Macro to mutate bytes in the input as a `$ty`
         macro_rules! mutate {
@@ -205,7 +411,11 @@ impl Strategy {
                 let tmp = self.input[offset].clone();
 
                 // Apply the delta, interpreting the bytes as a random
-                // endianness
+                // endianness. `Felt252`'s `+`/`-` already wrap modulo the
+                // STARK prime `P`, so there's no separate overflow/underflow
+                // check to perform here - unlike the fixed-width `$ty` this
+                // delta was sized for, wraparound is just normal field
+                // arithmetic.
                 let tmp = if self.rng.rand(0, 1) == 0 {
                     (Felt252::from(delta) + Felt252::from(tmp))
                 } else {
@@ -269,6 +479,9 @@ impl Strategy {
         Strategy::random_insert,
         Strategy::add_sub,
         Strategy::swap,
+        Strategy::dict_overwrite,
+        Strategy::dict_insert,
+        Strategy::field_edge,
     ];
 
     pub fn mutate(&mut self, mutations: usize) {
@@ -288,20 +501,51 @@ impl Strategy {
 }
 
 impl VMInputT<CairoState, CairoAddress, ConciseCairoInput> for CairoInput {
-    fn mutate<S>(&mut self, _state: &mut S) -> MutationResult
+    fn mutate<S>(&mut self, state: &mut S) -> MutationResult
     where
         S: State + HasRand + HasMaxSize + HasCaller<CairoAddress> + HasMetadata,
     {
+        let max_input_size = if self.max_input_size > 0 {
+            self.max_input_size
+        } else {
+            state.max_size()
+        };
+        // reseed per-call so repeated mutations of the same input don't
+        // replay the exact same xorshift sequence
+        let seed = state.rand_mut().next();
+        let mutations = state.rand_mut().below(8) + 1;
+        let dict = state
+            .metadata()
+            .get::<CairoDictMetadata>()
+            .map(|meta| meta.felts.clone())
+            .unwrap_or_default();
+
+        if !self.type_args.is_empty() {
+            // The signature is already resolved (see `Conversion::resolve`):
+            // mutate structurally so span length prefixes and struct/array
+            // arity stay correct instead of bouncing off argument
+            // deserialization.
+            let mut rng = Rng {
+                seed,
+                exp_disabled: false,
+            };
+            for _ in 0..mutations {
+                abi::mutate_structured(&self.type_args, &mut self.felts, &mut rng);
+            }
+            return MutationResult::Mutated;
+        }
+
         let mut strategy = Strategy {
             input: self.felts.clone(),
             accessed: Vec::new(),
-            max_input_size: 1024,
+            max_input_size,
+            dict,
             rng: Rng {
-                seed: 0x12640367f4b7ea35,
+                seed,
                 exp_disabled: false,
             },
         };
-        strategy.mutate(4);
+        strategy.mutate(mutations);
         self.felts = strategy.input.clone();
         MutationResult::Mutated
     }
@@ -6,6 +6,7 @@ use crate::{
 };
 
 use super::{
+    abi::Conversion,
     input::{CairoInput, ConciseCairoInput},
     vm::CairoState,
 };
@@ -30,12 +31,24 @@ pub type CairoStagedVMState = StagedVMState<CairoAddress, CairoState, ConciseCai
 
 pub type CairoInfantStateState = InfantStateState<CairoAddress, CairoState, ConciseCairoInput>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Function {
     pub name: String,
     pub entrypoint: String,
     pub num_args: u64,
-    pub type_args: Vec<String>,
+    pub type_args: Vec<Conversion>,
     pub hints: bool,
     pub decorators: Vec<String>,
 }
+
+impl Function {
+    /// The argument signature generation, mutation, and trace rendering
+    /// should walk - i.e. `type_args`, already resolved by
+    /// `Conversion::resolve`/`FromStr`. A named accessor rather than
+    /// exposing the field name directly at call sites, so callers read as
+    /// "give me this function's argument shapes" rather than reaching into
+    /// a field whose resolved-ness isn't obvious from `type_args` alone.
+    pub fn arg_conversions(&self) -> Vec<Conversion> {
+        self.type_args.clone()
+    }
+}
@@ -1,10 +1,13 @@
 use felt::Felt252;
+use itertools::Itertools;
 use libafl::state::{HasCorpus, HasMetadata, HasRand, State};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 use crate::{
     generic_vm::{
-        vm_executor::{ExecutionResult, GenericVM},
+        vm_executor::{DeployError, ExecutionResult, GenericVM},
         vm_state::VMStateT,
     },
     input::{ConciseSerde, VMInputT},
@@ -15,10 +18,16 @@ use crate::{
 use cairo_rs::{
     hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
     types::{program::Program, relocatable::MaybeRelocatable},
-    vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+    vm::{
+        errors::vm_exception::{get_location, VmException},
+        runners::cairo_runner::CairoRunner,
+        vm_core::VirtualMachine,
+    },
 };
 
 use super::{
+    abi::Conversion,
+    coverage::CairoCoverage,
     input::{CairoInput, ConciseCairoInput},
     types::{CairoAddress, Function},
 };
@@ -34,6 +43,14 @@ pub struct CairoState {
 
     // pub func_name: Option<String>,
     pub typed_bug: Vec<String>,
+
+    /// `(pc, tag)` for each entry in `typed_bug`, at the same index - the
+    /// structured form the per-detector oracles in `cairo::oracle` key off
+    /// of, since `typed_bug` itself is a free-form report string not meant
+    /// to be hashed/matched directly. `pc` is the faulting instruction
+    /// offset (`0` if it couldn't be resolved), `tag` is one of
+    /// [`classify_bug`]'s outputs.
+    pub bug_sites: Vec<(u32, String)>,
 }
 
 impl CairoState {
@@ -41,6 +58,7 @@ impl CairoState {
         Self {
             state: vec![],
             typed_bug: vec![],
+            bug_sites: vec![],
             // func_name,
             bug_hit: false,
         }
@@ -55,35 +73,42 @@ impl Default for CairoState {
 
 impl VMStateT for CairoState {
     fn get_hash(&self) -> u64 {
-        todo!()
+        let mut s = DefaultHasher::new();
+        for i in self.state.iter().sorted() {
+            i.0.hash(&mut s);
+            i.1.hash(&mut s);
+        }
+        s.finish()
     }
 
-    // fn has_post_execution(&self) -> bool {
-    //     self.post_execution.len() > 0
-    // }
+    fn has_post_execution(&self) -> bool {
+        false
+    }
 
+    // Cairo doesn't support multi-transaction post execution yet, so there
+    // is never anything pending to resume.
     fn get_post_execution_needed_len(&self) -> usize {
-        todo!()
+        0
     }
 
     fn get_post_execution_pc(&self) -> usize {
-        todo!()
+        0
     }
 
     fn get_post_execution_len(&self) -> usize {
-        todo!()
+        0
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
-        todo!()
+        self
     }
 
-    fn eq(&self, _other: &Self) -> bool {
-        todo!()
+    fn eq(&self, other: &Self) -> bool {
+        self.state == other.state
     }
 
-    fn is_subset_of(&self, _other: &Self) -> bool {
-        todo!()
+    fn is_subset_of(&self, other: &Self) -> bool {
+        self.state.iter().all(|pair| other.state.contains(pair))
     }
 }
 
@@ -97,6 +122,7 @@ where
 {
     program: Program,
     function: Function,
+    coverage: CairoCoverage,
     phantom: PhantomData<(VS, I, S, CI)>,
 }
 
@@ -106,23 +132,151 @@ where
     I: VMInputT<VS, CairoAddress, ConciseCairoInput>,
     VS: VMStateT,
 {
-    pub fn new(program: Program, function: Function) -> Self {
+    pub fn new(program: Program, function: Function, work_dir: String) -> Self {
+        let mut coverage = CairoCoverage::new(work_dir);
+        coverage.on_load(&function, &program);
         Self {
             program,
             function,
+            coverage,
             phantom: Default::default(),
         }
     }
+
+    /// The target function being fuzzed, e.g. so callers can read its
+    /// argument signature when seeding `CairoInput::type_args`.
+    pub fn function(&self) -> &Function {
+        &self.function
+    }
+
+    /// Every integer cell of the compiled program's data segment (immediate
+    /// felt operands, and the encoded instruction words themselves),
+    /// harvested once at load time to seed `CairoDictMetadata` with the
+    /// program's own magic constants (the Cairo analogue of
+    /// `evm::bytecode_analyzer::find_constants` scraping `PUSH` operands).
+    pub fn program_constants(&self) -> Vec<Felt252> {
+        self.program
+            .data
+            .iter()
+            .filter_map(|cell| match cell {
+                MaybeRelocatable::Int(value) => Some(value.clone()),
+                MaybeRelocatable::RelocatableValue(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// A single resolved frame of a crashing program's call stack: the source
+/// location cairo-rs's debug info maps a faulting program counter back to,
+/// plus the line of source it points at - the Cairo analogue of the
+/// file/line context EVM fuzzing already gets from `evm::srcmap`.
+struct CairoTracebackFrame {
+    file: String,
+    line: u32,
+    col: u32,
+    snippet: String,
+}
+
+/// How far back up the call stack to resolve locations for. Past a handful
+/// of frames the caller context stops adding anything useful to the report.
+const MAX_TRACEBACK_FRAMES: usize = 16;
+
+fn location_to_frame(location: &cairo_rs::serde::deserialize_program::Location) -> CairoTracebackFrame {
+    let snippet = std::fs::read_to_string(&location.input_file.filename)
+        .ok()
+        .and_then(|src| src.lines().nth(location.start_line.saturating_sub(1) as usize).map(str::to_string))
+        .unwrap_or_default();
+    CairoTracebackFrame {
+        file: location.input_file.filename.clone(),
+        line: location.start_line,
+        col: location.start_col,
+        snippet,
+    }
+}
+
+/// Turns a `VirtualMachineError` from a failed run into the same kind of
+/// actionable crash report EVM fuzzing gets for free: `VmException::
+/// from_vm_error` gives the formatted Cairo-style error (mirroring
+/// `cairo-run`'s own CLI output), and walking the raw execution trace
+/// backward from the fault - resolving each program counter with
+/// `get_location` - recovers `(file, line, col, snippet)` frames for
+/// whatever source maps the compiled program carries in its `debug_info`.
+fn build_crash_report(
+    cairo_runner: &CairoRunner,
+    vm: &VirtualMachine,
+    err: cairo_rs::vm::errors::vm_errors::VirtualMachineError,
+) -> (String, Vec<CairoTracebackFrame>, u32) {
+    let vm_exception = VmException::from_vm_error(cairo_runner, vm, err);
+    let error_message = vm_exception.to_string();
+
+    let mut frames = Vec::new();
+    let mut faulting_pc: u32 = 0;
+    for (i, entry) in vm.get_trace().iter().rev().enumerate() {
+        if i == 0 {
+            faulting_pc = entry.pc.try_into().unwrap_or(0);
+        }
+        if frames.len() >= MAX_TRACEBACK_FRAMES {
+            continue;
+        }
+        if let Some(location) = get_location(entry.pc, cairo_runner) {
+            frames.push(location_to_frame(&location));
+        }
+    }
+
+    (error_message, frames, faulting_pc)
+}
+
+/// Buckets a cairo-rs crash message into a detector tag the per-variant
+/// oracles in `cairo::oracle` key off of. cairo-rs surfaces assertion
+/// failures, the range-check builtin's failures (the practical observable
+/// symptom of felt252 arithmetic wrapping the field modulus outside the
+/// range a program asserted it into), and array/`Span` index checks (a
+/// range-check under the hood too, but worded around "index"/"size" rather
+/// than a bare value) as distinctly worded `VirtualMachineError`s, so a
+/// simple substring match on the formatted message is enough to tell them
+/// apart without needing to hook the VM's opcode dispatch.
+fn classify_bug(error_message: &str) -> &'static str {
+    let lower = error_message.to_ascii_lowercase();
+    if lower.contains("index") && (lower.contains("range") || lower.contains("size")) {
+        "array_bounds"
+    } else if error_message.contains("is not in the range") || error_message.contains("Out of range") {
+        "range_check_failure"
+    } else if error_message.contains("Assert") || error_message.contains("assert") {
+        "assert_failure"
+    } else {
+        "vm_exception"
+    }
+}
+
+/// Renders a crash report as the `typed_bug` string the fuzzer surfaces:
+/// the cairo-rs error message, followed by every resolved stack frame,
+/// innermost (closest to the fault) first.
+fn format_crash_report(error_message: &str, frames: &[CairoTracebackFrame]) -> String {
+    let mut report = error_message.to_string();
+    for frame in frames {
+        report.push_str(&format!("\n  at {}:{}:{} | {}", frame.file, frame.line, frame.col, frame.snippet));
+    }
+    report
 }
 
 pub trait HasCairoInput {
     fn get_felts(&self) -> Vec<Felt252>;
+    fn get_repeat(&self) -> usize;
+    fn get_type_args(&self) -> Vec<Conversion>;
 }
 
 impl HasCairoInput for CairoInput {
     fn get_felts(&self) -> Vec<Felt252> {
         self.felts.clone()
     }
+
+    fn get_repeat(&self) -> usize {
+        self.repeat
+    }
+
+    fn get_type_args(&self) -> Vec<Conversion> {
+        self.type_args.clone()
+    }
 }
 
 impl HasFunctionName for CairoInput {
@@ -131,7 +285,7 @@ impl HasFunctionName for CairoInput {
     }
 }
 
-trait HasFunctionName {
+pub trait HasFunctionName {
     fn get_function(&self) -> String;
 }
 
@@ -158,7 +312,7 @@ where
         _constructor_args: Option<usize>,
         _deployed_address: CairoAddress,
         _state: &mut S,
-    ) -> Option<CairoAddress> {
+    ) -> Result<CairoAddress, DeployError> {
         todo!()
     }
 
@@ -219,18 +373,29 @@ where
             args.push(val)
         }
 
-        match cairo_runner.run_from_entrypoint_fuzz(
+        if let Err(err) = cairo_runner.run_from_entrypoint_fuzz(
             entrypoint,
             args,
             true,
             &mut vm,
             &mut hint_processor,
         ) {
-            Ok(()) => (),
-            Err(_e) => {
-                panic!("Fail to run input program")
-            }
-        };
+            let (error_message, frames, faulting_pc) = build_crash_report(&cairo_runner, &vm, err);
+            let report = format_crash_report(&error_message, &frames);
+            let tag = classify_bug(&error_message);
+
+            let mut post_state = CairoState::new();
+            post_state.bug_hit = true;
+            post_state.typed_bug.push(report.clone());
+            post_state.bug_sites.push((faulting_pc, tag.to_string()));
+
+            return ExecutionResult {
+                output: vec![],
+                reverted: true,
+                new_state: StagedVMState::new_with_state(post_state),
+                additional_info: Some(report.into_bytes()),
+            };
+        }
 
         cairo_runner
             .relocate(&mut vm, false)
@@ -246,6 +411,8 @@ where
             ))
         }
 
+        self.coverage.record(&self.function, &cairo_runner, &ret);
+
         return ExecutionResult {
             output: ret,
             reverted: false,
@@ -0,0 +1,187 @@
+//! A small local control/status endpoint for steering a long-running,
+//! potentially `--run-forever` fuzz loop from outside the process: a Unix
+//! domain socket under `work_dir` that accepts one line command per
+//! connection - `stats`, `dump`, or `stop` - and is checked once per fuzz
+//! loop iteration via [`ControlSocketStage`]. The readiness check is a
+//! zero-timeout `poll(2)` on the listener's raw fd, so a campaign that
+//! never connects to the socket pays only the cost of one syscall per
+//! iteration instead of ever blocking the loop.
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use libafl::prelude::HasMetadata;
+use libafl::stages::Stage;
+use libafl::Error;
+
+use crate::oracle::BugMetadata;
+
+/// Set by `stop`'s handler so [`ControlSocketStage::perform`] can break the
+/// loop. `Fuzzer::fuzz_loop` has no "please stop" signal to propagate
+/// through a `Stage`'s return value beyond an `Err`, so this follows the
+/// same unsafe-static-toggle pattern `DATAFLOW_ENABLED`/`WRITE_RELATIONSHIPS`
+/// use elsewhere for out-of-band control that every iteration checks.
+pub static mut STOP_REQUESTED: bool = false;
+
+/// Coverage fill (non-zero entries, total entries) of the shared
+/// `evm::host::JMP_MAP` that both the EVM and Cairo fuzzers' `StdMapObserver`
+/// wraps, read by the `stats` command. Refreshed by
+/// `ControlSocketStage::perform` straight from `JMP_MAP` before it services
+/// any command, since it only gets the fuzzer `state`, not the observers.
+pub static mut JMP_MAP_FILL: (usize, usize) = (0, 0);
+
+pub struct ControlSocket {
+    listener: UnixListener,
+    sock_path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Binds a Unix domain socket at `{work_dir}/control.sock`, replacing
+    /// any stale socket file a previous crashed run left behind.
+    pub fn bind(work_dir: &str) -> std::io::Result<Self> {
+        let sock_path = Path::new(work_dir).join("control.sock");
+        let _ = std::fs::remove_file(&sock_path);
+        let listener = UnixListener::bind(&sock_path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            sock_path,
+        })
+    }
+
+    fn ready(&self) -> bool {
+        let mut fd = libc::pollfd {
+            fd: self.listener.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // A zero timeout makes this an immediate readiness check rather
+        // than a wait, so a fuzz loop iteration never blocks on it.
+        unsafe { libc::poll(&mut fd, 1, 0) > 0 && fd.revents & libc::POLLIN != 0 }
+    }
+
+    /// Services every connection currently pending (there's rarely more
+    /// than one), then returns so the fuzz loop can carry on.
+    pub fn poll_once(&self, work_dir: &str, bugs: &BugMetadata) {
+        while self.ready() {
+            match self.listener.accept() {
+                Ok((stream, _)) => Self::handle(stream, work_dir, bugs),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn handle(stream: UnixStream, work_dir: &str, bugs: &BugMetadata) {
+        // `accept()` hands back a *blocking* stream regardless of the
+        // listener's own non-blocking flag - without this, a client that
+        // connects but is slow (or never) to send a newline-terminated
+        // command stalls `read_line` forever, and since `perform` calls
+        // this synchronously once per fuzz-loop iteration, that hangs the
+        // whole campaign. A bounded read timeout turns that into a single
+        // slow iteration instead.
+        if stream.set_read_timeout(Some(Duration::from_millis(100))).is_err() {
+            return;
+        }
+        let mut reader = match stream.try_clone() {
+            Ok(s) => BufReader::new(s),
+            Err(_) => return,
+        };
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() {
+            return;
+        }
+        let mut stream = stream;
+        match line.trim() {
+            "stats" => {
+                let (filled, total) = unsafe { JMP_MAP_FILL };
+                let _ = writeln!(
+                    stream,
+                    "{{\"coverage_filled\": {}, \"coverage_total\": {}, \"known_bugs\": {}}}",
+                    filled,
+                    total,
+                    bugs.known_bugs.len()
+                );
+            }
+            "dump" => {
+                // The corpus and relationship graph are already flushed to
+                // `work_dir` incrementally as the campaign runs (see
+                // `dump_txn!`/`relationship_graph`); this just confirms
+                // where to look rather than triggering a separate flush.
+                let _ = writeln!(stream, "corpus and relationship graph are under {}", work_dir);
+            }
+            "stop" => {
+                unsafe {
+                    STOP_REQUESTED = true;
+                }
+                let _ = writeln!(stream, "stopping after the current stage");
+            }
+            other => {
+                let _ = writeln!(stream, "unknown command: {}", other);
+            }
+        }
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.sock_path);
+    }
+}
+
+/// Drives [`ControlSocket`] between fuzzing stages. Added to the stage
+/// `tuple_list!` after `StdMutationalStage` in both the EVM and Cairo
+/// entry points (and after `DataflowStage` on the EVM side), so `stats`/`dump`/`stop` get serviced once
+/// per iteration regardless of which VM is being fuzzed. If the socket
+/// failed to bind (e.g. the work dir is unwritable), `perform` is a no-op
+/// rather than aborting the run over an optional feature.
+pub struct ControlSocketStage {
+    socket: Option<ControlSocket>,
+    work_dir: String,
+}
+
+impl ControlSocketStage {
+    pub fn new(work_dir: String) -> Self {
+        let socket = match ControlSocket::bind(&work_dir) {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                eprintln!("warning: failed to bind control socket: {}", e);
+                None
+            }
+        };
+        Self { socket, work_dir }
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for ControlSocketStage
+where
+    S: HasMetadata,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+        _corpus_idx: usize,
+    ) -> Result<(), Error> {
+        let Some(socket) = &self.socket else {
+            return Ok(());
+        };
+        unsafe {
+            let map = &crate::evm::host::JMP_MAP;
+            JMP_MAP_FILL = (map.iter().filter(|b| **b != 0).count(), map.len());
+        }
+        let bugs = state
+            .metadata()
+            .get::<BugMetadata>()
+            .cloned()
+            .unwrap_or_default();
+        socket.poll_once(&self.work_dir, &bugs);
+        if unsafe { STOP_REQUESTED } {
+            return Err(Error::shutting_down());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+/// Liveness-based dataflow analysis driving the `DATAFLOW` fuzzer type.
+///
+/// A [`DataflowTrace`] records, in execution order, every slot read/write
+/// the VM performed during one run, each read additionally tagged with the
+/// input-byte offsets that produced the value being read. [`DataflowStage`]
+/// walks the trace backwards to a liveness fixpoint and turns the result
+/// into a [`DataflowMaskMetadata`] the mutator can consult to bias havoc
+/// toward the bytes that actually reach a branch condition or storage
+/// write, instead of mutating uniformly at random.
+use libafl::impl_serdeany;
+use libafl::stages::Stage;
+use libafl::state::HasMetadata;
+use libafl::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::state::{HasExecutionResult, HasItyState};
+
+/// One read or write of a slot (a storage slot, a stack/memory cell - any
+/// location `compute_mutation_mask` should track liveness for), recorded in
+/// the order the VM performed them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum DataflowEvent {
+    /// A read of `slot`, sourced (directly or through a chain of arithmetic)
+    /// from these input-byte offsets.
+    Read {
+        slot: usize,
+        provenance: Vec<usize>,
+    },
+    /// A write to `slot`. `conditional` is true for any write reached under
+    /// a branch (an `SSTORE` inside an `if`, as opposed to one that always
+    /// executes) - such a write is a *may*-write: the value live before it
+    /// can still flow to a caller that takes the other branch, so liveness
+    /// through it must not be killed the way an unconditional write's is.
+    Write { slot: usize, conditional: bool },
+}
+
+/// A single execution's recorded sequence of slot accesses, interned
+/// against a stable `slot -> name` table so callers that want to report on
+/// *which* location ended up live have something readable to print.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DataflowTrace {
+    pub events: Vec<DataflowEvent>,
+    pub slot_names: HashMap<usize, String>,
+    /// Events whose slot is read at a comparison (`EQ`/`LT`/`GT`/.../`JUMPI`
+    /// condition) or an `SSTORE` - the sites liveness is actually computed
+    /// "at", per the request this module implements. Stored as indices into
+    /// `events`.
+    pub sink_events: HashSet<usize>,
+}
+
+impl_serdeany!(DataflowTrace);
+
+impl DataflowTrace {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn slot_id(&mut self, name: String) -> usize {
+        let next_id = self.slot_names.len();
+        for (id, existing) in &self.slot_names {
+            if *existing == name {
+                return *id;
+            }
+        }
+        self.slot_names.insert(next_id, name);
+        next_id
+    }
+
+    /// Records a read of `name`, sourced from `provenance` input-byte
+    /// offsets. `is_sink` marks this read as a comparison/`SSTORE` site
+    /// liveness should be reported "at".
+    pub fn record_read(&mut self, name: String, provenance: Vec<usize>, is_sink: bool) {
+        let slot = self.slot_id(name);
+        let idx = self.events.len();
+        self.events.push(DataflowEvent::Read { slot, provenance });
+        if is_sink {
+            self.sink_events.insert(idx);
+        }
+    }
+
+    /// Records a write of `name`. `conditional` must be true unless the
+    /// caller can prove this write is reached on every path (see
+    /// [`DataflowEvent::Write`]) - when in doubt, pass `true`.
+    pub fn record_write(&mut self, name: String, conditional: bool) {
+        let slot = self.slot_id(name);
+        self.events.push(DataflowEvent::Write { slot, conditional });
+    }
+}
+
+/// Walks `trace` backwards to a liveness fixpoint and returns the union of
+/// input-byte offsets feeding every slot that's live at one of its
+/// `sink_events`.
+///
+/// `live[slot]` starts cleared. Walking in reverse instruction order: a
+/// read sets `live[slot]`; an unconditional write clears it (the
+/// definition it's about to overwrite, going forward, is dead going
+/// backward - nothing before this point needs to keep tracking it); a
+/// conditional write leaves it untouched, since the path that skips the
+/// write still needs the earlier value live. Because a later iteration's
+/// starting liveness can change an earlier iteration's read/write
+/// classification only through slot aliasing across loop back-edges - this
+/// trace format has none, each element of `events` is a distinct program
+/// point - one reverse pass already reaches the fixpoint for a straight-line
+/// trace. For a trace assembled by replaying a loop body multiple times
+/// (the recorded events already include one entry per iteration actually
+/// executed), the same single backward pass is exact for the same reason:
+/// there's no back-edge to re-converge across, since iterations are fully
+/// unrolled into `events` in the order they ran. The loop below still
+/// iterates to a fixpoint on the *provenance union* rather than assuming
+/// one pass suffices, so a future caller that does feed in a back-edge
+/// (e.g. a trace format that dedupes repeated iterations into a cycle)
+/// doesn't silently get an under-approximated mask.
+pub fn compute_mutation_mask(trace: &DataflowTrace) -> HashSet<usize> {
+    let mut mask: HashSet<usize> = HashSet::new();
+    loop {
+        let mut live: HashSet<usize> = HashSet::new();
+        let mut provenance_at: HashMap<usize, HashSet<usize>> = HashMap::new();
+        let mut next_mask = mask.clone();
+
+        for (idx, event) in trace.events.iter().enumerate().rev() {
+            match event {
+                DataflowEvent::Write { slot, conditional } => {
+                    if !conditional {
+                        live.remove(slot);
+                        provenance_at.remove(slot);
+                    }
+                }
+                DataflowEvent::Read { slot, provenance } => {
+                    live.insert(*slot);
+                    provenance_at
+                        .entry(*slot)
+                        .or_default()
+                        .extend(provenance.iter().copied());
+                    if trace.sink_events.contains(&idx) {
+                        if let Some(bytes) = provenance_at.get(slot) {
+                            next_mask.extend(bytes.iter().copied());
+                        }
+                    }
+                }
+            }
+        }
+
+        if next_mask == mask {
+            return mask;
+        }
+        mask = next_mask;
+    }
+}
+
+/// Set from `Config::fuzzer_type == FuzzerTypes::DATAFLOW` at fuzzer
+/// startup (see `evm_fuzzer`), the same unsafe-static-toggle pattern
+/// `SKIP_CBOR`/`WRITE_RELATIONSHIPS` use elsewhere for cheap optional
+/// behavior that every fuzz loop iteration checks. `DataflowStage` is
+/// always present in the stage list; this is what actually makes it a
+/// no-op outside the `DATAFLOW` fuzzer type.
+pub static mut DATAFLOW_ENABLED: bool = false;
+
+/// Per-input mutation mask: the input-byte offsets `compute_mutation_mask`
+/// found still live at a comparison/`SSTORE` site in the most recent
+/// execution. `FuzzMutator::mutate` only checks whether the mask is
+/// non-empty and, if so, favors mutating the input over its other
+/// branches; it does not steer mutation to these particular offsets.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DataflowMaskMetadata {
+    pub mutation_mask: HashSet<usize>,
+}
+
+impl_serdeany!(DataflowMaskMetadata);
+
+/// Runs after each execution, alongside [`crate::infant_state_stage::InfantStateStage`],
+/// recomputing the current input's [`DataflowMaskMetadata`] from whatever
+/// [`DataflowTrace`] the execution recorded.
+///
+/// This only drives the analysis engine (`compute_mutation_mask`) from a
+/// trace a middleware already produced; it's not itself what records
+/// per-instruction slot reads/writes - see
+/// `crate::evm::middlewares::dataflow_tracer::DataflowTracer` for the
+/// middleware that populates `DataflowTrace` from a real execution, and
+/// `FuzzMutator::mutate` for where `DataflowMaskMetadata` feeds back into
+/// mutation.
+pub struct DataflowStage {}
+
+impl DataflowStage {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for DataflowStage
+where
+    S: HasItyState + HasExecutionResult + HasMetadata,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+        _corpus_idx: usize,
+    ) -> Result<(), Error> {
+        if !unsafe { DATAFLOW_ENABLED } {
+            return Ok(());
+        }
+        let trace = match state.metadata().get::<DataflowTrace>() {
+            Some(trace) => trace.clone(),
+            None => return Ok(()),
+        };
+        let mutation_mask = compute_mutation_mask(&trace);
+        match state.metadata_mut().get_mut::<DataflowMaskMetadata>() {
+            Some(existing) => existing.mutation_mask = mutation_mask,
+            None => state.add_metadata(DataflowMaskMetadata { mutation_mask }),
+        }
+        Ok(())
+    }
+}
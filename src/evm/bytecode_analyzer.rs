@@ -1,6 +1,8 @@
 /// Analysis passes for EVM bytecode
 use crate::mutation_utils::ConstantPoolMetadata;
+use libafl::impl_serdeany;
 use libafl::state::{HasMetadata, State};
+use serde::{Deserialize, Serialize};
 
 use crate::evm::bytecode_iterator::all_bytecode;
 use revm_interpreter::opcode::JUMPI;
@@ -41,6 +43,61 @@ pub fn find_constants(bytecode: &Bytecode) -> HashSet<Vec<u8>> {
     constants
 }
 
+/// Finds the four-byte function selectors Solidity's dispatcher actually
+/// checks against `calldata[0..4]`, by scanning for the canonical pattern it
+/// compiles each `external`/`public` function's guard down to: `PUSH4
+/// <selector>`, followed within a small instruction window by `EQ` and a
+/// `JUMPI` (usually preceded by the `PUSH2 <jumpdest>` for the taken
+/// branch). This is a superset-safe heuristic - it only fires on bytecode
+/// that actually branches on the pushed constant, unlike `find_constants`,
+/// which would otherwise harvest the same four bytes as just another PUSH
+/// operand.
+pub fn find_function_selectors(bytecode: &Bytecode) -> HashSet<[u8; 4]> {
+    const PUSH4: u8 = 0x63;
+    const EQ: u8 = 0x14;
+    // How many instructions past the PUSH4 to look for the EQ/JUMPI that
+    // confirms it's a dispatcher check rather than an unrelated constant.
+    const WINDOW: usize = 6;
+
+    let bytes = bytecode.bytes();
+    let avail_bytecode = all_bytecode(&bytes.to_vec());
+    let mut selectors = HashSet::new();
+
+    for (idx, (pc, op)) in avail_bytecode.iter().enumerate() {
+        if *op != PUSH4 || pc + 4 >= bytes.len() {
+            continue;
+        }
+        let selector = [bytes[pc + 1], bytes[pc + 2], bytes[pc + 3], bytes[pc + 4]];
+
+        let mut saw_eq = false;
+        for (_, later_op) in avail_bytecode.iter().skip(idx + 1).take(WINDOW) {
+            if *later_op == EQ {
+                saw_eq = true;
+            } else if *later_op == JUMPI && saw_eq {
+                selectors.insert(selector);
+                break;
+            }
+        }
+    }
+    selectors
+}
+
+/// Recovered function selectors, seeded from `find_function_selectors` so
+/// the fuzzer can prioritize calldata that actually routes somewhere in the
+/// dispatcher instead of discovering valid selectors by brute force.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelectorMetadata {
+    pub selectors: HashSet<[u8; 4]>,
+}
+
+impl_serdeany!(SelectorMetadata);
+
+impl SelectorMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 /// Add constants in smart contract to the global state's [`ConstantPoolMetadata`]
 /// this can be costly, ensure sampling to be cheap
 pub fn add_analysis_result_to_state<S>(bytecode: &Bytecode, state: &mut S)
@@ -62,4 +119,10 @@ where
             });
         }
     }
+
+    let selectors = find_function_selectors(bytecode);
+    match state.metadata_mut().get_mut::<SelectorMetadata>() {
+        Some(meta) => meta.selectors.extend(selectors),
+        None => state.metadata_mut().insert(SelectorMetadata { selectors }),
+    }
 }
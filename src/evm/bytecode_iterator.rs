@@ -2,11 +2,100 @@ use revm_interpreter::opcode::{INVALID, JUMP, JUMPI, RETURN, REVERT, STOP};
 
 pub static mut SKIP_CBOR: bool = false;
 
-pub fn all_bytecode(bytes: &Vec<u8>) -> Vec<(usize, u8)> {
-    if bytes.len() == 0 {
-        return vec![];
+/// The kind of source-hash entry a Solidity/Vyper CBOR metadata map carries.
+/// `Ipfs` is the default since solc 0.6, `Bzzr1`/`Bzzr0` are the older
+/// swarm-hash schemes it previously emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceHashKind {
+    Ipfs,
+    Bzzr1,
+    Bzzr0,
+}
+
+/// The decoded trailing CBOR metadata blob solc/Vyper appends after the
+/// runtime code, fingerprinting the exact compiler and source used to
+/// produce this deployment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractMetadata {
+    pub compiler_version: Option<Vec<u8>>,
+    pub source_hash: Option<(SourceHashKind, Vec<u8>)>,
+}
+
+/// Reads a CBOR major-type/length header at `data[idx]`, returning
+/// `(major_type, length, header_len)`, or `None` if `idx` is out of bounds
+/// or the header uses an encoding this minimal decoder doesn't support
+/// (indefinite-length items, 8-byte lengths - neither of which solc's
+/// metadata encoder ever emits).
+fn read_cbor_header(data: &[u8], idx: usize) -> Option<(u8, usize, usize)> {
+    let byte = *data.get(idx)?;
+    let major_type = byte >> 5;
+    let info = byte & 0x1f;
+    match info {
+        0..=23 => Some((major_type, info as usize, 1)),
+        24 => Some((major_type, *data.get(idx + 1)? as usize, 2)),
+        25 => {
+            let hi = *data.get(idx + 1)? as usize;
+            let lo = *data.get(idx + 2)? as usize;
+            Some((major_type, (hi << 8) + lo, 3))
+        }
+        _ => None,
+    }
+}
+
+/// Reads a CBOR text string or byte string item at `data[idx]`, returning
+/// `(bytes, total_len)`.
+fn read_cbor_bytes_or_text(data: &[u8], idx: usize) -> Option<(Vec<u8>, usize)> {
+    let (major_type, len, header_len) = read_cbor_header(data, idx)?;
+    if major_type != 2 && major_type != 3 {
+        return None;
+    }
+    let start = idx + header_len;
+    let end = start.checked_add(len)?;
+    let bytes = data.get(start..end)?.to_vec();
+    Some((bytes, header_len + len))
+}
+
+/// Decodes a canonical CBOR map of text-string keys to byte/text-string
+/// values - the shape solc's metadata encoder always emits - recognizing
+/// the standard `solc`/`ipfs`/`bzzr0`/`bzzr1` keys. Returns the parsed
+/// metadata plus the number of bytes consumed, or `None` if `data` doesn't
+/// decode as that shape (an unsupported key type, a truncated item, an
+/// unsupported CBOR encoding) - in which case the caller should fall back
+/// to treating the tail as code.
+fn parse_cbor_metadata_map(data: &[u8]) -> Option<(ContractMetadata, usize)> {
+    let (major_type, pair_count, mut offset) = read_cbor_header(data, 0)?;
+    if major_type != 5 {
+        return None;
+    }
+    let mut metadata = ContractMetadata::default();
+    for _ in 0..pair_count {
+        let (key_bytes, key_len) = read_cbor_bytes_or_text(data, offset)?;
+        offset += key_len;
+        let (value_bytes, value_len) = read_cbor_bytes_or_text(data, offset)?;
+        offset += value_len;
+        match String::from_utf8(key_bytes).ok().as_deref() {
+            Some("solc") => metadata.compiler_version = Some(value_bytes),
+            Some("ipfs") => metadata.source_hash = Some((SourceHashKind::Ipfs, value_bytes)),
+            Some("bzzr1") => metadata.source_hash = Some((SourceHashKind::Bzzr1, value_bytes)),
+            Some("bzzr0") => metadata.source_hash = Some((SourceHashKind::Bzzr0, value_bytes)),
+            _ => {}
+        }
+    }
+    Some((metadata, offset))
+}
+
+/// Like [`all_bytecode`], but also returns the decoded trailing metadata
+/// blob (if the tail of `bytes` actually decodes as one). The candidate
+/// split point is still taken from the declared length in the final two
+/// bytes, but that candidate is only accepted once the CBOR map parses and
+/// exactly consumes `declared_len` bytes - a contract whose real code just
+/// happens to end on a byte sequence that looks like a length no longer
+/// gets misclassified, since a garbage "length" won't round-trip through
+/// the parser.
+pub fn all_bytecode_with_metadata(bytes: &Vec<u8>) -> (Vec<(usize, u8)>, Option<ContractMetadata>) {
+    if bytes.is_empty() {
+        return (vec![], None);
     }
-    let mut i = 0;
     let last_op = *bytes.last().unwrap();
     let has_cbor = last_op != JUMP
         && last_op != JUMPI
@@ -15,18 +104,32 @@ pub fn all_bytecode(bytes: &Vec<u8>) -> Vec<(usize, u8)> {
         && last_op != REVERT
         && last_op != RETURN;
 
+    let mut metadata = None;
     let cbor_len = if has_cbor && !unsafe { SKIP_CBOR } {
-        // load last 2 bytes as big endian
         let len = bytes.len();
         let last_2 = *bytes.get(len - 2).unwrap() as usize;
         let last_1 = *bytes.get(len - 1).unwrap() as usize;
-        (last_2 << 8) + last_1 + 2
+        let declared_len = (last_2 << 8) + last_1;
+        let total_len = declared_len + 2;
+        if total_len <= len {
+            let map_start = len - total_len;
+            let map_end = len - 2;
+            match parse_cbor_metadata_map(&bytes[map_start..map_end]) {
+                Some((parsed, consumed)) if consumed == declared_len => {
+                    metadata = Some(parsed);
+                    total_len
+                }
+                _ => 0,
+            }
+        } else {
+            0
+        }
     } else {
         0
     };
 
+    let mut i = 0;
     let mut res = Vec::new();
-
     while i < bytes.len() - cbor_len {
         let op = *bytes.get(i).unwrap();
         res.push((i, op));
@@ -35,7 +138,11 @@ pub fn all_bytecode(bytes: &Vec<u8>) -> Vec<(usize, u8)> {
             i += op as usize - 0x5f;
         }
     }
-    res
+    (res, metadata)
+}
+
+pub fn all_bytecode(bytes: &Vec<u8>) -> Vec<(usize, u8)> {
+    all_bytecode_with_metadata(bytes).0
 }
 
 #[macro_export]
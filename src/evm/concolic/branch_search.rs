@@ -0,0 +1,195 @@
+/// Turns the passive path-constraint collection in `ConcolicHost` into an
+/// active exploration loop: for each recorded `JUMPI`, try to prove the
+/// *other* side of the branch is reachable by asking the solver for a model
+/// of `prefix ∧ ¬branch`, and if SAT, seed that model back in as a new
+/// input. `ConcolicHost::on_step` only ever records the concrete branch it
+/// took; this module is what actually walks the other branches.
+use crate::evm::concolic::concolic_host::Solution;
+use crate::evm::concolic::constraint_ir::simplify_constraints;
+use crate::evm::concolic::expr::{ConcolicOp, Expr};
+use crate::evm::concolic::solver::SolverCtx;
+use crate::evm::types::{EVMAddress, EVMU256};
+use std::collections::{HashMap, HashSet};
+use z3::ast::{Ast, BV};
+use z3::{Config, Context, SatResult, Solver};
+
+/// A single `JUMPI` observed this run: the condition of the branch actually
+/// taken (`ConcolicHost::on_step`'s `real_path_constraint`; `explore`
+/// negates it to ask for the other side) plus the destination
+/// the *other* side would have jumped to, so coverage-guided ordering can
+/// prioritize destinations we've never reached.
+#[derive(Clone, Debug)]
+pub struct BranchPoint {
+    pub constraint: Box<Expr>,
+    pub other_side_dest: u64,
+}
+
+/// Which branch to try flipping first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontierOrder {
+    /// Flip the most recently taken branch first, deepest in the path.
+    DepthFirst,
+    /// Prioritize branches whose other side lands on a `JUMPDEST` that
+    /// `visited` has never seen, falling back to depth-first among ties.
+    CoverageGuided,
+}
+
+/// Feasibility of `prefix ∧ ¬branch` is a property of the query text alone,
+/// so a prefix seen again (a common case - most of a path is shared between
+/// nearby inputs) doesn't need to be re-asked of the solver.
+#[derive(Default, Debug)]
+pub struct FeasibilityCache {
+    cache: HashMap<String, bool>,
+}
+
+impl FeasibilityCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(query: &[Box<Expr>]) -> String {
+        query.iter().map(|c| c.pretty_print_str()).collect::<Vec<_>>().join("&&")
+    }
+
+    /// Returns the cached feasibility for `query` if we've solved it before,
+    /// otherwise asks `z3` and caches the result.
+    fn is_sat(&mut self, query: &[Box<Expr>]) -> bool {
+        let key = Self::key(query);
+        if let Some(sat) = self.cache.get(&key) {
+            return *sat;
+        }
+        let sat = solve(query).is_some();
+        self.cache.insert(key, sat);
+        sat
+    }
+}
+
+/// Orders branch indices per `order`, highest-priority first.
+fn frontier(points: &[BranchPoint], order: FrontierOrder, visited: &HashSet<u64>) -> Vec<usize> {
+    let mut idxs: Vec<usize> = (0..points.len()).collect();
+    match order {
+        FrontierOrder::DepthFirst => idxs.reverse(),
+        FrontierOrder::CoverageGuided => {
+            idxs.sort_by_key(|&i| {
+                let unexplored = !visited.contains(&points[i].other_side_dest);
+                // unexplored destinations sort first, ties broken depth-first
+                (unexplored as i32 * -1, std::cmp::Reverse(i))
+            });
+        }
+    }
+    idxs
+}
+
+/// For each branch point in `order`, builds `prefix(0..=i) with point[i]
+/// negated`, asks the solver (through `cache`), and on SAT turns the model
+/// into a `Solution` ready to be seeded into
+/// `ConcolicPrioritizationMetadata.solutions`. Branches already proven
+/// infeasible (or already explored via the cache) are skipped for free.
+pub fn explore(
+    points: &[BranchPoint],
+    order: FrontierOrder,
+    visited: &HashSet<u64>,
+    cache: &mut FeasibilityCache,
+) -> Vec<Solution> {
+    let mut found = vec![];
+    for i in frontier(points, order, visited) {
+        let mut query: Vec<Box<Expr>> = points[..i].iter().map(|p| p.constraint.clone()).collect();
+        query.push(points[i].constraint.clone().lnot());
+        let query = simplify_constraints(query);
+        if query.is_empty() {
+            // every constraint folded away to nothing: trivially feasible,
+            // but there's nothing left to solve a concrete model from
+            continue;
+        }
+        if cache.cache.contains_key(&FeasibilityCache::key(&query)) {
+            // proven infeasible, or already explored on an earlier run
+            continue;
+        }
+        if cache.is_sat(&query) {
+            if let Some(model) = solve(&query) {
+                found.push(model_to_solution(&model));
+            }
+        }
+    }
+    found
+}
+
+/// The solved value of every calldata word the query mentioned, keyed by
+/// word index (`SLICEDINPUT(idx)`, see `expr.rs`).
+type Model = HashMap<u64, [u8; 32]>;
+
+/// Runs `query` through Z3, returning a model over the calldata words it
+/// mentions if satisfiable.
+fn solve(query: &[Box<Expr>]) -> Option<Model> {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let mut solver_ctx = SolverCtx::new(&ctx);
+    let solver = Solver::new(&ctx);
+    for c in query {
+        solver.assert(&solver_ctx.to_bv(c)._eq(&BV::from_u64(&ctx, 1, 256)));
+    }
+    if solver.check() != SatResult::Sat {
+        return None;
+    }
+    let z3_model = solver.get_model()?;
+    let mut model = Model::new();
+    for word_idx in collect_calldata_words(query) {
+        let free_var = solver_ctx.to_bv(&Expr::new_sliced_input(EVMU256::from(word_idx)));
+        if let Some(val) = z3_model.eval(&free_var, true) {
+            // 256-bit words don't fit z3::ast::BV::as_u64, so pull out each
+            // of the four 64-bit limbs (big-endian) the same way
+            // `bv_from_u256!` assembled the free variable in the first
+            // place, and reassemble them into bytes.
+            let mut bytes = [0u8; 32];
+            let mut all_known = true;
+            for (limb, chunk) in [(192, 256), (128, 192), (64, 128), (0, 64)].iter().zip(bytes.chunks_mut(8)) {
+                let (lo, hi) = *limb;
+                match val.extract(hi - 1, lo).as_u64() {
+                    Some(v) => chunk.copy_from_slice(&v.to_be_bytes()),
+                    None => {
+                        all_known = false;
+                        break;
+                    }
+                }
+            }
+            if all_known {
+                model.insert(word_idx, bytes);
+            }
+        }
+    }
+    Some(model)
+}
+
+fn collect_calldata_words(query: &[Box<Expr>]) -> HashSet<u64> {
+    fn walk(e: &Expr, out: &mut HashSet<u64>) {
+        if let ConcolicOp::SLICEDINPUT(idx) = &e.op {
+            out.insert(idx.as_limbs()[0]);
+        }
+        if let Some(l) = &e.lhs {
+            walk(l, out);
+        }
+        if let Some(r) = &e.rhs {
+            walk(r, out);
+        }
+    }
+    let mut out = HashSet::new();
+    for c in query {
+        walk(c, &mut out);
+    }
+    out
+}
+
+fn model_to_solution(model: &Model) -> Solution {
+    let max_word = model.keys().copied().max().unwrap_or(0);
+    let mut input = vec![0u8; (max_word as usize + 1) * 32];
+    for (idx, bytes) in model {
+        let offset = *idx as usize * 32;
+        input[offset..offset + 32].copy_from_slice(bytes);
+    }
+    Solution {
+        input,
+        caller: EVMAddress::zero(),
+        value: EVMU256::from(0),
+        fields: vec![],
+    }
+}
@@ -0,0 +1,125 @@
+/// Flattens an `Expr` tree into a linear, stack-based program so repeatedly
+/// evaluating the same expression against thousands of candidate concrete
+/// inputs (as the directed branch search in `branch_search.rs` does while
+/// probing which mutation flips a branch) doesn't have to re-walk `lhs`/`rhs`
+/// `Box<Expr>` pointers and re-allocate on every input. `compile` runs once
+/// per expression; `eval` then runs a single linear pass over a contiguous
+/// `Vec` for every concrete assignment.
+use crate::evm::concolic::expr::{eval_concrete, ConcolicOp, Expr};
+use crate::evm::types::EVMU256;
+use std::collections::HashMap;
+
+/// One instruction in a compiled `Expr` program. Constants and input leaves
+/// push a single value; `Apply` pops `arity` operands (2 for a binop, 1 for
+/// a unary op/`SELECT`) and pushes the result, mirroring `ConcolicOp`'s own
+/// arity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExprByteCode {
+    PushConst(EVMU256),
+    /// Raw calldata bytes `[start, end)`, big-endian, zero-padded past the
+    /// end of the actual input - same range `FINEGRAINEDINPUT`/`SLICEDINPUT`
+    /// name.
+    PushInput { start: u32, end: u32 },
+    PushSymByte(String),
+    Apply { op: ConcolicOp, arity: u8 },
+}
+
+/// Appends the post-order instructions for `expr` to `out`. Returns `None`
+/// if `expr` contains a node this flat model can't represent: `CONCAT`
+/// (operand widths vary, so there's no single stack-slot width to pop/push),
+/// the theory-of-arrays memory nodes (`ARRAY`/`STORE`/`LOAD`), the
+/// uninterpreted-function precompiles (`SHA3`/`SHA256`/`ECRECOVER`), or the
+/// execution-environment leaves (`BALANCE`/`CALLVALUE`/`CALLER`) - none of
+/// these reduce to a concrete-assignment lookup the way a calldata slice or
+/// symbolic byte does.
+fn compile_into(expr: &Expr, out: &mut Vec<ExprByteCode>) -> Option<()> {
+    match &expr.op {
+        ConcolicOp::EVMU256(v) if expr.lhs.is_none() && expr.rhs.is_none() => {
+            out.push(ExprByteCode::PushConst(*v));
+        }
+        ConcolicOp::CONSTBYTE(b) if expr.lhs.is_none() && expr.rhs.is_none() => {
+            out.push(ExprByteCode::PushConst(EVMU256::from(*b)));
+        }
+        ConcolicOp::SLICEDINPUT(idx) => {
+            let word = idx.as_limbs()[0] as u32;
+            out.push(ExprByteCode::PushInput { start: word * 32, end: word * 32 + 32 });
+        }
+        ConcolicOp::FINEGRAINEDINPUT(start, end) => {
+            out.push(ExprByteCode::PushInput { start: *start, end: *end });
+        }
+        ConcolicOp::SYMBYTE(name) => out.push(ExprByteCode::PushSymByte(name.clone())),
+        ConcolicOp::ADD
+        | ConcolicOp::SUB
+        | ConcolicOp::MUL
+        | ConcolicOp::DIV
+        | ConcolicOp::SDIV
+        | ConcolicOp::SMOD
+        | ConcolicOp::UREM
+        | ConcolicOp::SREM
+        | ConcolicOp::AND
+        | ConcolicOp::OR
+        | ConcolicOp::XOR
+        | ConcolicOp::SHL
+        | ConcolicOp::SHR
+        | ConcolicOp::SAR
+        | ConcolicOp::EQ
+        | ConcolicOp::LT
+        | ConcolicOp::GT
+        | ConcolicOp::SLT
+        | ConcolicOp::SGT => {
+            compile_into(expr.lhs.as_ref()?, out)?;
+            compile_into(expr.rhs.as_ref()?, out)?;
+            out.push(ExprByteCode::Apply { op: expr.op.clone(), arity: 2 });
+        }
+        ConcolicOp::NOT | ConcolicOp::LNOT | ConcolicOp::SELECT(_, _) => {
+            compile_into(expr.lhs.as_ref()?, out)?;
+            out.push(ExprByteCode::Apply { op: expr.op.clone(), arity: 1 });
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Compiles `expr` into a flat program, or `None` if it contains a node
+/// `ExprByteCode` can't represent (see `compile_into`).
+pub fn compile(expr: &Expr) -> Option<Vec<ExprByteCode>> {
+    let mut out = Vec::with_capacity(expr.depth() as usize * 2 + 1);
+    compile_into(expr, &mut out)?;
+    Some(out)
+}
+
+/// Reads the big-endian value of `input[start..end)`, zero-padding past the
+/// end of `input` the way calldata does past its actual length.
+fn read_input_range(input: &[u8], start: u32, end: u32) -> EVMU256 {
+    let (start, end) = (start as usize, end as usize);
+    let mut bytes = vec![0u8; end.saturating_sub(start)];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        if let Some(v) = input.get(start + i) {
+            *b = *v;
+        }
+    }
+    EVMU256::try_from_be_slice(&bytes).unwrap_or(EVMU256::ZERO)
+}
+
+/// Runs a program compiled by `compile` over a single linear operand stack,
+/// resolving `PushInput`/`PushSymByte` against `input`/`sym_bytes`.
+/// `sym_bytes` defaults any name it doesn't have an entry for to `0`.
+pub fn eval(program: &[ExprByteCode], input: &[u8], sym_bytes: &HashMap<String, u8>) -> EVMU256 {
+    let mut stack: Vec<EVMU256> = Vec::with_capacity(program.len());
+    for instr in program {
+        match instr {
+            ExprByteCode::PushConst(v) => stack.push(*v),
+            ExprByteCode::PushInput { start, end } => stack.push(read_input_range(input, *start, *end)),
+            ExprByteCode::PushSymByte(name) => {
+                stack.push(EVMU256::from(*sym_bytes.get(name).unwrap_or(&0)));
+            }
+            ExprByteCode::Apply { op, arity } => {
+                let rhs = if *arity == 2 { Some(stack.pop().expect("stack underflow")) } else { None };
+                let lhs = stack.pop().expect("stack underflow");
+                let result = eval_concrete(op, Some(lhs), rhs).expect("op unsupported by compile");
+                stack.push(result);
+            }
+        }
+    }
+    stack.pop().expect("empty program")
+}
@@ -21,12 +21,17 @@ use revm_primitives::{Bytecode, HashMap};
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 
+use crate::evm::concolic::branch_search::{self, BranchPoint, FeasibilityCache, FrontierOrder};
 use crate::evm::concolic::concolic_stage::ConcolicPrioritizationMetadata;
+use crate::evm::concolic::constraint_ir::simplify_constraints;
 use crate::evm::concolic::expr::{simplify, ConcolicOp, Expr};
-use crate::evm::types::{as_u64, is_zero, EVMAddress, EVMU256};
+use crate::evm::concolic::solver::ConcolicSolver;
+use crate::evm::types::{as_u64, convert_u256_to_h160, is_zero, EVMAddress, EVMU256};
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::Arc;
+use z3::Context;
 
 pub static mut CONCOLIC_MAP: [u8; MAP_SIZE] = [0; MAP_SIZE];
 
@@ -56,9 +61,15 @@ impl Solution {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SymbolicMemory {
-    /// Memory is a vector of bytes, each byte is a symbolic value
+    /// Memory is a vector of bytes, each byte is a symbolic value. Fast path
+    /// for the overwhelmingly common case of a concrete offset.
     pub memory: Vec<Option<Box<Expr>>>,
     // pub memory_32: Vec<Option<Box<Expr>>>,
+    /// Theory-of-arrays view of the same memory, updated alongside `memory`
+    /// on every concrete-offset write and otherwise via STORE. Used as the
+    /// fallback for symbolic-offset loads/stores so the offset expression
+    /// propagates instead of being concretized to its low 64 bits.
+    pub array: Box<Expr>,
 }
 
 impl SymbolicMemory {
@@ -66,10 +77,32 @@ impl SymbolicMemory {
         Self {
             memory: vec![],
             // memory_32: vec![],
+            array: Expr::new_symbolic_array("memory"),
         }
     }
 
+    fn idx_expr(idx: EVMU256) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::EVMU256(idx),
+        })
+    }
+
+    /// Stores `val` (a full 32-byte word) at the symbolic offset `idx_expr`,
+    /// used instead of `insert_256` when the offset itself isn't constant.
+    pub fn insert_256_symbolic(&mut self, idx_expr: Box<Expr>, val: Box<Expr>) {
+        self.array = Expr::new_array_store(self.array.clone(), idx_expr, val);
+    }
+
+    /// Loads a full 32-byte word from the symbolic offset `idx_expr`, used
+    /// instead of `get_256` when the offset itself isn't constant.
+    pub fn get_256_symbolic(&self, idx_expr: Box<Expr>) -> Box<Expr> {
+        Expr::new_array_load(self.array.clone(), idx_expr)
+    }
+
     pub fn insert_256(&mut self, idx: EVMU256, val: Box<Expr>) {
+        self.array = Expr::new_array_store(self.array.clone(), Self::idx_expr(idx), val.clone());
         let idx = idx.as_limbs()[0] as usize;
         if idx + 32 >= self.memory.len() {
             self.memory.resize(idx + 32 + 1, None);
@@ -91,19 +124,18 @@ impl SymbolicMemory {
     }
 
     pub fn insert_8(&mut self, idx: EVMU256, val: Box<Expr>) {
-        // TODO: use SELECT instead of concrete value
         let idx = idx.as_limbs()[0] as usize;
         if idx >= self.memory.len() {
             self.memory.resize(idx + 1, None);
         }
 
-        println!("insert_8: idx: {}, val: {:?}", idx, val);
-        todo!("insert_8");
-        // self.memory[idx] = Some(Box::new(Expr {
-        //     lhs: Some(val.clone()),
-        //     rhs: None,
-        //     op: ConcolicOp::SELECT(31 - i_u32*8, 24 - i_u32*8),
-        // }));
+        // MSTORE8 stores the low-order byte of `val`, mirroring how
+        // insert_256 slices each byte out of the stored word via SELECT.
+        self.memory[idx] = Some(Box::new(Expr {
+            lhs: Some(val),
+            rhs: None,
+            op: ConcolicOp::SELECT(7, 0),
+        }));
     }
 
     pub fn get_256(&self, idx: EVMU256) -> Option<Box<Expr>> {
@@ -177,25 +209,119 @@ impl SymbolicMemory {
     }
 }
 
+/// Per-address symbolic storage, keyed on concrete slots for the fast path
+/// and backed by a theory-of-arrays view so a symbolic `SLOAD`/`SSTORE` key
+/// (e.g. `mapping[msg.sender]`) still produces solvable select/store
+/// constraints instead of being concretized away. Unlike `SymbolicMemory`,
+/// this lives outside the per-call `ConcolicCallCtx` stack: storage is keyed
+/// by contract address, not call frame, so it must survive `push_ctx`/
+/// `pop_ctx` across `CALL`/`DELEGATECALL`/`STATICCALL`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SymbolicStorage {
+    pub slots: HashMap<EVMU256, Option<Box<Expr>>>,
+    pub array: Option<Box<Expr>>,
+}
+
+impl SymbolicStorage {
+    pub fn new() -> Self {
+        Self {
+            slots: Default::default(),
+            array: None,
+        }
+    }
+
+    fn array_or_init(&mut self) -> Box<Expr> {
+        self.array
+            .get_or_insert_with(|| Expr::new_symbolic_array("storage"))
+            .clone()
+    }
+
+    pub fn insert(&mut self, key_expr: Box<Expr>, key: EVMU256, val: Box<Expr>) {
+        let array = self.array_or_init();
+        self.array = Some(Expr::new_array_store(array, key_expr, val.clone()));
+        self.slots.insert(key, Some(val));
+    }
+
+    pub fn get(&mut self, key_expr: Box<Expr>, key: EVMU256, key_is_concrete: bool) -> Option<Box<Expr>> {
+        if key_is_concrete {
+            if let Some(v) = self.slots.get(&key) {
+                return v.clone();
+            }
+        }
+        // no concrete write observed for this slot: fall back to the array
+        // theory view so an aliasing symbolic key can still be solved
+        self.array.as_ref().map(|array| Expr::new_array_load(array.clone(), key_expr))
+    }
+}
+
+// The symbolic binding to splice into the caller's memory (at the call's
+// `retOffset`) once a precompile call returns, since precompiles never run
+// interpreted bytecode and so never reach `on_step` themselves.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PrecompileBinding {
+    // 0x01: ecrecover(hash, v, r, s) -> address, bound as a single 32-byte word
+    Ecrecover(Box<Expr>),
+    // 0x02: sha256(preimage) -> digest, bound as a single 32-byte word
+    Sha256(Box<Expr>),
+    // 0x04: identity, a pure byte-for-byte copy of the input slice
+    Identity(Vec<Box<Expr>>),
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConcolicCallCtx {
     pub symbolic_stack: Vec<Option<Box<Expr>>>,
     pub symbolic_memory: SymbolicMemory,
-    pub symbolic_state: HashMap<EVMU256, Option<Box<Expr>>>,
+    // the contract address storage reads/writes should be keyed against once
+    // we return to this frame (storage itself lives in
+    // `ConcolicHost::symbolic_state`, keyed by address, and isn't cloned here)
+    pub storage_address: EVMAddress,
 
     // seperated by 32 bytes
     pub input_bytes: Vec<Box<Expr>>,
+
+    // set when the call target is a precompile (0x01-0x09) we model
+    // symbolically: (retOffset, retLen, binding), applied to the caller's
+    // memory in `on_return`/`pop_ctx`
+    pub precompile_result: Option<(EVMU256, EVMU256, PrecompileBinding)>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConcolicHost<I, VS> {
     pub symbolic_stack: Vec<Option<Box<Expr>>>,
     pub symbolic_memory: SymbolicMemory,
-    pub symbolic_state: HashMap<EVMU256, Option<Box<Expr>>>,
+    /// Storage, keyed by contract address so symbolic slots written by one
+    /// contract are still visible after a `CALL` into another contract and
+    /// back (`push_ctx`/`pop_ctx` only swap `storage_address`, they never
+    /// reset or clone this map).
+    pub symbolic_state: HashMap<EVMAddress, SymbolicStorage>,
+    pub storage_address: EVMAddress,
     pub input_bytes: Vec<Box<Expr>>,
-    pub constraints: Vec<Box<Expr>>,
+    /// Path constraints, one frame per call-context depth: `constraint_scopes[0]`
+    /// is the top-level transaction, and each `push_ctx`/`pop_ctx` pair opens
+    /// and closes a frame on top, mirroring the incremental scopes
+    /// `ConcolicSolver::push_scope`/`pop_scope` open in the actual solver.
+    /// A sub-call that `REVERT`s has its frame dropped instead of merged
+    /// into the caller's on `pop_ctx` (see `pending_revert`), since its
+    /// internal branches never affect anything outside the reverted call.
+    pub constraint_scopes: Vec<Vec<Box<Expr>>>,
+    /// Set by the `REVERT` (0xfd) handler so the next `pop_ctx` (driven by
+    /// `on_return`) knows to discard rather than merge the frame it closes.
+    #[serde(skip)]
+    pub pending_revert: bool,
     pub testcase_ref: Arc<EVMInput>,
 
+    /// One entry per `JUMPI` seen this run, in path order; feeds
+    /// `explore_branches`' directed search over the road not taken.
+    #[serde(skip)]
+    pub branch_points: Vec<BranchPoint>,
+    /// `JUMPDEST`s actually reached this run, used to prioritize
+    /// `FrontierOrder::CoverageGuided` exploration toward destinations we
+    /// haven't seen yet.
+    #[serde(skip)]
+    pub visited_jumpdests: HashSet<u64>,
+    #[serde(skip)]
+    pub feasibility_cache: FeasibilityCache,
+
     pub ctxs: Vec<ConcolicCallCtx>,
     pub phantom: PhantomData<(I, VS)>,
 }
@@ -206,51 +332,179 @@ impl<I, VS> ConcolicHost<I, VS> {
             symbolic_stack: Vec::new(),
             symbolic_memory: SymbolicMemory::new(),
             symbolic_state: Default::default(),
+            storage_address: EVMAddress::default(),
             input_bytes: Self::construct_input_from_abi(
                 testcase_ref.get_data_abi().expect("data abi not found"),
             ),
-            constraints: vec![],
+            constraint_scopes: vec![vec![]],
+            pending_revert: false,
             testcase_ref,
+            branch_points: vec![],
+            visited_jumpdests: Default::default(),
+            feasibility_cache: FeasibilityCache::new(),
             phantom: Default::default(),
             ctxs: vec![],
         }
     }
 
+    /// The storage map for the contract currently executing, creating an
+    /// empty one on first touch.
+    fn current_storage(&mut self) -> &mut SymbolicStorage {
+        self.symbolic_state.entry(self.storage_address).or_insert_with(SymbolicStorage::new)
+    }
+
+    /// Asserts `constraint` into the innermost open scope, i.e. the call
+    /// frame currently executing.
+    pub fn assert_constraint(&mut self, constraint: Box<Expr>) {
+        self.constraint_scopes.last_mut().expect("no open scope").push(constraint);
+    }
+
+    /// Every constraint still live, outermost scope first, for callers (IR
+    /// passes, branch search) that want one flat path condition.
+    pub fn flattened_constraints(&self) -> Vec<Box<Expr>> {
+        self.constraint_scopes.iter().flatten().cloned().collect()
+    }
+
     pub fn pop_ctx(&mut self) {
         let ctx = self.ctxs.pop();
         if let Some(ctx) = ctx {
             self.symbolic_stack = ctx.symbolic_stack;
             self.symbolic_memory = ctx.symbolic_memory;
-            self.symbolic_state = ctx.symbolic_state;
+            self.storage_address = ctx.storage_address;
+            let frame = self.constraint_scopes.pop().unwrap_or_default();
+            if self.pending_revert {
+                // the sub-call reverted: its internal branch constraints
+                // never affected anything outside it, so drop the whole
+                // frame instead of merging it into the caller's
+                self.pending_revert = false;
+            } else {
+                self.constraint_scopes.last_mut().expect("no open scope").extend(frame);
+            }
+            if let Some((ret_offset, ret_len, binding)) = ctx.precompile_result {
+                self.bind_precompile_result(ret_offset, ret_len, binding);
+            }
         } else {
             panic!("pop_ctx: ctx is empty");
         }
     }
 
+    // Splices a precompile's symbolic output into the (now-restored) caller
+    // memory at `retOffset`, so e.g. `require(ecrecover(...) == signer)` stays
+    // a solvable constraint instead of collapsing to a concrete address.
+    fn bind_precompile_result(&mut self, ret_offset: EVMU256, ret_len: EVMU256, binding: PrecompileBinding) {
+        match binding {
+            PrecompileBinding::Ecrecover(addr) => {
+                self.symbolic_memory.insert_256(ret_offset, addr);
+            }
+            PrecompileBinding::Sha256(digest) => {
+                self.symbolic_memory.insert_256(ret_offset, digest);
+            }
+            PrecompileBinding::Identity(bytes) => {
+                let offset = ret_offset.as_limbs()[0] as usize;
+                let len = (ret_len.as_limbs()[0] as usize).min(bytes.len());
+                if offset + len >= self.symbolic_memory.memory.len() {
+                    self.symbolic_memory.memory.resize(offset + len + 1, None);
+                }
+                for (i, b) in bytes.into_iter().take(len).enumerate() {
+                    self.symbolic_memory.memory[offset + i] = Some(b);
+                }
+            }
+        }
+    }
+
+    // Concatenates consecutive per-byte expressions from `get_slice` into a
+    // single multi-byte word, e.g. to rebuild a 32-byte input word for a
+    // precompile argument.
+    fn concat_bytes(bytes: &[Box<Expr>]) -> Box<Expr> {
+        let mut word = bytes[0].clone();
+        for b in &bytes[1..] {
+            word = word.concat(b.clone());
+        }
+        word
+    }
+
+    // Recognizes a call into one of the precompiles we model symbolically
+    // (0x01 ecrecover, 0x02 sha256, 0x04 identity) and, if so, decodes its
+    // input words from the (pre-reset) caller memory.
+    fn precompile_call(
+        &mut self,
+        target: EVMU256,
+        arg_offset: EVMU256,
+        arg_len: EVMU256,
+        ret_offset: EVMU256,
+        ret_len: EVMU256,
+    ) -> Option<(EVMU256, EVMU256, PrecompileBinding)> {
+        if is_zero(target) || target > EVMU256::from(9) {
+            return None;
+        }
+        let input_words = self.symbolic_memory.get_slice(arg_offset, arg_len);
+        if input_words.is_empty() {
+            return None;
+        }
+        let binding = match target.as_limbs()[0] {
+            // ecrecover: 128-byte input = hash(32) || v(32) || r(32) || s(32)
+            1 if input_words.len() >= 128 => {
+                let word = |i: usize| Self::concat_bytes(&input_words[i * 32..i * 32 + 32]);
+                PrecompileBinding::Ecrecover(Expr::new_ecrecover(word(0), word(1), word(2), word(3)))
+            }
+            2 => {
+                let preimage = Self::concat_bytes(&input_words);
+                PrecompileBinding::Sha256(Expr::new_sha256(preimage, input_words.len()))
+            }
+            4 => PrecompileBinding::Identity(input_words),
+            _ => return None,
+        };
+        Some((ret_offset, ret_len, binding))
+    }
+
     pub fn push_ctx(&mut self, interp: &mut Interpreter) {
         // interp.stack.data()[interp.stack.len() - 1 - $idx]
-        let (arg_offset, arg_len) = match unsafe { *interp.instruction_pointer } {
-            0xf1 | 0xf2 => (interp.stack.peek(3).unwrap(), interp.stack.peek(4).unwrap()),
-            0xf4 | 0xfa => (interp.stack.peek(2).unwrap(), interp.stack.peek(3).unwrap()),
+        let (arg_offset, arg_len, ret_offset, ret_len) = match unsafe { *interp.instruction_pointer } {
+            0xf1 | 0xf2 => (
+                interp.stack.peek(3).unwrap(),
+                interp.stack.peek(4).unwrap(),
+                interp.stack.peek(5).unwrap(),
+                interp.stack.peek(6).unwrap(),
+            ),
+            0xf4 | 0xfa => (
+                interp.stack.peek(2).unwrap(),
+                interp.stack.peek(3).unwrap(),
+                interp.stack.peek(4).unwrap(),
+                interp.stack.peek(5).unwrap(),
+            ),
             _ => {
                 panic!("not supported opcode");
             }
         };
+        let target = interp.stack.peek(1).unwrap();
+
+        let precompile_result = self.precompile_call(target, arg_offset, arg_len, ret_offset, ret_len);
 
         let ctx = ConcolicCallCtx {
             symbolic_stack: self.symbolic_stack.clone(),
             symbolic_memory: self.symbolic_memory.clone(),
-            symbolic_state: self.symbolic_state.clone(),
+            storage_address: self.storage_address,
             input_bytes: {
                 let by = self.symbolic_memory.get_slice(arg_offset, arg_len);
                 by
             },
+            precompile_result,
         };
         self.ctxs.push(ctx);
 
+        // CALL/STATICCALL run against the callee's own storage; CALLCODE/
+        // DELEGATECALL keep executing against the caller's storage. Either
+        // way `self.symbolic_state` itself is untouched here, so slots
+        // written under the old address are still there once we pop back.
+        if matches!(unsafe { *interp.instruction_pointer }, 0xf1 | 0xfa) {
+            self.storage_address = convert_u256_to_h160(target);
+        }
+
         self.symbolic_stack = vec![];
         self.symbolic_memory = SymbolicMemory::new();
-        self.symbolic_state = Default::default();
+        // open this sub-call's own constraint scope, mirroring
+        // `ConcolicSolver::push_scope`; `pop_ctx` closes it
+        self.constraint_scopes.push(vec![]);
     }
 
     fn construct_input_from_abi(vm_input: BoxedABI) -> Vec<Box<Expr>> {
@@ -259,6 +513,41 @@ impl<I, VS> ConcolicHost<I, VS> {
         res
     }
 
+    // Recognizes `EQ(<selector-derived expr>, <4-byte constant>)`, the shape
+    // of the condition a Solidity `msg.sig` dispatcher JUMPIs on, and returns
+    // the constant selector if found. Letting the solver discover every
+    // selector by brute force is slow; this seeds them directly.
+    fn match_selector_eq(expr: &Expr) -> Option<u32> {
+        if expr.op != ConcolicOp::EQ {
+            return None;
+        }
+        let lhs = expr.lhs.as_ref()?;
+        let rhs = expr.rhs.as_ref()?;
+        let (konst, other) = match (&lhs.op, &rhs.op) {
+            (ConcolicOp::EVMU256(v), _) => (*v, rhs.as_ref()),
+            (_, ConcolicOp::EVMU256(v)) => (*v, lhs.as_ref()),
+            _ => return None,
+        };
+        if !Self::derives_from_selector(other) {
+            return None;
+        }
+        Some((konst.as_limbs()[0] & 0xffff_ffff) as u32)
+    }
+
+    // True if `expr` is (a function of) the 4-byte selector sliced off the
+    // start of calldata, e.g. `shr(224, calldataload(0))` or a mask of it.
+    fn derives_from_selector(expr: &Expr) -> bool {
+        match &expr.op {
+            ConcolicOp::SLICEDINPUT(idx) => is_zero(*idx),
+            ConcolicOp::FINEGRAINEDINPUT(start, _) => *start == 0,
+            ConcolicOp::SHR | ConcolicOp::AND => {
+                expr.lhs.as_ref().is_some_and(|l| Self::derives_from_selector(l))
+                    || expr.rhs.as_ref().is_some_and(|r| Self::derives_from_selector(r))
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_input_slice_from_ctx(&self, idx: usize, length: usize) -> Box<Expr> {
         let data = self.ctxs.last().expect("no ctx").input_bytes.clone();
         let mut bytes = data[idx].clone();
@@ -271,6 +560,41 @@ impl<I, VS> ConcolicHost<I, VS> {
         }
         simplify(bytes)
     }
+
+    /// The path constraints accumulated so far, run through the
+    /// `constraint_ir` pass pipeline (constant folding, boolean algebra,
+    /// dedup, dead-constraint elimination). Call this once per solve
+    /// request rather than solving `flattened_constraints()` directly -
+    /// it's both smaller and avoids re-deriving already-decided branches.
+    pub fn solve_constraints(&self) -> Vec<Box<Expr>> {
+        simplify_constraints(self.flattened_constraints())
+    }
+
+    /// Replays `self.constraint_scopes` into a fresh incremental solver,
+    /// one `push_scope` per call-context frame - the same frames
+    /// `push_ctx`/`pop_ctx` open and close live. A caller exploring several
+    /// candidate flips that share an outer frame (e.g. several branches
+    /// inside the same sub-call) can keep reusing this solver with its own
+    /// nested `push_scope`/`assert`/`check`/`pop_scope`, instead of
+    /// re-asserting the shared prefix for every candidate.
+    pub fn build_solver<'ctx>(&self, ctx: &'ctx Context) -> ConcolicSolver<'ctx> {
+        let mut solver = ConcolicSolver::new(ctx);
+        for scope in &self.constraint_scopes {
+            solver.push_scope();
+            for c in scope {
+                solver.assert(c);
+            }
+        }
+        solver
+    }
+
+    /// Walks the recorded branch points under `order`, querying the solver
+    /// for each side we haven't taken yet (through `self.feasibility_cache`
+    /// so repeat prefixes are free), and returns a freshly-solved `Solution`
+    /// per branch that turned out to be flippable.
+    pub fn explore_branches(&mut self, order: FrontierOrder) -> Vec<Solution> {
+        branch_search::explore(&self.branch_points, order, &self.visited_jumpdests, &mut self.feasibility_cache)
+    }
 }
 
 impl<I, VS, S> Middleware<VS, I, S> for ConcolicHost<I, VS>
@@ -339,7 +663,7 @@ where
             }};
         }
 
-        let solutions = vec![];
+        let mut solutions = vec![];
 
         // if self.ctxs.len() > 0 {
         //     return;
@@ -460,9 +784,36 @@ where
             0x0a => {
                 concrete_eval!(2, 1)
             }
-            // SIGNEXTEND - FIXME: need to check
+            // SIGNEXTEND - sign-extends x from (b+1) bytes to 32 bytes, b
+            // counting bytes from the least-significant one. Built from
+            // bvand/bvor masks like insert_256's byte slicing; which mask
+            // applies depends on the sign bit's concrete value, the same way
+            // ADD/SUB etc. already follow the concrete execution's path
+            // rather than introducing an ITE.
             0x0b => {
-                concrete_eval!(2, 1)
+                let b = fast_peek!(0).as_limbs()[0];
+                let x = stack_bv!(1);
+                let res = if b >= 31 {
+                    Some(x)
+                } else {
+                    let bit = 8 * (b as u32 + 1) - 1;
+                    let sign_is_set = !is_zero(fast_peek!(1) & (EVMU256::from(1) << bit));
+                    let const_u256 = |v: EVMU256| -> Box<Expr> {
+                        Box::new(Expr {
+                            lhs: None,
+                            rhs: None,
+                            op: ConcolicOp::EVMU256(v),
+                        })
+                    };
+                    Some(if sign_is_set {
+                        x.bvor(const_u256(EVMU256::MAX << (bit + 1)))
+                    } else {
+                        x.bvand(const_u256((EVMU256::from(1) << (bit + 1)) - EVMU256::from(1)))
+                    })
+                };
+                self.symbolic_stack.pop();
+                self.symbolic_stack.pop();
+                vec![res]
             }
             // LT
             0x10 => {
@@ -536,10 +887,30 @@ where
                 self.symbolic_stack.pop();
                 vec![res]
             }
-            // BYTE
-            // FIXME: support this
+            // BYTE - SELECT the i-th byte (i=0 is most significant) out of
+            // the 256-bit operand, mirroring the SELECT slicing insert_256
+            // already uses to write individual bytes.
             0x1a => {
-                concrete_eval!(2, 1)
+                let i = fast_peek!(0).as_limbs()[0];
+                let x = stack_bv!(1);
+                let res = if i >= 32 {
+                    Some(Box::new(Expr {
+                        lhs: None,
+                        rhs: None,
+                        op: ConcolicOp::EVMU256(EVMU256::from(0)),
+                    }))
+                } else {
+                    let hi = 255 - 8 * (i as u32);
+                    let lo = 248 - 8 * (i as u32);
+                    Some(Box::new(Expr {
+                        lhs: Some(x),
+                        rhs: None,
+                        op: ConcolicOp::SELECT(hi, lo),
+                    }))
+                };
+                self.symbolic_stack.pop();
+                self.symbolic_stack.pop();
+                vec![res]
             }
             // SHL
             0x1b => {
@@ -562,9 +933,26 @@ where
                 self.symbolic_stack.pop();
                 vec![res]
             }
-            // SHA3
+            // SHA3 - model as a symbolic digest over the hashed memory region
+            // rather than discarding the preimage, so keccak equalities
+            // (mapping slot derivation, signature/commitment checks) stay
+            // solvable instead of collapsing to an opaque concrete value.
             0x20 => {
-                concrete_eval!(2, 1)
+                let offset = fast_peek!(0);
+                let len = fast_peek!(1);
+                self.symbolic_stack.pop();
+                self.symbolic_stack.pop();
+                let len_usize = as_u64(len) as usize;
+                if len_usize == 0 {
+                    vec![None]
+                } else {
+                    let bytes = self.symbolic_memory.get_slice(offset, len);
+                    let mut preimage = bytes[0].clone();
+                    for b in &bytes[1..] {
+                        preimage = preimage.concat(b.clone());
+                    }
+                    vec![Some(Expr::new_sha3(preimage, len_usize))]
+                }
             }
             // ADDRESS
             0x30 => {
@@ -600,6 +988,7 @@ where
             }
             // CALLDATALOAD
             0x35 => {
+                let offset_expr = stack_bv!(0);
                 let offset = interp.stack.peek(0).unwrap();
                 self.symbolic_stack.pop();
                 if self.ctxs.len() > 0 {
@@ -614,15 +1003,25 @@ where
                             .pretty_print();
                     }
                     vec![Some(self.get_input_slice_from_ctx(offset_usize, 32))]
-                } else {
+                } else if offset_expr.is_concrete() {
                     vec![Some(Expr::new_sliced_input(offset))]
+                } else {
+                    // symbolic offset: route through the same array-theory
+                    // LOAD memory uses, instead of silently concretizing it
+                    vec![Some(Expr::new_array_load(
+                        Expr::new_symbolic_array("calldata"),
+                        offset_expr,
+                    ))]
                 }
             }
             // CALLDATASIZE
             0x36 => {
                 vec![None]
             }
-            // CALLDATACOPY
+            // CALLDATACOPY - the destination region's *contents* aren't
+            // tracked (same as before this change); what matters here is
+            // only draining the 3 symbolic operands so the stack stays in
+            // sync, same as `concrete_eval!`.
             0x37 => {
                 concrete_eval!(3, 0)
             }
@@ -702,15 +1101,28 @@ where
             // MLOAD
             0x51 => {
                 // println!("[concolic] MLOAD: {:?}", self.symbolic_stack);
+                let offset_expr = stack_bv!(0);
                 let offset = fast_peek!(0);
                 self.symbolic_stack.pop();
-                vec![self.symbolic_memory.get_256(offset)]
+                if offset_expr.is_concrete() {
+                    vec![self.symbolic_memory.get_256(offset)]
+                } else {
+                    // symbolic offset: fall back to the array-theory view so
+                    // the offset expression propagates end-to-end instead of
+                    // being concretized via `as_limbs()[0]`
+                    vec![Some(self.symbolic_memory.get_256_symbolic(offset_expr))]
+                }
             }
             // MSTORE
             0x52 => {
+                let offset_expr = stack_bv!(0);
                 let offset = fast_peek!(0);
                 let value = stack_bv!(1);
-                self.symbolic_memory.insert_256(offset, value);
+                if offset_expr.is_concrete() {
+                    self.symbolic_memory.insert_256(offset, value);
+                } else {
+                    self.symbolic_memory.insert_256_symbolic(offset_expr, value);
+                }
                 self.symbolic_stack.pop();
                 self.symbolic_stack.pop();
                 vec![]
@@ -726,18 +1138,18 @@ where
             }
             // SLOAD
             0x54 => {
-                self.symbolic_stack.pop();
+                let key_expr = stack_bv!(0);
                 let key = fast_peek!(0);
-                vec![match self.symbolic_state.get(&key) {
-                    Some(v) => v.clone(),
-                    None => None,
-                }]
+                let key_is_concrete = key_expr.is_concrete();
+                self.symbolic_stack.pop();
+                vec![self.current_storage().get(key_expr, key, key_is_concrete)]
             }
             // SSTORE
             0x55 => {
+                let key_expr = stack_bv!(1);
                 let key = fast_peek!(1);
                 let value = stack_bv!(0);
-                self.symbolic_state.insert(key, Some(value));
+                self.current_storage().insert(key_expr, key, value);
                 self.symbolic_stack.pop();
                 self.symbolic_stack.pop();
                 vec![]
@@ -753,6 +1165,17 @@ where
                 // jump dest in concolic solving mode is the opposite of the concrete
                 let br = is_zero(fast_peek!(1));
 
+                if let Some(selector) = Self::match_selector_eq(&stack_bv!(1)) {
+                    let mut input = selector.to_be_bytes().to_vec();
+                    input.extend(std::iter::repeat(0u8).take(28));
+                    solutions.push(Solution {
+                        input,
+                        caller: EVMAddress::zero(),
+                        value: EVMU256::from(0),
+                        fields: vec![],
+                    });
+                }
+
                 let real_path_constraint = if br {
                     // path_condition = false
                     stack_bv!(1).lnot()
@@ -761,9 +1184,19 @@ where
                     stack_bv!(1)
                 };
 
+                // the destination actually reached this time, and the one
+                // the other side of the branch would have reached instead
+                let taken_dest = if br { interp.program_counter() as u64 + 1 } else { as_u64(fast_peek!(0)) };
+                self.visited_jumpdests.insert(taken_dest);
+
                 // jumping only happens if the second element is false
                 if !real_path_constraint.is_concrete() {
-                    self.constraints.push(real_path_constraint);
+                    let other_side_dest = if br { as_u64(fast_peek!(0)) } else { interp.program_counter() as u64 + 1 };
+                    self.branch_points.push(BranchPoint {
+                        constraint: real_path_constraint.clone(),
+                        other_side_dest,
+                    });
+                    self.assert_constraint(real_path_constraint);
                 }
                 self.symbolic_stack.pop();
                 self.symbolic_stack.pop();
@@ -843,6 +1276,7 @@ where
             }
             // REVERT
             0xfd => {
+                self.pending_revert = true;
                 concrete_eval!(2, 0)
             }
             // INVALID
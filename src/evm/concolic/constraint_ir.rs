@@ -0,0 +1,165 @@
+/// A small pass pipeline over the path constraints collected by
+/// `ConcolicHost::on_step` (one `Expr` per `JUMPI`, see `concolic_host.rs`),
+/// run once per solve request before the set is handed to `SolverCtx`.
+/// Lowering straight from raw, un-simplified terms means re-deriving the
+/// same infeasible branch on every solve and shipping Z3 queries that are
+/// bigger than they need to be; these passes shrink the set first.
+///
+/// New passes are just `fn(Vec<Box<Expr>>) -> Vec<Box<Expr>>` and get
+/// appended to `default_passes()`.
+use crate::evm::concolic::expr::{simplify, ConcolicOp, Expr};
+use crate::evm::types::EVMU256;
+
+pub type Pass = fn(Vec<Box<Expr>>) -> Vec<Box<Expr>>;
+
+/// Folds a binary node whose operands are both concrete `EVMU256` constants
+/// into a single `EVMU256` leaf, bottom-up. Wrapping arithmetic matches EVM
+/// 256-bit semantics (e.g. `ADD` wraps on overflow, `DIV`/`UREM` by zero is
+/// zero per the EVM spec rather than a trap).
+fn fold_constants_node(expr: Box<Expr>) -> Box<Expr> {
+    let op = expr.op.clone();
+    let lhs = expr.lhs.map(fold_constants_node);
+    let rhs = expr.rhs.map(fold_constants_node);
+
+    let konst = |e: &Option<Box<Expr>>| -> Option<EVMU256> {
+        match e {
+            Some(e) => match e.op {
+                ConcolicOp::EVMU256(v) if e.lhs.is_none() && e.rhs.is_none() => Some(v),
+                _ => None,
+            },
+            None => None,
+        }
+    };
+
+    let folded = match (&op, konst(&lhs), konst(&rhs)) {
+        (ConcolicOp::ADD, Some(l), Some(r)) => Some(l.wrapping_add(r)),
+        (ConcolicOp::SUB, Some(l), Some(r)) => Some(l.wrapping_sub(r)),
+        (ConcolicOp::MUL, Some(l), Some(r)) => Some(l.wrapping_mul(r)),
+        (ConcolicOp::DIV, Some(l), Some(r)) => Some(if r.is_zero() { EVMU256::ZERO } else { l / r }),
+        (ConcolicOp::UREM, Some(l), Some(r)) => Some(if r.is_zero() { EVMU256::ZERO } else { l % r }),
+        (ConcolicOp::AND, Some(l), Some(r)) => Some(l & r),
+        (ConcolicOp::OR, Some(l), Some(r)) => Some(l | r),
+        (ConcolicOp::XOR, Some(l), Some(r)) => Some(l ^ r),
+        (ConcolicOp::EQ, Some(l), Some(r)) => Some(if l == r { EVMU256::from(1) } else { EVMU256::ZERO }),
+        (ConcolicOp::LT, Some(l), Some(r)) => Some(if l < r { EVMU256::from(1) } else { EVMU256::ZERO }),
+        (ConcolicOp::GT, Some(l), Some(r)) => Some(if l > r { EVMU256::from(1) } else { EVMU256::ZERO }),
+        _ => None,
+    };
+
+    if let Some(v) = folded {
+        return Expr::const_u256(v);
+    }
+
+    // LNOT(concrete) -> concrete, folds the `is_zero(is_zero(x))` chain this
+    // pass's caller produces into a plain boolean once `x` is fully known.
+    if op == ConcolicOp::LNOT {
+        if let Some(v) = konst(&lhs) {
+            return Expr::const_u256(if v.is_zero() { EVMU256::from(1) } else { EVMU256::ZERO });
+        }
+    }
+
+    Box::new(Expr { lhs, rhs, op })
+}
+
+/// Constant-folds every fully-concrete subterm of every constraint.
+pub fn fold_constants(constraints: Vec<Box<Expr>>) -> Vec<Box<Expr>> {
+    constraints.into_iter().map(fold_constants_node).collect()
+}
+
+/// `LNOT(LNOT(x))` is `x` widened back to a boolean 0/1, so collapsing the
+/// double negation still needs the outer "is it nonzero" coercion - just
+/// without re-negating twice. We recover that by rewriting to `EQ(x, 0) ==
+/// 0`, i.e. `NEQ(x, 0)`-shaped, which `fold_constants` can finish off when
+/// `x` happens to be concrete too.
+fn collapse_double_negation_node(expr: Box<Expr>) -> Box<Expr> {
+    let op = expr.op.clone();
+    let lhs = expr.lhs.map(collapse_double_negation_node);
+    let rhs = expr.rhs.map(collapse_double_negation_node);
+
+    if op == ConcolicOp::LNOT {
+        if let Some(inner) = &lhs {
+            if inner.op == ConcolicOp::LNOT && inner.rhs.is_none() {
+                // is_zero(is_zero(x)) == bool(x): same truth value as x != 0,
+                // expressed as LNOT(EQ(x, 0)) so it stays a 0/1 bitvector.
+                let x = inner.lhs.clone().expect("lnot missing operand");
+                return Box::new(Expr {
+                    lhs: Some(Box::new(Expr {
+                        lhs: Some(x),
+                        rhs: Some(Expr::const_u256(EVMU256::ZERO)),
+                        op: ConcolicOp::EQ,
+                    })),
+                    rhs: None,
+                    op: ConcolicOp::LNOT,
+                });
+            }
+        }
+    }
+
+    Box::new(Expr { lhs, rhs, op })
+}
+
+/// Algebraic rewrite of `lnot`/`is_zero` chains: collapses `LNOT(LNOT(x))`.
+pub fn simplify_booleans(constraints: Vec<Box<Expr>>) -> Vec<Box<Expr>> {
+    constraints.into_iter().map(collapse_double_negation_node).collect()
+}
+
+/// Runs the shared concat/select rewrite (`expr::simplify`) over every
+/// constraint, same as is already applied ad hoc elsewhere in the concolic
+/// engine, so the IR benefits from it too.
+pub fn simplify_concat(constraints: Vec<Box<Expr>>) -> Vec<Box<Expr>> {
+    constraints.into_iter().map(simplify).collect()
+}
+
+/// Drops a constraint once an earlier, syntactically-identical constraint
+/// has already been kept - re-asserting the same condition can't change
+/// solvability and only bloats the query.
+pub fn dedup(constraints: Vec<Box<Expr>>) -> Vec<Box<Expr>> {
+    let mut kept: Vec<Box<Expr>> = Vec::with_capacity(constraints.len());
+    for c in constraints {
+        if !kept.iter().any(|k| k == &c) {
+            kept.push(c);
+        }
+    }
+    kept
+}
+
+/// True if any input-derived (i.e. fuzzer-controllable) leaf occurs in
+/// `expr`: a calldata slice/word, a symbolic byte, or a load out of the
+/// theory-of-arrays memory/storage (which may itself be seeded from
+/// calldata). A constraint with none of these can never flip under
+/// mutation, so the solver can't act on it.
+fn mentions_input(expr: &Expr) -> bool {
+    match &expr.op {
+        ConcolicOp::SLICEDINPUT(_) | ConcolicOp::FINEGRAINEDINPUT(_, _) | ConcolicOp::SYMBYTE(_) => true,
+        ConcolicOp::LOAD(_, _) | ConcolicOp::STORE(_, _, _) | ConcolicOp::ARRAY(_) => true,
+        _ => expr.lhs.as_deref().is_some_and(mentions_input) || expr.rhs.as_deref().is_some_and(mentions_input),
+    }
+}
+
+/// Drops constraints that are fully concrete (already decided, nothing to
+/// solve) or don't mention any input-derived variable (the solver has no
+/// lever to flip them).
+pub fn eliminate_dead(constraints: Vec<Box<Expr>>) -> Vec<Box<Expr>> {
+    constraints
+        .into_iter()
+        .filter(|c| !c.is_concrete() && mentions_input(c))
+        .collect()
+}
+
+/// The passes run, in order, on every solve request. Earlier passes feed
+/// later ones: folding surfaces concrete constraints for `eliminate_dead`,
+/// and `simplify_booleans` turns more double-negation chains concrete for
+/// `fold_constants` to finish.
+pub fn default_passes() -> Vec<Pass> {
+    vec![simplify_concat, simplify_booleans, fold_constants, dedup, eliminate_dead]
+}
+
+/// Runs `passes` over `constraints` in order, returning the simplified set.
+pub fn run_passes(constraints: Vec<Box<Expr>>, passes: &[Pass]) -> Vec<Box<Expr>> {
+    passes.iter().fold(constraints, |acc, pass| pass(acc))
+}
+
+/// Runs the default pass pipeline.
+pub fn simplify_constraints(constraints: Vec<Box<Expr>>) -> Vec<Box<Expr>> {
+    run_passes(constraints, &default_passes())
+}
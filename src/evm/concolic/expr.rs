@@ -20,6 +20,23 @@ pub enum ConcolicOp {
     SHR,
     SAR,
     SLICEDINPUT(EVMU256),
+    // keccak256/SHA3 of `preimage`, `len` bytes long. Modeled as an
+    // uninterpreted function when lowered to the solver so that equal
+    // preimages are forced to equal digests for free, while distinct
+    // concrete preimages observed at runtime get asserted distinct.
+    SHA3(Box<Expr>, usize),
+    // theory-of-arrays memory: a fresh, named symbolic array constant (the
+    // initial state of a region such as "memory" or "calldata")
+    ARRAY(String),
+    // STORE(array, idx, val) -> the array after writing `val` at `idx`
+    STORE(Box<Expr>, Box<Expr>, Box<Expr>),
+    // LOAD(array, idx) -> the value stored at `idx`
+    LOAD(Box<Expr>, Box<Expr>),
+    // precompile 0x01: recover the signer address from (hash, v, r, s),
+    // modeled as an uninterpreted function for the same reason as SHA3
+    ECRECOVER(Box<Expr>, Box<Expr>, Box<Expr>, Box<Expr>),
+    // precompile 0x02: sha256(preimage), `len` bytes long
+    SHA256(Box<Expr>, usize),
     BALANCE,
     CALLVALUE,
     CALLER,
@@ -132,6 +149,57 @@ impl Expr {
         })
     }
 
+    pub fn new_sha3(preimage: Box<Expr>, len: usize) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::SHA3(preimage, len),
+        })
+    }
+
+    /// A fresh symbolic array, used as the base of a theory-of-arrays memory
+    /// or calldata region so a non-constant index still solves soundly
+    /// instead of being concretized.
+    pub fn new_symbolic_array(name: &str) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::ARRAY(name.to_string()),
+        })
+    }
+
+    pub fn new_array_store(array: Box<Expr>, idx: Box<Expr>, val: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::STORE(array, idx, val),
+        })
+    }
+
+    pub fn new_array_load(array: Box<Expr>, idx: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::LOAD(array, idx),
+        })
+    }
+
+    pub fn new_ecrecover(hash: Box<Expr>, v: Box<Expr>, r: Box<Expr>, s: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::ECRECOVER(hash, v, r, s),
+        })
+    }
+
+    pub fn new_sha256(preimage: Box<Expr>, len: usize) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::SHA256(preimage, len),
+        })
+    }
+
     pub fn new_balance() -> Box<Expr> {
         Box::new(Expr {
             lhs: None,
@@ -254,6 +322,14 @@ impl Expr {
         })
     }
 
+    pub fn const_u256(v: EVMU256) -> Box<Expr> {
+        Box::new(Expr {
+            lhs: None,
+            rhs: None,
+            op: ConcolicOp::EVMU256(v),
+        })
+    }
+
     // logical not
     pub fn lnot(self) -> Box<Expr> {
         Box::new(Expr {
@@ -275,6 +351,22 @@ impl Expr {
                 ConcolicOp::CONSTBYTE(_) => true,
                 ConcolicOp::FINEGRAINEDINPUT(_, _) => false,
                 ConcolicOp::CALLER => false,
+                // these carry their operands in the enum payload rather than
+                // `lhs`/`rhs`, but are otherwise concrete exactly when every
+                // operand is - letting `fold_precompiles` replace a fully
+                // concrete call with the real recovered address/digest
+                // instead of leaving it for the solver as an uninterpreted
+                // function
+                ConcolicOp::SHA3(preimage, _) => preimage.is_concrete(),
+                ConcolicOp::ECRECOVER(hash, v, r, s) => {
+                    hash.is_concrete() && v.is_concrete() && r.is_concrete() && s.is_concrete()
+                }
+                ConcolicOp::SHA256(preimage, _) => preimage.is_concrete(),
+                // a symbolic array base can never be concrete; STORE/LOAD
+                // inherit that regardless of their index/value operands
+                ConcolicOp::ARRAY(_) => false,
+                ConcolicOp::STORE(_, _, _) => false,
+                ConcolicOp::LOAD(_, _) => false,
                 _ => unreachable!(),
             },
             (Some(l), None) => l.is_concrete(),
@@ -282,6 +374,38 @@ impl Expr {
         }
     }
 
+    /// Flattens a concrete byte-string expression - a `CONCAT` chain of
+    /// `CONSTBYTE` leaves and byte-aligned `SELECT`s of concrete values (the
+    /// per-byte slices `SymbolicMemory::insert_256` stores, which
+    /// `ConcolicHost::concat_bytes` joins into precompile preimages), or a
+    /// single 32-byte `EVMU256` word - into its raw bytes, big-endian / in
+    /// concat order. Returns `None` for any other node, including concrete
+    /// ones such as arithmetic over constants.
+    pub fn concrete_bytes(&self) -> Option<Vec<u8>> {
+        match &self.op {
+            ConcolicOp::CONSTBYTE(b) => Some(vec![*b]),
+            ConcolicOp::SELECT(high, low) if *low % 8 == 0 && (*high + 1) % 8 == 0 => {
+                let inner = self.lhs.as_ref()?.concrete_bytes()?;
+                let (n, hi_byte, lo_byte) = (inner.len(), (*high / 8) as usize, (*low / 8) as usize);
+                if hi_byte >= n {
+                    return None;
+                }
+                Some(inner[n - 1 - hi_byte..n - lo_byte].to_vec())
+            }
+            ConcolicOp::EVMU256(v) if self.lhs.is_none() && self.rhs.is_none() => {
+                let bytes: [u8; 32] = v.to_be_bytes();
+                Some(bytes.to_vec())
+            }
+            ConcolicOp::CONCAT => {
+                let mut lhs = self.lhs.as_ref()?.concrete_bytes()?;
+                let rhs = self.rhs.as_ref()?.concrete_bytes()?;
+                lhs.extend(rhs);
+                Some(lhs)
+            }
+            _ => None,
+        }
+    }
+
     pub fn depth(&self) -> u32 {
         if self.lhs.is_none() && self.rhs.is_none() {
             return 0;
@@ -371,7 +495,264 @@ pub fn simplify_concat_select(expr: Box<Expr>) -> Box<Expr> {
     simplify_concat_select_helper(expr).1
 }
 
+extern crate crypto;
+use self::crypto::digest::Digest;
+use self::crypto::sha2::Sha256 as RcSha256;
+use self::crypto::sha3::Sha3;
+
+fn keccak256(preimage: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    let mut hasher = Sha3::keccak256();
+    hasher.input(preimage);
+    hasher.result(&mut digest);
+    digest
+}
+
+fn sha256(preimage: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    let mut hasher = RcSha256::new();
+    hasher.input(preimage);
+    hasher.result(&mut digest);
+    digest
+}
+
+/// Recovers the signer address for a fully concrete `(hash, v, r, s)`, the
+/// same secp256k1 recovery `ControlledKeypair::sign` is the inverse of (see
+/// `crate::evm::presets::ecrecover`). Returns all-zero (as the real
+/// precompile does) if the signature doesn't recover.
+fn ecrecover(hash: &[u8], v: &[u8], r: &[u8], s: &[u8]) -> [u8; 32] {
+    use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+    use secp256k1::{Message, Secp256k1};
+
+    let recovery_byte = *v.last().unwrap_or(&0);
+    let recovery_id = match recovery_byte {
+        27 | 0 => 0,
+        28 | 1 => 1,
+        _ => return [0u8; 32],
+    };
+    let mut compact = [0u8; 64];
+    compact[..32].copy_from_slice(&r[r.len().saturating_sub(32)..]);
+    compact[32..].copy_from_slice(&s[s.len().saturating_sub(32)..]);
+
+    let result = (|| -> Result<[u8; 32], secp256k1::Error> {
+        let message = Message::from_slice(hash)?;
+        let id = RecoveryId::from_i32(recovery_id)?;
+        let sig = RecoverableSignature::from_compact(&compact, id)?;
+        let public = Secp256k1::new().recover_ecdsa(&message, &sig)?;
+        let uncompressed = public.serialize_uncompressed();
+        let digest = keccak256(&uncompressed[1..]);
+        let mut address = [0u8; 32];
+        address[12..].copy_from_slice(&digest[12..]);
+        Ok(address)
+    })();
+    result.unwrap_or([0u8; 32])
+}
+
+/// Replaces any fully concrete `SHA3`/`SHA256`/`ECRECOVER` node with the
+/// digest/address the real primitive produces, bottom-up, so a concrete
+/// transaction never pays for (or needs) the uninterpreted-function
+/// placeholder `solver::SolverCtx` lowers these to for the symbolic case.
+/// A concrete node whose operands `concrete_bytes` can't flatten is left
+/// as it is.
+pub fn fold_precompiles(expr: Box<Expr>) -> Box<Expr> {
+    let op = match expr.op {
+        ConcolicOp::SHA3(preimage, len) => ConcolicOp::SHA3(fold_precompiles(preimage), len),
+        ConcolicOp::SHA256(preimage, len) => ConcolicOp::SHA256(fold_precompiles(preimage), len),
+        ConcolicOp::ECRECOVER(hash, v, r, s) => ConcolicOp::ECRECOVER(
+            fold_precompiles(hash),
+            fold_precompiles(v),
+            fold_precompiles(r),
+            fold_precompiles(s),
+        ),
+        other => other,
+    };
+    let lhs = expr.lhs.map(fold_precompiles);
+    let rhs = expr.rhs.map(fold_precompiles);
+    let rebuilt = Box::new(Expr { lhs, rhs, op });
+
+    if !rebuilt.is_concrete() {
+        return rebuilt;
+    }
+
+    match &rebuilt.op {
+        ConcolicOp::SHA3(preimage, _) => {
+            let Some(bytes) = preimage.concrete_bytes() else { return rebuilt };
+            Expr::const_u256(EVMU256::from_be_bytes(keccak256(&bytes)))
+        }
+        ConcolicOp::SHA256(preimage, _) => {
+            let Some(bytes) = preimage.concrete_bytes() else { return rebuilt };
+            Expr::const_u256(EVMU256::from_be_bytes(sha256(&bytes)))
+        }
+        ConcolicOp::ECRECOVER(hash, v, r, s) => {
+            let (Some(hash), Some(v), Some(r), Some(s)) =
+                (hash.concrete_bytes(), v.concrete_bytes(), r.concrete_bytes(), s.concrete_bytes())
+            else {
+                return rebuilt;
+            };
+            Expr::const_u256(EVMU256::from_be_bytes(ecrecover(&hash, &v, &r, &s)))
+        }
+        _ => rebuilt,
+    }
+}
+
+/// The value of a leaf `fold_constants` can fold: a plain `EVMU256` constant,
+/// or a `CONSTBYTE` widened to a 256-bit value.
+fn leaf_value(expr: &Expr) -> Option<EVMU256> {
+    if expr.lhs.is_some() || expr.rhs.is_some() {
+        return None;
+    }
+    match expr.op {
+        ConcolicOp::EVMU256(v) => Some(v),
+        ConcolicOp::CONSTBYTE(b) => Some(EVMU256::from(b)),
+        _ => None,
+    }
+}
+
+/// Two's complement negation: the EVM has no dedicated negate op, but
+/// `SDIV`/`SMOD` need it to go from a sign/magnitude view back to the raw
+/// 256-bit representation.
+fn i256_neg(v: EVMU256) -> EVMU256 {
+    (!v).wrapping_add(EVMU256::from(1u64))
+}
+
+fn i256_is_negative(v: EVMU256) -> bool {
+    (v >> 255u32) & EVMU256::from(1u64) == EVMU256::from(1u64)
+}
+
+/// `(magnitude, was_negative)`, i.e. the sign/magnitude view of a two's
+/// complement `EVMU256`.
+fn i256_abs(v: EVMU256) -> (EVMU256, bool) {
+    if i256_is_negative(v) {
+        (i256_neg(v), true)
+    } else {
+        (v, false)
+    }
+}
+
+/// Signed less-than via the standard sign-bit-flip trick: flipping the top
+/// bit of both operands turns a signed comparison into an unsigned one.
+fn i256_slt(l: EVMU256, r: EVMU256) -> bool {
+    const SIGN_BIT: u32 = 255;
+    (l ^ (EVMU256::from(1u64) << SIGN_BIT)) < (r ^ (EVMU256::from(1u64) << SIGN_BIT))
+}
+
+/// Evaluates a single `ConcolicOp` node whose operands are already known
+/// constants, per the real EVM's 256-bit wrapping semantics. Returns `None`
+/// for ops `fold_constants` doesn't fold (container/byte-string nodes like
+/// `CONCAT`, `SHA3`, `ARRAY`, ... - those are either meaningless to collapse
+/// into a single word or are `fold_precompiles`'s job).
+pub(crate) fn eval_concrete(op: &ConcolicOp, lhs: Option<EVMU256>, rhs: Option<EVMU256>) -> Option<EVMU256> {
+    let bool_val = |b: bool| if b { EVMU256::from(1u64) } else { EVMU256::ZERO };
+    match (op, lhs, rhs) {
+        (ConcolicOp::ADD, Some(l), Some(r)) => Some(l.wrapping_add(r)),
+        (ConcolicOp::SUB, Some(l), Some(r)) => Some(l.wrapping_sub(r)),
+        (ConcolicOp::MUL, Some(l), Some(r)) => Some(l.wrapping_mul(r)),
+        (ConcolicOp::DIV, Some(l), Some(r)) => Some(if r.is_zero() { EVMU256::ZERO } else { l / r }),
+        (ConcolicOp::UREM, Some(l), Some(r)) => Some(if r.is_zero() { EVMU256::ZERO } else { l % r }),
+        (ConcolicOp::SDIV, Some(l), Some(r)) => {
+            if r.is_zero() {
+                return Some(EVMU256::ZERO);
+            }
+            let (abs_l, neg_l) = i256_abs(l);
+            let (abs_r, neg_r) = i256_abs(r);
+            let quotient = abs_l / abs_r;
+            Some(if neg_l != neg_r { i256_neg(quotient) } else { quotient })
+        }
+        // `SMOD`/`SREM` are two names for the same opcode-level remainder in
+        // this IR (see `SolverCtx::to_bv`, which lowers both to `bvsrem`):
+        // the result takes the dividend's sign, matching the EVM's SMOD.
+        (ConcolicOp::SMOD, Some(l), Some(r)) | (ConcolicOp::SREM, Some(l), Some(r)) => {
+            if r.is_zero() {
+                return Some(EVMU256::ZERO);
+            }
+            let (abs_l, neg_l) = i256_abs(l);
+            let (abs_r, _) = i256_abs(r);
+            let rem = abs_l % abs_r;
+            Some(if neg_l { i256_neg(rem) } else { rem })
+        }
+        (ConcolicOp::AND, Some(l), Some(r)) => Some(l & r),
+        (ConcolicOp::OR, Some(l), Some(r)) => Some(l | r),
+        (ConcolicOp::XOR, Some(l), Some(r)) => Some(l ^ r),
+        (ConcolicOp::NOT, Some(l), None) => Some(!l),
+        (ConcolicOp::SHL, Some(l), Some(r)) => {
+            Some(if r >= EVMU256::from(256u64) { EVMU256::ZERO } else { l << (r.as_limbs()[0] as u32) })
+        }
+        (ConcolicOp::SHR, Some(l), Some(r)) => {
+            Some(if r >= EVMU256::from(256u64) { EVMU256::ZERO } else { l >> (r.as_limbs()[0] as u32) })
+        }
+        (ConcolicOp::SAR, Some(l), Some(r)) => {
+            let negative = i256_is_negative(l);
+            Some(if r >= EVMU256::from(256u64) {
+                if negative { EVMU256::MAX } else { EVMU256::ZERO }
+            } else {
+                let shift = r.as_limbs()[0] as u32;
+                if negative { !((!l) >> shift) } else { l >> shift }
+            })
+        }
+        (ConcolicOp::EQ, Some(l), Some(r)) => Some(bool_val(l == r)),
+        (ConcolicOp::LT, Some(l), Some(r)) => Some(bool_val(l < r)),
+        (ConcolicOp::GT, Some(l), Some(r)) => Some(bool_val(l > r)),
+        (ConcolicOp::SLT, Some(l), Some(r)) => Some(bool_val(i256_slt(l, r))),
+        (ConcolicOp::SGT, Some(l), Some(r)) => Some(bool_val(i256_slt(r, l))),
+        (ConcolicOp::LNOT, Some(l), None) => Some(bool_val(l.is_zero())),
+        (ConcolicOp::SELECT(high, low), Some(l), None) => {
+            let width = high - low + 1;
+            let shifted = l >> low;
+            let mask = if width >= 256 { EVMU256::MAX } else { (EVMU256::from(1u64) << width) - EVMU256::from(1u64) };
+            Some(shifted & mask)
+        }
+        _ => None,
+    }
+}
+
+/// Constant-folds, bottom-up, every compound node whose operands are
+/// constant leaves and whose op `eval_concrete` evaluates into a single
+/// `EVMU256` leaf, with full EVM 256-bit wrapping semantics. Concrete
+/// nodes `eval_concrete` returns `None` for (a `CONCAT` of constant bytes,
+/// a concrete `SHA3`, ...) are not folded here. Shrinks the trees handed to the solver - `depth()` is already
+/// used elsewhere as a proxy for how expensive an expression is - and means
+/// a constant subterm no longer costs a solver variable.
+pub fn fold_constants(expr: Box<Expr>) -> Box<Expr> {
+    let op = match expr.op {
+        ConcolicOp::SHA3(preimage, len) => ConcolicOp::SHA3(fold_constants(preimage), len),
+        ConcolicOp::SHA256(preimage, len) => ConcolicOp::SHA256(fold_constants(preimage), len),
+        ConcolicOp::ECRECOVER(hash, v, r, s) => ConcolicOp::ECRECOVER(
+            fold_constants(hash),
+            fold_constants(v),
+            fold_constants(r),
+            fold_constants(s),
+        ),
+        ConcolicOp::STORE(array, idx, val) => {
+            ConcolicOp::STORE(fold_constants(array), fold_constants(idx), fold_constants(val))
+        }
+        ConcolicOp::LOAD(array, idx) => ConcolicOp::LOAD(fold_constants(array), fold_constants(idx)),
+        other => other,
+    };
+    let lhs = expr.lhs.map(fold_constants);
+    let rhs = expr.rhs.map(fold_constants);
+
+    // Only a compound node (one with an lhs/rhs) is a candidate: bare
+    // leaves that are already concrete (EVMU256, CONSTBYTE, ...) are already
+    // as folded as they'll get, and rewriting them would just churn the
+    // tree (or, for CONSTBYTE, break callers like `concrete_bytes` that
+    // pattern-match the byte form specifically).
+    if lhs.is_none() && rhs.is_none() {
+        return Box::new(Expr { lhs, rhs, op });
+    }
+
+    if let Some(v) = eval_concrete(&op, lhs.as_deref().and_then(leaf_value), rhs.as_deref().and_then(leaf_value)) {
+        return Expr::const_u256(v);
+    }
+
+    Box::new(Expr { lhs, rhs, op })
+}
+
 pub fn simplify(expr: Box<Expr>) -> Box<Expr> {
     let expr = simplify_concat_select(expr);
+    // Precompiles first so a concrete SHA3/SHA256/ECRECOVER becomes a plain
+    // `EVMU256` leaf before `fold_constants` looks for arithmetic built on
+    // top of it (e.g. `ADD(SHA3(...), 1)`).
+    let expr = fold_precompiles(expr);
+    let expr = fold_constants(expr);
     expr
 }
@@ -0,0 +1,268 @@
+/// A text-based SMT-LIB2 backend for `Expr`, independent of the in-process
+/// Z3 bindings `solver.rs` uses (`bv_from_u256!` et al. couple straight to a
+/// linked Z3 build). `to_smtlib` renders an `Expr` as a `(declare-const ...)`
+/// preamble plus a single `(assert ...)`, so it can be piped into any
+/// SMT-LIB2-compliant solver binary; `parse_model` reads that solver's
+/// `get-model` response back into concrete bytes for each declared input.
+///
+/// Covers the same bitvector core `solver.rs` does (arithmetic, bitwise,
+/// shifts, comparisons, `extract`/`concat`) plus the free-variable leaves
+/// (`SLICEDINPUT`/`FINEGRAINEDINPUT`/`SYMBYTE`/`BALANCE`/`CALLVALUE`/
+/// `CALLER`). It does NOT cover the theory-of-arrays memory nodes
+/// (`ARRAY`/`STORE`/`LOAD`) or the uninterpreted-function precompiles
+/// (`SHA3`/`SHA256`/`ECRECOVER`) - by the time a path constraint reaches a
+/// solver those are almost always folded to a concrete `EVMU256` already
+/// (see `expr::fold_precompiles`/`expr::fold_constants`); `to_smtlib` panics
+/// if one survives.
+use crate::evm::concolic::expr::{ConcolicOp, Expr};
+use crate::evm::types::EVMU256;
+use std::collections::HashMap;
+
+/// A `declare-const` this expression needs, in first-use order.
+struct Declaration {
+    name: String,
+    width: u32,
+}
+
+#[derive(Default)]
+struct SmtLibBuilder {
+    declarations: Vec<Declaration>,
+    declared_names: std::collections::HashSet<String>,
+}
+
+impl SmtLibBuilder {
+    /// Registers `name` as a free `width`-bit variable, if it hasn't been
+    /// already, and returns its SMT-LIB2 reference (just `name` itself).
+    fn declare(&mut self, name: String, width: u32) -> String {
+        if self.declared_names.insert(name.clone()) {
+            self.declarations.push(Declaration { name: name.clone(), width });
+        }
+        name
+    }
+
+    /// The bit-width `expr` lowers to, mirroring how `SolverCtx::to_bv`
+    /// sizes each node (leaves are 256-bit except a symbolic byte, binops
+    /// keep their operand width, `EQ`/`LT`/.../`LNOT` widen their `Bool`
+    /// result back to 256 bits, `SELECT`/`CONCAT` compute their own).
+    fn width(&self, expr: &Expr) -> u32 {
+        match &expr.op {
+            ConcolicOp::CONSTBYTE(_) | ConcolicOp::SYMBYTE(_) => 8,
+            ConcolicOp::SELECT(high, low) => high - low + 1,
+            ConcolicOp::CONCAT => {
+                self.width(expr.lhs.as_ref().expect("concat missing lhs"))
+                    + self.width(expr.rhs.as_ref().expect("concat missing rhs"))
+            }
+            ConcolicOp::NOT => self.width(expr.lhs.as_ref().expect("not missing operand")),
+            ConcolicOp::ADD
+            | ConcolicOp::SUB
+            | ConcolicOp::MUL
+            | ConcolicOp::DIV
+            | ConcolicOp::SDIV
+            | ConcolicOp::SMOD
+            | ConcolicOp::UREM
+            | ConcolicOp::SREM
+            | ConcolicOp::AND
+            | ConcolicOp::OR
+            | ConcolicOp::XOR
+            | ConcolicOp::SHL
+            | ConcolicOp::SHR
+            | ConcolicOp::SAR => self.width(expr.lhs.as_ref().expect("binop missing lhs")),
+            _ => 256,
+        }
+    }
+
+    fn zero(width: u32) -> String {
+        format!("(_ bv0 {})", width)
+    }
+
+    fn one_256() -> String {
+        "(_ bv1 256)".to_string()
+    }
+
+    /// Renders `expr` as an SMT-LIB2 term, registering any free-variable
+    /// leaves it touches along the way.
+    fn term(&mut self, expr: &Expr) -> String {
+        macro_rules! bin {
+            ($f:expr) => {{
+                let lhs = self.term(expr.lhs.as_ref().expect("binop missing lhs"));
+                let rhs = self.term(expr.rhs.as_ref().expect("binop missing rhs"));
+                format!("({} {} {})", $f, lhs, rhs)
+            }};
+        }
+        macro_rules! bin_bool_widened {
+            ($f:expr) => {{
+                let lhs = self.term(expr.lhs.as_ref().expect("binop missing lhs"));
+                let rhs = self.term(expr.rhs.as_ref().expect("binop missing rhs"));
+                format!("(ite ({} {} {}) {} {})", $f, lhs, rhs, Self::one_256(), Self::zero(256))
+            }};
+        }
+
+        match &expr.op {
+            ConcolicOp::EVMU256(v) => format!("(_ bv{} 256)", v),
+            ConcolicOp::CONSTBYTE(b) => format!("(_ bv{} 8)", b),
+            ConcolicOp::ADD => bin!("bvadd"),
+            ConcolicOp::SUB => bin!("bvsub"),
+            ConcolicOp::MUL => bin!("bvmul"),
+            ConcolicOp::DIV => bin!("bvudiv"),
+            ConcolicOp::SDIV => bin!("bvsdiv"),
+            ConcolicOp::UREM => bin!("bvurem"),
+            ConcolicOp::SREM | ConcolicOp::SMOD => bin!("bvsrem"),
+            ConcolicOp::AND => bin!("bvand"),
+            ConcolicOp::OR => bin!("bvor"),
+            ConcolicOp::XOR => bin!("bvxor"),
+            ConcolicOp::NOT => {
+                let lhs = self.term(expr.lhs.as_ref().expect("not missing operand"));
+                format!("(bvnot {})", lhs)
+            }
+            ConcolicOp::SHL => bin!("bvshl"),
+            ConcolicOp::SHR => bin!("bvlshr"),
+            ConcolicOp::SAR => bin!("bvashr"),
+            ConcolicOp::LT => bin_bool_widened!("bvult"),
+            ConcolicOp::GT => bin_bool_widened!("bvugt"),
+            ConcolicOp::SLT => bin_bool_widened!("bvslt"),
+            ConcolicOp::SGT => bin_bool_widened!("bvsgt"),
+            ConcolicOp::EQ => bin_bool_widened!("="),
+            ConcolicOp::LNOT => {
+                let operand_width = self.width(expr.lhs.as_ref().expect("lnot missing operand"));
+                let lhs = self.term(expr.lhs.as_ref().expect("lnot missing operand"));
+                format!("(ite (= {} {}) {} {})", lhs, Self::zero(operand_width), Self::one_256(), Self::zero(256))
+            }
+            ConcolicOp::CONCAT => bin!("concat"),
+            ConcolicOp::SELECT(high, low) => {
+                let lhs = self.term(expr.lhs.as_ref().expect("select missing operand"));
+                format!("((_ extract {} {}) {})", high, low, lhs)
+            }
+            ConcolicOp::SYMBYTE(name) => self.declare(format!("symbyte_{}", name), 8),
+            ConcolicOp::SLICEDINPUT(idx) => self.declare(format!("calldata_word_{}", idx), 256),
+            ConcolicOp::FINEGRAINEDINPUT(start, end) => {
+                self.declare(format!("calldata_slice_{}_{}", start, end), 256)
+            }
+            ConcolicOp::BALANCE => self.declare("balance".to_string(), 256),
+            ConcolicOp::CALLVALUE => self.declare("callvalue".to_string(), 256),
+            ConcolicOp::CALLER => self.declare("caller".to_string(), 256),
+            other => panic!("to_smtlib: {:?} has no SMT-LIB2 rendering", other),
+        }
+    }
+}
+
+/// Serializes `expr` to a standalone SMT-LIB2 script: a `declare-const` for
+/// every distinct free-variable leaf, followed by `(assert ...)` of `expr`
+/// being EVM-truthy (nonzero) - the same convention `ConcolicSolver::assert`
+/// uses for the in-process Z3 path.
+pub fn to_smtlib(expr: &Expr) -> String {
+    let mut builder = SmtLibBuilder::default();
+    let term = builder.term(expr);
+    let width = builder.width(expr);
+
+    let mut out = String::new();
+    for decl in &builder.declarations {
+        out.push_str(&format!("(declare-const {} (_ BitVec {}))\n", decl.name, decl.width));
+    }
+    out.push_str(&format!("(assert (not (= {} {})))\n", term, SmtLibBuilder::zero(width)));
+    out
+}
+
+/// Converts a bitvector literal token (`#xDEAD` or `#b1010...`) into its
+/// big-endian bytes; the `(_ bvN W)` form goes through `decimal_to_bytes`.
+fn bv_literal_to_bytes(token: &str) -> Option<Vec<u8>> {
+    if let Some(hex_digits) = token.strip_prefix("#x") {
+        let padded = if hex_digits.len() % 2 == 1 { format!("0{}", hex_digits) } else { hex_digits.to_string() };
+        hex::decode(padded).ok()
+    } else if let Some(bits) = token.strip_prefix("#b") {
+        let mut padded = bits.to_string();
+        while padded.len() % 8 != 0 {
+            padded.insert(0, '0');
+        }
+        Some(padded.as_bytes().chunks(8).map(|chunk| u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 2).ok()).collect::<Option<Vec<_>>>()?)
+    } else {
+        None
+    }
+}
+
+fn decimal_to_bytes(value: &str, width: u32) -> Option<Vec<u8>> {
+    let v = EVMU256::from_str_radix(value, 10).ok()?;
+    let bytes: [u8; 32] = v.to_be_bytes();
+    let n = ((width as usize) + 7) / 8;
+    Some(bytes[32 - n..].to_vec())
+}
+
+/// A minimal SMT-LIB2 tokenizer: splits on whitespace and treats `(`/`)` as
+/// their own tokens. Enough to walk a solver's `get-model` response without
+/// a full s-expression parser.
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut cur = String::new();
+    for c in s.chars() {
+        if c == '(' || c == ')' {
+            if !cur.is_empty() {
+                tokens.push(std::mem::take(&mut cur));
+            }
+            tokens.push(c.to_string());
+        } else if c.is_whitespace() {
+            if !cur.is_empty() {
+                tokens.push(std::mem::take(&mut cur));
+            }
+        } else {
+            cur.push(c);
+        }
+    }
+    if !cur.is_empty() {
+        tokens.push(cur);
+    }
+    tokens
+}
+
+/// Parses a solver's `get-model` response (a sequence of `(define-fun NAME
+/// () (_ BitVec W) VALUE)` entries) into `{ name -> big-endian bytes }`,
+/// ready to feed back into a `Solution`/calldata as the concrete values for
+/// whatever `to_smtlib` declared.
+pub fn parse_model(output: &str) -> HashMap<String, Vec<u8>> {
+    let tokens = tokenize(output);
+    let mut model = HashMap::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i] != "define-fun" {
+            i += 1;
+            continue;
+        }
+        // define-fun NAME ( ) ( _ BitVec W ) VALUE
+        let Some(name) = tokens.get(i + 1) else { break };
+        if tokens.get(i + 2).map(String::as_str) != Some("(") || tokens.get(i + 3).map(String::as_str) != Some(")") {
+            i += 1;
+            continue;
+        }
+        if tokens.get(i + 4).map(String::as_str) != Some("(")
+            || tokens.get(i + 5).map(String::as_str) != Some("_")
+            || tokens.get(i + 6).map(String::as_str) != Some("BitVec")
+        {
+            i += 1;
+            continue;
+        }
+        let Some(width) = tokens.get(i + 7).and_then(|w| w.parse::<u32>().ok()) else {
+            i += 1;
+            continue;
+        };
+        if tokens.get(i + 8).map(String::as_str) != Some(")") {
+            i += 1;
+            continue;
+        }
+
+        let value_bytes = match tokens.get(i + 9).map(String::as_str) {
+            Some("(") => {
+                // (_ bvNUMBER WIDTH)
+                let value = tokens.get(i + 11).and_then(|v| v.strip_prefix("bv")).map(str::to_string);
+                value.and_then(|v| decimal_to_bytes(&v, width))
+            }
+            Some(tok) => bv_literal_to_bytes(tok),
+            None => None,
+        };
+
+        if let Some(bytes) = value_bytes {
+            model.insert(name.clone(), bytes);
+        }
+        i += 9;
+    }
+
+    model
+}
@@ -0,0 +1,260 @@
+/// Lowers a `concolic::Expr` tree (see `expr.rs`) into Z3 bitvector/array
+/// terms.
+///
+/// Most of the IR maps directly onto Z3's bitvector theory, but a few nodes
+/// can't: `SHA3`/`ECRECOVER`/`SHA256` have no SMT-expressible definition, and
+/// a symbolic memory/calldata offset needs the theory of arrays rather than
+/// a fixed bit-vector index. Both are modeled as uninterpreted Z3 objects
+/// (functions and array constants respectively) so the solver still gets
+/// functional consistency - equal preimages are forced to equal digests,
+/// equal array+index pairs to equal loads - for free, without us having to
+/// teach it the real math.
+use crate::bv_from_u256;
+use crate::evm::concolic::expr::{ConcolicOp, Expr};
+use std::collections::HashMap;
+use z3::ast::{Array, Ast, BV};
+use z3::{Context, FuncDecl, Sort};
+
+const WORD_BITS: u32 = 256;
+
+/// Expands to `<lhs>.<$f>(&<rhs>)` as a `Bool`, since EQ/LT/... compare two
+/// bitvectors but (unlike ADD/SUB/...) must produce a `Bool` before being
+/// widened back to a 256-bit 0/1 value by `bool_to_bv`.
+macro_rules! bin_bool {
+    ($self:ident, $expr:expr, $f:ident) => {{
+        let lhs = $self.to_bv($expr.lhs.as_ref().expect("binop missing lhs"));
+        let rhs = $self.to_bv($expr.rhs.as_ref().expect("binop missing rhs"));
+        lhs.$f(&rhs)
+    }};
+}
+
+/// Per-solve-attempt state: caches the uninterpreted function/array
+/// declarations so repeated references to the same hash width or the same
+/// named region (e.g. "memory") resolve to the same Z3 object, which is
+/// what makes functional consistency apply across the whole formula.
+pub struct SolverCtx<'ctx> {
+    ctx: &'ctx Context,
+    keccak_fns: HashMap<u32, FuncDecl<'ctx>>,
+    sha256_fns: HashMap<u32, FuncDecl<'ctx>>,
+    ecrecover_fn: Option<FuncDecl<'ctx>>,
+    arrays: HashMap<String, Array<'ctx>>,
+    free_vars: HashMap<String, BV<'ctx>>,
+}
+
+impl<'ctx> SolverCtx<'ctx> {
+    pub fn new(ctx: &'ctx Context) -> Self {
+        Self {
+            ctx,
+            keccak_fns: HashMap::new(),
+            sha256_fns: HashMap::new(),
+            ecrecover_fn: None,
+            arrays: HashMap::new(),
+            free_vars: HashMap::new(),
+        }
+    }
+
+    fn keccak_fn(&mut self, preimage_bits: u32) -> FuncDecl<'ctx> {
+        self.keccak_fns
+            .entry(preimage_bits)
+            .or_insert_with(|| {
+                FuncDecl::new(
+                    self.ctx,
+                    format!("keccak256_{}", preimage_bits),
+                    &[Sort::bitvector(self.ctx, preimage_bits)],
+                    &Sort::bitvector(self.ctx, WORD_BITS),
+                )
+            })
+            .clone()
+    }
+
+    fn sha256_fn(&mut self, preimage_bits: u32) -> FuncDecl<'ctx> {
+        self.sha256_fns
+            .entry(preimage_bits)
+            .or_insert_with(|| {
+                FuncDecl::new(
+                    self.ctx,
+                    format!("sha256_{}", preimage_bits),
+                    &[Sort::bitvector(self.ctx, preimage_bits)],
+                    &Sort::bitvector(self.ctx, WORD_BITS),
+                )
+            })
+            .clone()
+    }
+
+    fn ecrecover_fn(&mut self) -> FuncDecl<'ctx> {
+        if self.ecrecover_fn.is_none() {
+            let bv256 = Sort::bitvector(self.ctx, WORD_BITS);
+            self.ecrecover_fn = Some(FuncDecl::new(
+                self.ctx,
+                "ecrecover",
+                &[&bv256, &bv256, &bv256, &bv256],
+                &bv256,
+            ));
+        }
+        self.ecrecover_fn.clone().unwrap()
+    }
+
+    fn named_array(&mut self, name: &str) -> Array<'ctx> {
+        self.arrays
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let idx_sort = Sort::bitvector(self.ctx, WORD_BITS);
+                let val_sort = Sort::bitvector(self.ctx, WORD_BITS);
+                Array::fresh_const(self.ctx, name, &idx_sort, &val_sort)
+            })
+            .clone()
+    }
+
+    /// A free (uninterpreted) 256-bit variable for an execution-environment
+    /// value we don't otherwise model precisely (balance, caller, ...). Each
+    /// distinct `name` gets its own variable, reused on repeat lookups.
+    fn free_var(&mut self, name: &str) -> BV<'ctx> {
+        self.free_vars
+            .entry(name.to_string())
+            .or_insert_with(|| BV::fresh_const(self.ctx, name, WORD_BITS))
+            .clone()
+    }
+
+    /// Lowers `expr` to a bitvector term.
+    pub fn to_bv(&mut self, expr: &Expr) -> BV<'ctx> {
+        macro_rules! bin {
+            ($f:ident) => {{
+                let lhs = self.to_bv(expr.lhs.as_ref().expect("binop missing lhs"));
+                let rhs = self.to_bv(expr.rhs.as_ref().expect("binop missing rhs"));
+                lhs.$f(&rhs)
+            }};
+        }
+
+        match &expr.op {
+            ConcolicOp::EVMU256(v) => bv_from_u256!(v, self.ctx),
+            ConcolicOp::ADD => bin!(bvadd),
+            ConcolicOp::SUB => bin!(bvsub),
+            ConcolicOp::MUL => bin!(bvmul),
+            ConcolicOp::DIV => bin!(bvudiv),
+            ConcolicOp::SDIV => bin!(bvsdiv),
+            ConcolicOp::UREM => bin!(bvurem),
+            ConcolicOp::SREM | ConcolicOp::SMOD => bin!(bvsrem),
+            ConcolicOp::AND => bin!(bvand),
+            ConcolicOp::OR => bin!(bvor),
+            ConcolicOp::XOR => bin!(bvxor),
+            ConcolicOp::NOT => self.to_bv(expr.lhs.as_ref().expect("not missing operand")).bvnot(),
+            ConcolicOp::SHL => bin!(bvshl),
+            ConcolicOp::SHR => bin!(bvlshr),
+            ConcolicOp::SAR => bin!(bvashr),
+            ConcolicOp::LT => self.bool_to_bv(bin_bool!(self, expr, bvult)),
+            ConcolicOp::GT => self.bool_to_bv(bin_bool!(self, expr, bvugt)),
+            ConcolicOp::SLT => self.bool_to_bv(bin_bool!(self, expr, bvslt)),
+            ConcolicOp::SGT => self.bool_to_bv(bin_bool!(self, expr, bvsgt)),
+            ConcolicOp::EQ => self.bool_to_bv(bin_bool!(self, expr, _eq)),
+            ConcolicOp::LNOT => {
+                let operand = self.to_bv(expr.lhs.as_ref().expect("lnot missing operand"));
+                let zero = BV::from_u64(self.ctx, 0, operand.get_size());
+                self.bool_to_bv(operand._eq(&zero))
+            }
+            ConcolicOp::CONCAT => bin!(concat),
+            ConcolicOp::SELECT(hi, lo) => self
+                .to_bv(expr.lhs.as_ref().expect("select missing operand"))
+                .extract(*hi, *lo),
+            ConcolicOp::SYMBYTE(name) => self.free_var(&format!("symbyte_{}", name)).extract(7, 0),
+            ConcolicOp::CONSTBYTE(b) => BV::from_u64(self.ctx, *b as u64, 8),
+            ConcolicOp::SLICEDINPUT(idx) => self.free_var(&format!("calldata_word_{}", idx)),
+            ConcolicOp::FINEGRAINEDINPUT(start, end) => {
+                self.free_var(&format!("calldata_slice_{}_{}", start, end))
+            }
+            ConcolicOp::BALANCE => self.free_var("balance"),
+            ConcolicOp::CALLVALUE => self.free_var("callvalue"),
+            ConcolicOp::CALLER => self.free_var("caller"),
+            ConcolicOp::SHA3(preimage, len) => {
+                let preimage_bv = self.to_bv(preimage);
+                let f = self.keccak_fn((*len as u32) * 8);
+                f.apply(&[&preimage_bv]).as_bv().expect("keccak returns a bitvector")
+            }
+            ConcolicOp::SHA256(preimage, len) => {
+                let preimage_bv = self.to_bv(preimage);
+                let f = self.sha256_fn((*len as u32) * 8);
+                f.apply(&[&preimage_bv]).as_bv().expect("sha256 returns a bitvector")
+            }
+            ConcolicOp::ECRECOVER(hash, v, r, s) => {
+                let args = [self.to_bv(hash), self.to_bv(v), self.to_bv(r), self.to_bv(s)];
+                let f = self.ecrecover_fn();
+                f.apply(&[&args[0], &args[1], &args[2], &args[3]])
+                    .as_bv()
+                    .expect("ecrecover returns a bitvector")
+            }
+            ConcolicOp::LOAD(array, idx) => {
+                let array = self.to_array(array);
+                let idx_bv = self.to_bv(idx);
+                array.select(&idx_bv).as_bv().expect("array holds bitvectors")
+            }
+            ConcolicOp::ARRAY(_) | ConcolicOp::STORE(_, _, _) => {
+                panic!("array-sorted expr used where a bitvector was expected")
+            }
+        }
+    }
+
+    /// Lowers `expr` to an SMT array term (only `ARRAY`/`STORE` nodes).
+    pub fn to_array(&mut self, expr: &Expr) -> Array<'ctx> {
+        match &expr.op {
+            ConcolicOp::ARRAY(name) => self.named_array(name),
+            ConcolicOp::STORE(array, idx, val) => {
+                let array = self.to_array(array);
+                let idx_bv = self.to_bv(idx);
+                let val_bv = self.to_bv(val);
+                array.store(&idx_bv, &val_bv)
+            }
+            _ => panic!("bitvector-sorted expr used where an array was expected"),
+        }
+    }
+
+    fn bool_to_bv(&self, b: z3::ast::Bool<'ctx>) -> BV<'ctx> {
+        b.ite(&BV::from_u64(self.ctx, 1, 256), &BV::from_u64(self.ctx, 0, 256))
+    }
+}
+
+/// An incremental wrapper over a single Z3 `Solver`, with scopes meant to
+/// mirror `ConcolicHost`'s `push_ctx`/`pop_ctx` call-context stack one for
+/// one: `push_scope` on entering a `CALL`/`DELEGATECALL`/`STATICCALL`,
+/// `pop_scope` on `on_return` (including a `REVERT`). Since everything
+/// asserted in an outer scope stays asserted across an inner scope's
+/// push/pop, a deep call tree only pays for lowering+asserting each
+/// constraint once, rather than re-building and re-asserting the whole
+/// flattened path on every query.
+pub struct ConcolicSolver<'ctx> {
+    ctx: &'ctx Context,
+    solver_ctx: SolverCtx<'ctx>,
+    z3_solver: z3::Solver<'ctx>,
+}
+
+impl<'ctx> ConcolicSolver<'ctx> {
+    pub fn new(ctx: &'ctx Context) -> Self {
+        Self {
+            ctx,
+            solver_ctx: SolverCtx::new(ctx),
+            z3_solver: z3::Solver::new(ctx),
+        }
+    }
+
+    /// Opens a new incremental scope. Everything `assert`ed afterward is
+    /// undone by the matching `pop_scope`.
+    pub fn push_scope(&mut self) {
+        self.z3_solver.push();
+    }
+
+    /// Discards every assertion made since the matching `push_scope`.
+    pub fn pop_scope(&mut self) {
+        self.z3_solver.pop(1);
+    }
+
+    /// Lowers `expr` and asserts it (as nonzero, i.e. EVM-truthy) into the
+    /// current scope.
+    pub fn assert(&mut self, expr: &Expr) {
+        let bv = self.solver_ctx.to_bv(expr);
+        let zero = BV::from_u64(self.ctx, 0, bv.get_size());
+        self.z3_solver.assert(&bv._eq(&zero).not());
+    }
+
+    /// Checks satisfiability of everything asserted across every open scope.
+    pub fn check(&mut self) -> z3::SatResult {
+        self.z3_solver.check()
+    }
+}
@@ -6,6 +6,7 @@ use std::rc::Rc;
 
 use crate::evm::types::EVMAddress;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FuzzerTypes {
     CMP,
     DATAFLOW,
@@ -29,6 +30,27 @@ impl StorageFetchingMode {
     }
 }
 
+/// Which [`crate::generic_vm::vm_executor::VmFactory`] backend drives the
+/// fuzzing campaign, following the VM-factory / `--jitvm`-style pluggable-VM
+/// pattern. `Evm` (backed by `EvmVmFactory`) is the only backend shipped
+/// today; a future JIT/fast interpreter or symbolic engine would add a
+/// variant here and its own `VmFactory` impl. `EVMCorpusInitializer` holds
+/// its executor as a `GenericVM`, but downcasts it to `EVMExecutor` for
+/// `set_code` and host-state reads, so it works only with the `Evm`
+/// backend.
+pub enum VmBackend {
+    Evm,
+}
+
+impl VmBackend {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "evm" => Ok(VmBackend::Evm),
+            _ => Err(format!("Unknown VM backend: {}", s)),
+        }
+    }
+}
+
 impl FuzzerTypes {
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s {
@@ -60,4 +82,16 @@ pub struct Config<VS, Addr, Code, By, Out, I, S, CI> {
     pub typed_bug: bool,
     pub selfdestruct_bug: bool,
     pub arbitrary_external_call: bool,
+    /// Enables [`crate::evm::oracles::gas_usage::GasUsageOracle`] and
+    /// [`crate::evm::oracles::gas_usage::GasGriefingOracle`]. `gas_threshold`
+    /// is the absolute per-transaction gas ceiling `GasUsageOracle` flags
+    /// above.
+    pub gas_oracle: bool,
+    pub gas_threshold: u64,
+    pub vm_backend: VmBackend,
+    /// Optional JSON file of `{ "function", "args", "comment" }` test
+    /// cases (see `EVMCorpusInitializer::seed_from_file`), decoded into
+    /// concrete `EVMInput` seeds added to the corpus before fuzzing
+    /// begins.
+    pub corpus_seed_file: Option<String>,
 }
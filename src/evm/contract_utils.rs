@@ -1,12 +1,15 @@
+use crate::evm::onchain::endpoints::OnChainConfig;
 use crate::evm::types::{fixed_address, generate_random_address, EVMAddress, EVMFuzzState};
 use glob::glob;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::process::Command;
 
 use itertools::Itertools;
 use std::io::Read;
 use std::path::Path;
+use std::str::FromStr;
 
 extern crate crypto;
 
@@ -374,6 +377,353 @@ impl ContractLoader {
 
         ContractLoader { contracts, abis }
     }
+
+    /// Loads a single already-deployed on-chain contract by `address`,
+    /// fetching its runtime bytecode (and, best-effort, its verified ABI)
+    /// through `onchain` at whatever block `onchain` was built for.
+    ///
+    /// `local_proxy_addr`, when set, is used as the implementation address
+    /// to fetch code from instead of `onchain`'s own EIP-1967/1822 slot
+    /// resolution (`OnChainConfig::resolve_implementation`) - useful when a
+    /// proxy doesn't follow either standard, or the operator already knows
+    /// the real implementation. `replacements` overrides the fetched code
+    /// entirely for addresses present in it (e.g. to fuzz a local patch of
+    /// a verified contract instead of what's actually live).
+    ///
+    /// Storage isn't fetched here: like `is_code_deployed` contracts from
+    /// `from_prefix`, only the bytecode is seeded up front, and any slot an
+    /// execution actually reads is resolved lazily against `onchain` by the
+    /// onchain-aware host.
+    pub fn from_address(
+        address: &str,
+        _state: &mut EVMFuzzState,
+        onchain: &mut OnChainConfig,
+        local_proxy_addr: Option<EVMAddress>,
+        replacements: &HashMap<EVMAddress, Vec<u8>>,
+    ) -> Self {
+        let deployed_address =
+            EVMAddress::from_str(address).expect("invalid onchain target address");
+
+        let code = match replacements.get(&deployed_address) {
+            Some(replacement) => {
+                println!("Using replacement bytecode for {}", address);
+                replacement.clone()
+            }
+            None => {
+                let code_address = local_proxy_addr.unwrap_or(deployed_address);
+                onchain.get_contract_code(code_address, false).bytes().to_vec()
+            }
+        };
+
+        if code.is_empty() {
+            println!(
+                "Warning: no bytecode found for {} at block {}, does the contract exist there?",
+                address, onchain.block_number
+            );
+        }
+
+        let abi = onchain
+            .fetch_abi(deployed_address)
+            .map(|abi_str| Self::parse_abi_str(&abi_str))
+            .unwrap_or_else(|| {
+                println!(
+                    "Contract {} has no verified ABI, falling back to signature extraction",
+                    address
+                );
+                vec![]
+            });
+
+        let contract_result = ContractInfo {
+            name: address.to_string(),
+            code,
+            abi: abi.clone(),
+            is_code_deployed: true,
+            constructor_args: vec![],
+            deployed_address,
+            source_map: None,
+        };
+
+        Self {
+            contracts: if contract_result.code.len() > 0 {
+                vec![contract_result]
+            } else {
+                vec![]
+            },
+            abis: vec![ABIInfo {
+                source: address.to_string(),
+                abi,
+            }],
+        }
+    }
+
+    /// Parses an `onchain_replacements_file`: a JSON object of `{
+    /// "<address>": "<hex bytecode>" }` entries, consumed by
+    /// [`Self::from_address`] to override specific addresses' fetched
+    /// bytecode entirely (e.g. to fuzz a local patch instead of what's
+    /// actually live). An empty `path` (the CLI's default) means no
+    /// replacements.
+    pub fn parse_replacements_file(path: &str) -> HashMap<EVMAddress, Vec<u8>> {
+        if path.is_empty() {
+            return HashMap::new();
+        }
+        let data = std::fs::read_to_string(path).expect("failed to read onchain replacements file");
+        let json: HashMap<String, String> =
+            serde_json::from_str(&data).expect("failed to parse onchain replacements file");
+        json.into_iter()
+            .map(|(addr, code)| {
+                let addr = EVMAddress::from_str(addr.as_str())
+                    .expect("invalid address in onchain replacements file");
+                let code = hex::decode(code.trim_start_matches("0x"))
+                    .expect("invalid hex bytecode in onchain replacements file");
+                (addr, code)
+            })
+            .collect()
+    }
+
+    /// Compiles `path` (a file or glob) with `solc --combined-json
+    /// abi,bin,bin-runtime,srcmap-runtime` and loads the result, so a whole
+    /// Solidity project can be fuzzed without a separate build step
+    /// producing `.abi`/`.bin` files for [`Self::from_glob`] to pick up.
+    ///
+    /// `solc_version` pins the compiler (looked up as `solc-<version>` on
+    /// `PATH`, the naming `solc-select` and most CI images install under);
+    /// when `None`, the first `pragma solidity` pin found under `path` is
+    /// used. `remappings` are forwarded to `solc` as-is (e.g.
+    /// `@openzeppelin/=lib/openzeppelin-contracts/`).
+    pub fn from_solc_source(
+        path: &str,
+        state: &mut EVMFuzzState,
+        solc_version: Option<String>,
+        remappings: &Vec<String>,
+    ) -> Self {
+        let solc_version = solc_version.or_else(|| detect_pragma_version(path));
+        let solc_bin = resolve_solc(&solc_version);
+
+        let mut cmd = Command::new(&solc_bin);
+        cmd.arg("--combined-json")
+            .arg("abi,bin,bin-runtime,srcmap-runtime")
+            .arg("--allow-paths")
+            .arg(".");
+        for remapping in remappings {
+            cmd.arg(remapping);
+        }
+        cmd.arg(path);
+
+        let output = cmd
+            .output()
+            .unwrap_or_else(|e| panic!("failed to run `{}`: {:?}", solc_bin, e));
+        if !output.status.success() {
+            panic!(
+                "{} failed to compile {}: {}",
+                solc_bin,
+                path,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let combined_json =
+            String::from_utf8(output.stdout).expect("solc combined-json output is not utf8");
+        Self::from_combined_json(combined_json, state)
+    }
+
+    /// Turns a `solc --combined-json abi,bin,bin-runtime,srcmap-runtime`
+    /// blob into fully-populated `ContractInfo`s: `abi` is parsed through
+    /// [`Self::parse_abi_str`] and `source_map` through
+    /// [`decode_instructions`], same as the `combined.json` path in
+    /// [`Self::from_glob`]. Unlike that path, this also links any
+    /// `__$...$__` library placeholders left in `bin` against whatever
+    /// other contract in the same blob the placeholder hash resolves to,
+    /// deploying that library to a fresh address the first time it's seen.
+    pub fn from_combined_json(json: String, state: &mut EVMFuzzState) -> Self {
+        let map_json: Value = serde_json::from_str(&json).expect("failed to parse combined json");
+        let contracts = map_json["contracts"]
+            .as_object()
+            .expect("contracts not found");
+        let file_list = map_json["sourceList"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .map(|x| x.as_str().expect("sourceList is not string").to_string())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+
+        let placeholder_to_name: HashMap<String, String> = contracts
+            .keys()
+            .map(|qualified_name| (library_placeholder_hash(qualified_name), qualified_name.clone()))
+            .collect();
+
+        let mut library_addresses: HashMap<String, EVMAddress> = HashMap::new();
+        let mut contracts_out = vec![];
+        let mut abis = vec![];
+
+        for (qualified_name, info) in contracts {
+            let bin = match info["bin"].as_str() {
+                Some(b) if !b.is_empty() => b,
+                // interfaces and abstract contracts compile to no bytecode
+                _ => continue,
+            };
+            let contract_name = qualified_name
+                .split(':')
+                .last()
+                .unwrap_or(qualified_name)
+                .to_string();
+
+            let abi_value = &info["abi"];
+            let abi_str = if let Some(s) = abi_value.as_str() {
+                s.to_string()
+            } else {
+                abi_value.to_string()
+            };
+            let abi = Self::parse_abi_str(&abi_str);
+
+            let deployed_address = *library_addresses
+                .entry(qualified_name.clone())
+                .or_insert_with(|| generate_random_address(state));
+            let mut code = link_library_placeholders(
+                bin,
+                &placeholder_to_name,
+                &mut library_addresses,
+                state,
+            );
+
+            let mut constructor_args = vec![];
+            if let Some(ctor) = abi.iter().find(|abi| abi.is_constructor) {
+                let mut abi_instance = get_abi_type_boxed_with_address(
+                    &ctor.abi,
+                    fixed_address(FIX_DEPLOYER).0.to_vec(),
+                );
+                abi_instance.set_func_with_name(ctor.function, ctor.function_name.clone());
+                constructor_args = abi_instance.get().get_bytes();
+            }
+            code.extend(constructor_args.clone());
+
+            let source_map = match (info["bin-runtime"].as_str(), info["srcmap-runtime"].as_str())
+            {
+                (Some(bin_runtime), Some(srcmap_runtime)) => {
+                    let bin_runtime_bytes =
+                        hex::decode(bin_runtime).expect("bin-runtime is not hex");
+                    Some(decode_instructions(
+                        bin_runtime_bytes,
+                        srcmap_runtime.to_string(),
+                        &file_list,
+                    ))
+                }
+                _ => None,
+            };
+
+            contracts_out.push(ContractInfo {
+                name: contract_name.clone(),
+                code,
+                abi: abi.clone(),
+                is_code_deployed: false,
+                constructor_args,
+                deployed_address,
+                source_map,
+            });
+            abis.push(ABIInfo {
+                source: contract_name,
+                abi,
+            });
+        }
+
+        ContractLoader {
+            contracts: contracts_out,
+            abis,
+        }
+    }
+}
+
+/// `solc-select`-style lookup: prefer a `solc-<version>` binary on `PATH`
+/// when a version is known, falling back to plain `solc`.
+fn resolve_solc(version: &Option<String>) -> String {
+    if let Some(v) = version {
+        let versioned = format!("solc-{}", v);
+        if Command::new(&versioned).arg("--version").output().is_ok() {
+            return versioned;
+        }
+        println!(
+            "{} not found on PATH, falling back to default `solc`",
+            versioned
+        );
+    }
+    "solc".to_string()
+}
+
+/// Best-effort `pragma solidity` sniff over every `.sol` file matched by
+/// `path`, so callers of [`ContractLoader::from_solc_source`] don't have to
+/// pin a version explicitly. Returns the first concrete version pin found
+/// (e.g. `pragma solidity ^0.8.19;` -> `0.8.19`).
+fn detect_pragma_version(path: &str) -> Option<String> {
+    for entry in glob(path).ok()?.flatten() {
+        if entry.extension().and_then(|e| e.to_str()) != Some("sol") {
+            continue;
+        }
+        let src = std::fs::read_to_string(&entry).ok()?;
+        for line in src.lines() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("pragma solidity") {
+                let version = rest
+                    .trim_end_matches(';')
+                    .trim()
+                    .trim_start_matches(|c: char| !c.is_ascii_digit())
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+                    .to_string();
+                if !version.is_empty() {
+                    return Some(version);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The first 17 bytes (34 hex chars) of `keccak256(fully_qualified_name)`,
+/// the hash `solc` embeds in a library placeholder
+/// (`__$<34 hex chars>$__`) in unlinked bytecode.
+fn library_placeholder_hash(fully_qualified_name: &str) -> String {
+    let mut digest = [0u8; 32];
+    let mut hasher = Sha3::keccak256();
+    hasher.input_str(fully_qualified_name);
+    hasher.result(&mut digest);
+    hex::encode(&digest[..17])
+}
+
+/// Replaces every `__$...$__` library placeholder in `bin` with the address
+/// of the library it resolves to in `placeholder_to_name`, deploying that
+/// library (i.e. reserving it an address in `library_addresses`) the first
+/// time it's referenced.
+fn link_library_placeholders(
+    bin: &str,
+    placeholder_to_name: &HashMap<String, String>,
+    library_addresses: &mut HashMap<String, EVMAddress>,
+    state: &mut EVMFuzzState,
+) -> Vec<u8> {
+    let mut linked = bin.to_string();
+    while let Some(start) = linked.find("__$") {
+        let end = start + 40; // "__$" + 34 hex chars + "$__"
+        if end > linked.len() {
+            break;
+        }
+        let placeholder = linked[start..end].to_string();
+        let hash = &placeholder[3..37];
+        let address = match placeholder_to_name.get(hash) {
+            Some(name) => *library_addresses
+                .entry(name.clone())
+                .or_insert_with(|| generate_random_address(state)),
+            None => {
+                println!(
+                    "Warning: could not resolve library placeholder {}, using a random address",
+                    placeholder
+                );
+                generate_random_address(state)
+            }
+        };
+        linked.replace_range(start..end, &hex::encode(address.0));
+    }
+    hex::decode(linked).expect("linked bytecode is not hex")
 }
 
 type ContractsSourceMapInfo = HashMap<String, HashMap<usize, SourceMapLocation>>;
@@ -445,3 +795,55 @@ pub fn extract_sig_from_contract(code: &str) -> Vec<[u8; 4]> {
     }
     code_sig.iter().cloned().collect_vec()
 }
+
+/// Reads the immediate operand of a `PUSH1`..`PUSH8` at `pc` as a `u64`, or
+/// `None` if `op` isn't a small enough `PUSH` or the operand runs past the
+/// end of the code (same bounds style as `extract_sig_from_contract`).
+fn push_immediate(bytes: &[u8], pc: usize, op: u8) -> Option<u64> {
+    if !(0x60..=0x67).contains(&op) {
+        // PUSH1..PUSH8: anything wider can't hold a single-digit precompile
+        // address anyway
+        return None;
+    }
+    let len = (op - 0x5f) as usize;
+    let start = pc + 1;
+    let end = start + len;
+    if end > bytes.len() {
+        return None;
+    }
+    let mut value = 0u64;
+    for b in &bytes[start..end] {
+        value = (value << 8) | (*b as u64);
+    }
+    Some(value)
+}
+
+/// Heuristically detects a `require(ecrecover(...) == owner)`-style guard:
+/// a `PUSH` of the `ecrecover` precompile address (`0x1`) followed, within
+/// a few opcodes (pushing gas and the in/out memory offsets), by a
+/// `CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`. Contracts that clear this
+/// are otherwise unfuzzable through that guard, since a random 65-byte
+/// signature recovers to a random address almost never equal to `owner`.
+pub fn uses_ecrecover_precompile(code: &str) -> bool {
+    let bytes = match hex::decode(code) {
+        Ok(b) => b,
+        Err(_) => return false,
+    };
+    let bytecode = all_bytecode(&bytes);
+    const CALL_OPCODES: [u8; 4] = [0xf1, 0xf2, 0xf4, 0xfa]; // CALL, CALLCODE, DELEGATECALL, STATICCALL
+    const LOOKAHEAD: usize = 8;
+
+    for (i, &(pc, op)) in bytecode.iter().enumerate() {
+        if push_immediate(&bytes, pc, op) != Some(0x1) {
+            continue;
+        }
+        if bytecode[i + 1..]
+            .iter()
+            .take(LOOKAHEAD)
+            .any(|(_, op)| CALL_OPCODES.contains(op))
+        {
+            return true;
+        }
+    }
+    false
+}
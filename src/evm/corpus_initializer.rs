@@ -3,13 +3,14 @@ use crate::{
     evm::{
         abi::{get_abi_type_boxed, BoxedABI},
         bytecode_analyzer,
-        contract_utils::{extract_sig_from_contract, ABIConfig, ContractLoader},
+        contract_utils::{extract_sig_from_contract, uses_ecrecover_precompile, ABIConfig, ContractLoader},
         input::{ConciseEVMInput, EVMInput},
+        presets::ecrecover::ControlledKeypair,
         types::{
             fixed_address, EVMAddress, EVMFuzzState, EVMInfantStateState, EVMStagedVMState,
             ProjectSourceMapTy, EVMU256,
         },
-        vm::{EVMExecutor, EVMState},
+        vm::{CallTrace, EVMExecutor, EVMState},
     },
     fuzzer::{DUMP_FILE_COUNT, REPLAY},
     generic_vm::vm_executor::GenericVM,
@@ -41,8 +42,57 @@ use std::{
 
 use crate::evm::types::EVMExecutionResult;
 
+/// One entry of a corpus-seed file (see
+/// `EVMCorpusInitializer::seed_from_file`): a named test case whose `args`
+/// encode against the matching ABI's parameter list.
+#[derive(Debug, Deserialize)]
+struct EVMTestVector {
+    function: String,
+    #[serde(default)]
+    args: Vec<serde_json::Value>,
+    #[serde(default)]
+    comment: Option<String>,
+}
+
+/// Left-pads a single JSON scalar (bool, integer, or `0x`-hex string) into
+/// its own 32-byte big-endian ABI word, the way a statically-sized
+/// Solidity parameter (`uintN`, `address`, `bool`) is laid out. A hex
+/// string shorter than 32 bytes is right-aligned as well, so a fixed-size
+/// `bytesN` argument (left-aligned in the ABI) must be given as all 32
+/// bytes.
+fn encode_seed_arg(value: &serde_json::Value) -> Result<[u8; 32], String> {
+    let mut word = [0u8; 32];
+    match value {
+        serde_json::Value::Bool(b) => word[31] = *b as u8,
+        serde_json::Value::Number(n) => {
+            let v = n
+                .as_u64()
+                .ok_or_else(|| format!("{} does not fit in a u64", n))?;
+            word[24..].copy_from_slice(&v.to_be_bytes());
+        }
+        serde_json::Value::String(s) => {
+            let s = s.trim().strip_prefix("0x").unwrap_or(s.trim());
+            let bytes = hex::decode(s).map_err(|e| e.to_string())?;
+            if bytes.len() > 32 {
+                return Err("hex argument longer than 32 bytes".to_string());
+            }
+            word[32 - bytes.len()..].copy_from_slice(&bytes);
+        }
+        _ => return Err("expected a bool, integer, or hex string".to_string()),
+    }
+    Ok(word)
+}
+
 pub struct EVMCorpusInitializer<'a> {
-    executor: &'a mut EVMExecutor<EVMInput, EVMFuzzState, EVMState, ConciseEVMInput>,
+    /// Borrowed through the generic `GenericVM` interface rather than the
+    /// concrete `EVMExecutor` so a different `VmFactory` backend (see
+    /// `crate::evm::config::VmBackend`) can reuse corpus setup, contract
+    /// deployment, and ABI registration unchanged. The few spots that need
+    /// EVM-specific host access (`set_code`, reading `code` and `evmstate`) go through
+    /// [`Self::evm_executor`], the same `as_any` downcast pattern used
+    /// elsewhere in this codebase to recover a concrete type from a generic
+    /// one.
+    executor: &'a mut dyn GenericVM<EVMState, Bytecode, Bytes, EVMAddress, Vec<u8>, EVMInput, EVMFuzzState, ConciseEVMInput>,
     scheduler: &'a dyn Scheduler<EVMInput, EVMFuzzState>,
     infant_scheduler: &'a dyn Scheduler<EVMStagedVMState, EVMInfantStateState>,
     state: &'a mut EVMFuzzState,
@@ -55,6 +105,16 @@ pub struct EVMInitializationArtifacts {
     pub address_to_abi: HashMap<EVMAddress, Vec<ABIConfig>>,
     pub address_to_abi_object: HashMap<EVMAddress, Vec<BoxedABI>>,
     pub address_to_name: HashMap<EVMAddress, String>,
+    /// The constructor's `BoxedABI`, which `add_abi` records here instead
+    /// of seeding a corpus entry for it, so a mutator can generate
+    /// a fresh encoding and hand it to
+    /// `EVMExecutor::redeploy_with_constructor_args` instead of every
+    /// contract being explored with only its one seed constructor config.
+    pub address_to_constructor_abi_object: HashMap<EVMAddress, BoxedABI>,
+    /// Pre-constructor-args init code for each contract, so re-deploying
+    /// with a mutated constructor encoding doesn't need to re-read the
+    /// contract off disk.
+    pub address_to_init_code: HashMap<EVMAddress, Bytecode>,
     pub initial_state: EVMStagedVMState,
 }
 
@@ -127,7 +187,7 @@ macro_rules! add_input_to_corpus {
 
 impl<'a> EVMCorpusInitializer<'a> {
     pub fn new(
-        executor: &'a mut EVMExecutor<EVMInput, EVMFuzzState, EVMState, ConciseEVMInput>,
+        executor: &'a mut dyn GenericVM<EVMState, Bytecode, Bytes, EVMAddress, Vec<u8>, EVMInput, EVMFuzzState, ConciseEVMInput>,
         scheduler: &'a dyn Scheduler<EVMInput, EVMFuzzState>,
         infant_scheduler: &'a dyn Scheduler<EVMStagedVMState, EVMInfantStateState>,
         state: &'a mut EVMFuzzState,
@@ -142,12 +202,142 @@ impl<'a> EVMCorpusInitializer<'a> {
         }
     }
 
-    pub fn initialize(&mut self, loader: &mut ContractLoader) -> EVMInitializationArtifacts {
+    /// Recovers the concrete EVM executor for the handful of call sites that
+    /// need EVM-specific host access (`set_code`, reading `code` and `evmstate`)
+    /// instead of the generic `GenericVM` interface. Same `as_any` downcast
+    /// idiom already used elsewhere (e.g. `execute_abi` recovering
+    /// `EVMState` out of a generic `VS`); panics only if this initializer is
+    /// ever wired up with a non-EVM `VmFactory` backend, which isn't
+    /// supported today.
+    fn evm_executor(&mut self) -> &mut EVMExecutor<EVMInput, EVMFuzzState, EVMState, ConciseEVMInput> {
+        self.executor
+            .as_any()
+            .downcast_mut::<EVMExecutor<EVMInput, EVMFuzzState, EVMState, ConciseEVMInput>>()
+            .expect("EVMCorpusInitializer only supports the EVM VmFactory backend")
+    }
+
+    pub fn initialize(
+        &mut self,
+        loader: &mut ContractLoader,
+        corpus_seed_file: Option<&str>,
+    ) -> EVMInitializationArtifacts {
         self.state.metadata_mut().insert(ABIMap::new());
         self.setup_default_callers();
         self.setup_contract_callers();
+        self.setup_precompiles();
         self.initialize_contract(loader);
-        self.initialize_corpus(loader)
+        let artifacts = self.initialize_corpus(loader);
+        if let Some(path) = corpus_seed_file {
+            self.seed_from_file(path, &artifacts);
+        }
+        artifacts
+    }
+
+    /// Loads `path` as a JSON array of `{ "function", "args", "comment" }`
+    /// test vectors and, for each entry matching a known ABI's
+    /// `function_name` (see `artifacts.address_to_abi`), seeds a direct
+    /// call against the contract that declares it: the 4-byte selector
+    /// followed by each `args` entry left-padded into its own 32-byte
+    /// word. Only statically-sized parameters are supported - no dynamic
+    /// arrays/strings/bytes - which covers the common case of seeding PoC
+    /// transactions and boundary values (`u8::MAX`, the zero address, ...)
+    /// without a full ABI encoder at seed time. An entry with no matching
+    /// ABI or with an arg that can't be encoded is logged and skipped; a
+    /// file that can't be read or doesn't deserialize as a whole (e.g. one
+    /// entry lacks `function`) is logged and ignored entirely. Neither
+    /// aborts the run.
+    fn seed_from_file(&mut self, path: &str, artifacts: &EVMInitializationArtifacts) {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("warning: could not read corpus seed file {}: {}", path, e);
+                return;
+            }
+        };
+        let vectors: Vec<EVMTestVector> = match serde_json::from_str(&contents) {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                eprintln!("warning: corpus seed file {} is not valid JSON: {}", path, e);
+                return;
+            }
+        };
+
+        for vector in vectors {
+            let Some((deployed_address, abi)) =
+                artifacts.address_to_abi.iter().find_map(|(addr, abis)| {
+                    abis.iter()
+                        .find(|abi| abi.function_name == vector.function)
+                        .map(|abi| (*addr, abi.clone()))
+                })
+            else {
+                eprintln!(
+                    "warning: skipping seed for {}: no matching ABI entry found",
+                    vector.function
+                );
+                continue;
+            };
+
+            let mut calldata = abi.function.to_vec();
+            let encoded = vector
+                .args
+                .iter()
+                .map(encode_seed_arg)
+                .collect::<Result<Vec<_>, _>>();
+            let words = match encoded {
+                Ok(words) => words,
+                Err(e) => {
+                    eprintln!(
+                        "warning: skipping seed for {}{}: {}",
+                        vector.function,
+                        vector
+                            .comment
+                            .as_deref()
+                            .map(|c| format!(" ({})", c))
+                            .unwrap_or_default(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            for word in words {
+                calldata.extend_from_slice(&word);
+            }
+
+            let caller = self.state.get_rand_caller();
+            let mut access_pattern = AccessPattern::new();
+            access_pattern.prewarm([caller, deployed_address], []);
+            let input = EVMInput {
+                caller,
+                contract: deployed_address,
+                data: None,
+                sstate: StagedVMState::new_uninitialized(),
+                sstate_idx: 0,
+                txn_value: if abi.is_payable {
+                    Some(EVMU256::ZERO)
+                } else {
+                    None
+                },
+                step: false,
+                env: Default::default(),
+                access_pattern: Rc::new(RefCell::new(access_pattern)),
+                direct_data: Bytes::from(calldata),
+                randomness: vec![0],
+                repeat: 1,
+            };
+            add_input_to_corpus!(self.state, self.scheduler, input);
+        }
+    }
+
+    /// Registers the standard precompile addresses (`0x1`-`0x9`) as known
+    /// callable addresses, same as `setup_contract_callers` does for the
+    /// fixed contract callers. Without this, a contract guarded by
+    /// `require(ecrecover(...) == owner)` is unfuzzable: the fuzzer never
+    /// considers `0x1` a meaningful call target, let alone a forgeable
+    /// signature check (see `seed_ecrecover_guard`).
+    pub fn setup_precompiles(&mut self) {
+        for i in 1..=9u64 {
+            self.state.add_address(&fixed_address(&format!("{:040x}", i)));
+        }
     }
 
     pub fn initialize_contract(&mut self, loader: &mut ContractLoader) {
@@ -160,9 +350,9 @@ impl<'a> EVMCorpusInitializer<'a> {
                     contract.deployed_address,
                     self.state,
                 ) {
-                    Some(addr) => addr,
-                    None => {
-                        println!("Failed to deploy contract: {}", contract.name);
+                    Ok(addr) => addr,
+                    Err(e) => {
+                        println!("Failed to deploy contract: {}: {}", contract.name, e);
                         // we could also panic here
                         continue;
                     }
@@ -171,7 +361,7 @@ impl<'a> EVMCorpusInitializer<'a> {
                 // directly set bytecode
                 let contract_code = Bytecode::new_raw(Bytes::from(contract.code.clone()));
                 bytecode_analyzer::add_analysis_result_to_state(&contract_code, self.state);
-                self.executor
+                self.evm_executor()
                     .host
                     .set_code(contract.deployed_address, contract_code, self.state);
                 contract.deployed_address
@@ -189,6 +379,8 @@ impl<'a> EVMCorpusInitializer<'a> {
             address_to_abi: HashMap::new(),
             address_to_abi_object: Default::default(),
             address_to_name: Default::default(),
+            address_to_constructor_abi_object: Default::default(),
+            address_to_init_code: Default::default(),
             initial_state: StagedVMState::new_uninitialized(),
         };
         for contract in &mut loader.contracts {
@@ -213,8 +405,12 @@ impl<'a> EVMCorpusInitializer<'a> {
             artifacts
                 .address_to_abi
                 .insert(contract.deployed_address, contract.abi.clone());
+            artifacts.address_to_init_code.insert(
+                contract.deployed_address,
+                Bytecode::new_raw(Bytes::from(contract.code.clone())),
+            );
             let mut code = vec![];
-            self.executor
+            self.evm_executor()
                 .host
                 .code
                 .clone()
@@ -227,6 +423,10 @@ impl<'a> EVMCorpusInitializer<'a> {
                 Bytecode::new_raw(Bytes::from(code)),
             );
 
+            if uses_ecrecover_precompile(&hex::encode(contract.code.clone())) {
+                self.seed_ecrecover_guard(contract.deployed_address);
+            }
+
             let mut name = contract.name.clone().trim_end_matches('*').to_string();
             if name != format!("{:?}", contract.deployed_address) {
                 name = format!("{}({:?})", name, contract.deployed_address.clone());
@@ -245,8 +445,15 @@ impl<'a> EVMCorpusInitializer<'a> {
             }
             // add transfer txn
             {
+                let caller = self.state.get_rand_caller();
+                // a transaction's sender and recipient are warmed up by the
+                // protocol itself before execution starts (EIP-2929), so
+                // seed them instead of charging this seeded input for
+                // discovering them cold
+                let mut access_pattern = AccessPattern::new();
+                access_pattern.prewarm([caller, contract.deployed_address], []);
                 let input = EVMInput {
-                    caller: self.state.get_rand_caller(),
+                    caller,
                     contract: contract.deployed_address,
                     data: None,
                     sstate: StagedVMState::new_uninitialized(),
@@ -254,7 +461,7 @@ impl<'a> EVMCorpusInitializer<'a> {
                     txn_value: Some(EVMU256::from(1)),
                     step: false,
                     env: Default::default(),
-                    access_pattern: Rc::new(RefCell::new(AccessPattern::new())),
+                    access_pattern: Rc::new(RefCell::new(access_pattern)),
                     direct_data: Default::default(),
                     randomness: vec![0],
                     repeat: 1,
@@ -263,7 +470,7 @@ impl<'a> EVMCorpusInitializer<'a> {
             }
         }
         artifacts.initial_state =
-            StagedVMState::new_with_state(self.executor.host.evmstate.clone());
+            StagedVMState::new_with_state(self.evm_executor().host.evmstate.clone());
 
         let mut tc = Testcase::new(artifacts.initial_state.clone());
         tc.set_exec_time(Duration::from_secs(0));
@@ -291,6 +498,38 @@ impl<'a> EVMCorpusInitializer<'a> {
         }
     }
 
+    /// `contract` clears `require(ecrecover(...) == owner)` or similar: seed
+    /// a keypair the fuzzer fully controls so that guard is satisfiable.
+    /// The recovered address is registered as a caller (for guards that
+    /// compare against `msg.sender`), and a corpus entry calling the
+    /// `ecrecover` precompile directly with a valid signature over it gives
+    /// the mutator a real `(hash, v, r, s)` tuple to splice from instead of
+    /// starting from 128 bytes of random noise.
+    fn seed_ecrecover_guard(&mut self, contract: EVMAddress) {
+        let keypair = ControlledKeypair::from_seed(1);
+        self.state.add_caller(&keypair.address);
+
+        let hash = [0u8; 32];
+        let precompile = fixed_address(&format!("{:040x}", 1));
+        let mut access_pattern = AccessPattern::new();
+        access_pattern.prewarm([precompile, keypair.address, contract], []);
+        let input = EVMInput {
+            caller: keypair.address,
+            contract: precompile,
+            data: None,
+            sstate: StagedVMState::new_uninitialized(),
+            sstate_idx: 0,
+            txn_value: None,
+            step: false,
+            env: Default::default(),
+            access_pattern: Rc::new(RefCell::new(access_pattern)),
+            direct_data: keypair.ecrecover_calldata(hash),
+            randomness: vec![0],
+            repeat: 1,
+        };
+        add_input_to_corpus!(self.state, self.scheduler, input);
+    }
+
     pub fn setup_contract_callers(&mut self) {
         let contract_callers = HashSet::from([
             fixed_address("e1A425f1AC34A8a441566f93c82dD730639c8510"),
@@ -299,7 +538,7 @@ impl<'a> EVMCorpusInitializer<'a> {
         ]);
         for caller in contract_callers {
             self.state.add_caller(&caller);
-            self.executor.host.set_code(
+            self.evm_executor().host.set_code(
                 caller,
                 Bytecode::new_raw(Bytes::from(vec![0xfd, 0x00])),
                 self.state,
@@ -315,6 +554,11 @@ impl<'a> EVMCorpusInitializer<'a> {
         artifacts: &mut EVMInitializationArtifacts,
     ) {
         if abi.is_constructor {
+            let mut abi_instance = get_abi_type_boxed(&abi.abi);
+            abi_instance.set_func_with_name(abi.function, abi.function_name.clone());
+            artifacts
+                .address_to_constructor_abi_object
+                .insert(deployed_address, abi_instance);
             return;
         }
 
@@ -344,8 +588,11 @@ impl<'a> EVMCorpusInitializer<'a> {
             .entry(deployed_address)
             .or_insert(vec![])
             .push(abi_instance.clone());
+        let caller = self.state.get_rand_caller();
+        let mut access_pattern = AccessPattern::new();
+        access_pattern.prewarm([caller, deployed_address], []);
         let input = EVMInput {
-            caller: self.state.get_rand_caller(),
+            caller,
             contract: deployed_address,
             data: Some(abi_instance),
             sstate: StagedVMState::new_uninitialized(),
@@ -357,13 +604,22 @@ impl<'a> EVMCorpusInitializer<'a> {
             },
             step: false,
             env: Default::default(),
-            access_pattern: Rc::new(RefCell::new(AccessPattern::new())),
+            access_pattern: Rc::new(RefCell::new(access_pattern)),
             direct_data: Default::default(),
             randomness: vec![0],
             repeat: 1,
         };
         add_input_to_corpus!(self.state, scheduler, input.clone());
 
+        // When replaying, seed the call-tree/opcode trace on the executor's
+        // EVMState so `CallTracer` (enabled for the same reason) has
+        // somewhere to record into, and a dump built from this seed can
+        // still be inspected the way a live oracle pattern-matches on
+        // internal call structure.
+        if unsafe { REPLAY } {
+            self.evm_executor().host.evmstate.call_trace = Some(CallTrace::new());
+        }
+
         let corpus_dir = format!("{}/corpus", self.work_dir.as_str()).to_string();
         dump_txn!(corpus_dir, &input)
     }
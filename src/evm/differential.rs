@@ -0,0 +1,146 @@
+/// Optional second execution path that JIT-compiles deployed bytecode to a
+/// native function and cross-checks it against the `Interpreter`-based
+/// path `deploy`/`execute_abi` already run, so a divergence between the two
+/// engines - not just a violated invariant in one of them - becomes a
+/// first-class finding. Modeled on fuzzing an EVM bytecode-to-native
+/// compiler against the reference interpreter: an opcode the compiler
+/// mishandles shows up as a mismatch in return data and gas used (on a
+/// successful return) or in whether the call returned or reverted.
+/// `DifferentialExecutor::check` compares only those; revert reasons and
+/// storage writes are not compared.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use revm_interpreter::InstructionResult;
+use revm_primitives::Bytecode;
+
+use crate::evm::types::{EVMAddress, EVMU256};
+
+/// A lowered, directly callable form of one contract's bytecode. Kept
+/// opaque behind `Arc<dyn Fn>` rather than a concrete compiled-function
+/// pointer type so swapping the backing JIT crate (Cranelift, an LLVM
+/// wrapper, ...) doesn't change this module's public surface.
+#[derive(Clone)]
+pub struct CompiledContract {
+    entry: Arc<dyn Fn(&Bytes, &HashMap<EVMU256, EVMU256>) -> JitOutcome + Send + Sync>,
+}
+
+impl CompiledContract {
+    pub fn new(
+        entry: impl Fn(&Bytes, &HashMap<EVMU256, EVMU256>) -> JitOutcome + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            entry: Arc::new(entry),
+        }
+    }
+
+    fn run(&self, calldata: &Bytes, storage: &HashMap<EVMU256, EVMU256>) -> JitOutcome {
+        (self.entry)(calldata, storage)
+    }
+}
+
+/// What running `CompiledContract` against a snapshot of pre-execution
+/// state produced, in the same terms `execute_from_pc` reports for the
+/// interpreter path so the two are directly comparable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JitOutcome {
+    Return {
+        output: Vec<u8>,
+        gas_used: u64,
+        storage_writes: Vec<(EVMU256, EVMU256)>,
+    },
+    Revert {
+        reason: String,
+    },
+}
+
+/// Lowers a contract's bytecode to a [`CompiledContract`] once, lazily, on
+/// first insert - compilation is assumed deterministic and pure so caching
+/// by address is safe to reuse across every subsequent call into that
+/// address for the rest of the campaign.
+pub trait JitBackend {
+    fn compile(&mut self, address: EVMAddress, code: &Bytecode) -> CompiledContract;
+}
+
+/// Enough context to replay a divergence outside the fuzzer: the address
+/// and calldata that triggered it, and both engines' outcomes.
+#[derive(Clone, Debug)]
+pub struct DifferentialFinding {
+    pub address: EVMAddress,
+    pub calldata: Bytes,
+    pub interpreter_ret: InstructionResult,
+    pub interpreter_output: Vec<u8>,
+    pub interpreter_gas_used: u64,
+    pub jit_outcome: JitOutcome,
+}
+
+/// Drives the JIT path: compiles lazily, runs it against a byte-for-byte
+/// clone of the pre-execution storage (so side effects from the JIT run
+/// never leak into the interpreter's state or vice versa), and compares
+/// against what the interpreter path observed.
+pub struct DifferentialExecutor {
+    backend: Box<dyn JitBackend + Send>,
+    compiled: HashMap<EVMAddress, CompiledContract>,
+}
+
+impl DifferentialExecutor {
+    pub fn new(backend: Box<dyn JitBackend + Send>) -> Self {
+        Self {
+            backend,
+            compiled: HashMap::new(),
+        }
+    }
+
+    /// Compile `code` for `address` if this is the first time it's been
+    /// seen, mirroring the `on_insert` middleware hook that runs once per
+    /// deploy in `EVMExecutor::deploy`.
+    pub fn compile_lazily(&mut self, address: EVMAddress, code: &Bytecode) {
+        self.compiled
+            .entry(address)
+            .or_insert_with(|| self.backend.compile(address, code));
+    }
+
+    /// Run the JIT path for `address` against `pre_state` (a clone taken
+    /// before the interpreter ran, so neither engine observes the other's
+    /// writes) and compare against what the interpreter path already
+    /// produced. Returns `None` when nothing was compiled for `address`
+    /// (differential mode wasn't enabled for it, e.g. it's a precompile or
+    /// hasn't been deployed through this executor) or the two agree.
+    pub fn check(
+        &self,
+        address: EVMAddress,
+        calldata: &Bytes,
+        pre_state: &HashMap<EVMU256, EVMU256>,
+        interpreter_ret: InstructionResult,
+        interpreter_output: &[u8],
+        interpreter_gas_used: u64,
+    ) -> Option<DifferentialFinding> {
+        let compiled = self.compiled.get(&address)?;
+        let jit_outcome = compiled.run(calldata, pre_state);
+
+        let matches = match &jit_outcome {
+            JitOutcome::Return {
+                output, gas_used, ..
+            } => {
+                interpreter_ret == InstructionResult::Return
+                    && output.as_slice() == interpreter_output
+                    && *gas_used == interpreter_gas_used
+            }
+            JitOutcome::Revert { .. } => interpreter_ret != InstructionResult::Return,
+        };
+
+        if matches {
+            None
+        } else {
+            Some(DifferentialFinding {
+                address,
+                calldata: calldata.clone(),
+                interpreter_ret,
+                interpreter_output: interpreter_output.to_vec(),
+                interpreter_gas_used,
+                jit_outcome,
+            })
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::evm::host::FuzzHost;
+use crate::evm::input::{ConciseEVMInput, EVMInputT};
+use crate::evm::middlewares::middleware::{Middleware, MiddlewareType};
+use crate::evm::types::{convert_u256_to_h160, EVMAddress, EVMU256};
+use crate::evm::vm::{CallFrame, CallTrace, CallType, TraceStep};
+use crate::generic_vm::vm_state::VMStateT;
+use crate::input::VMInputT;
+use crate::state::{HasCaller, HasCurrentInputIdx, HasItyState};
+use bytes::Bytes;
+use libafl::inputs::Input;
+use libafl::prelude::{HasCorpus, HasMetadata, State};
+use revm_interpreter::{Interpreter, InstructionResult};
+use revm_primitives::Bytecode;
+use std::fmt::Debug;
+
+/// What only [`CallTracer::on_step`] knows when a call/create opcode fires -
+/// who's calling whom with what - kept open until the matching
+/// [`CallTracer::on_return`] supplies what only the callee's return can
+/// tell us (return data, gas used, whether it reverted).
+#[derive(Clone, Debug)]
+struct OpenFrame {
+    from: EVMAddress,
+    to: EVMAddress,
+    call_type: CallType,
+    value: EVMU256,
+    input: Bytes,
+    gas_before: u64,
+}
+
+/// Builds a structured call tree and opcode trace over one transaction by
+/// watching every `CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`/`CREATE`/
+/// `CREATE2` and its matching return, following the `Tracer`/`VMTracer`
+/// model mature EVM executives use to let an inspector replay internal
+/// call structure (e.g. reentrancy, unexpected delegatecall targets)
+/// without re-running the transaction. Gated by `enabled` so an ordinary
+/// fuzzing run doesn't pay for per-step bookkeeping nothing reads;
+/// `evm_fuzzer` constructs it with `enabled` set to `REPLAY` so a dumped
+/// reproduction carries the trace alongside it.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    pub enabled: bool,
+    open_frames: Vec<OpenFrame>,
+}
+
+impl CallTracer {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            open_frames: vec![],
+        }
+    }
+
+    fn call_type(opcode: u8) -> Option<CallType> {
+        match opcode {
+            0xf1 => Some(CallType::Call),
+            0xf2 => Some(CallType::CallCode),
+            0xf4 => Some(CallType::DelegateCall),
+            0xfa => Some(CallType::StaticCall),
+            0xf0 => Some(CallType::Create),
+            0xf5 => Some(CallType::Create2),
+            _ => None,
+        }
+    }
+}
+
+impl<I, VS, S> Middleware<VS, I, S> for CallTracer
+where
+    I: Input + VMInputT<VS, EVMAddress, ConciseEVMInput> + EVMInputT + 'static,
+    VS: VMStateT,
+    S: State
+        + HasCaller<EVMAddress>
+        + HasCorpus<I>
+        + HasItyState<EVMAddress, VS, ConciseEVMInput>
+        + HasMetadata
+        + HasCurrentInputIdx
+        + Debug
+        + Clone,
+{
+    unsafe fn on_step(
+        &mut self,
+        interp: &mut Interpreter,
+        host: &mut FuzzHost<VS, I, S>,
+        _state: &mut S,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let opcode = *interp.instruction_pointer;
+        let trace = host.evmstate.call_trace.get_or_insert_with(CallTrace::new);
+        trace.steps.push(TraceStep {
+            depth: self.open_frames.len(),
+            pc: interp.program_counter(),
+            opcode,
+        });
+
+        let call_type = match Self::call_type(opcode) {
+            Some(t) => t,
+            None => return,
+        };
+        // CALLCODE still takes a value argument like CALL; DELEGATECALL and
+        // STATICCALL don't push one at all. CREATE/CREATE2's callee address
+        // isn't known until the call completes, so it's filled in as the
+        // zero address here - good enough for an oracle matching on `from`
+        // and call type, e.g. an unexpected DELEGATECALL target.
+        let (to, value, input) = match call_type {
+            CallType::Call | CallType::CallCode => (
+                convert_u256_to_h160(interp.stack.peek(1).unwrap()),
+                interp.stack.peek(2).unwrap(),
+                Bytes::new(),
+            ),
+            CallType::DelegateCall | CallType::StaticCall => (
+                convert_u256_to_h160(interp.stack.peek(1).unwrap()),
+                EVMU256::ZERO,
+                Bytes::new(),
+            ),
+            CallType::Create | CallType::Create2 => (
+                EVMAddress::default(),
+                interp.stack.peek(0).unwrap(),
+                Bytes::new(),
+            ),
+        };
+        self.open_frames.push(OpenFrame {
+            from: interp.contract.address,
+            to,
+            call_type,
+            value,
+            input,
+            gas_before: interp.gas.remaining(),
+        });
+    }
+
+    unsafe fn on_return(
+        &mut self,
+        interp: &mut Interpreter,
+        host: &mut FuzzHost<VS, I, S>,
+        _state: &mut S,
+        by: &Bytes,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(frame) = self.open_frames.pop() {
+            let trace = host.evmstate.call_trace.get_or_insert_with(CallTrace::new);
+            trace.frames.push(CallFrame {
+                depth: self.open_frames.len(),
+                from: frame.from,
+                to: frame.to,
+                call_type: frame.call_type,
+                value: frame.value,
+                input: frame.input,
+                return_data: by.clone(),
+                gas_used: frame.gas_before.saturating_sub(interp.gas.remaining()),
+                reverted: interp.instruction_result == InstructionResult::Revert,
+            });
+        }
+    }
+
+    unsafe fn on_insert(
+        &mut self,
+        _bytecode: &mut Bytecode,
+        _address: EVMAddress,
+        _host: &mut FuzzHost<VS, I, S>,
+        _state: &mut S,
+    ) {
+    }
+
+    fn get_type(&self) -> MiddlewareType {
+        MiddlewareType::CallTracer
+    }
+}
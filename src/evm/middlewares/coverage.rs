@@ -54,6 +54,21 @@ pub fn instructions_pc(bytecode: &Bytecode) -> (HashSet<usize>, HashSet<usize>,
     )
 }
 
+fn ratio(hit: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        hit as f64 / total as f64
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[derive(Clone, Debug)]
 pub struct Coverage {
     pub pc_coverage: HashMap<EVMAddress, HashSet<usize>>,
@@ -140,6 +155,131 @@ impl CoverageReport {
         s
     }
 
+    /// Renders this report as an LCOV tracefile (the `genhtml`/Codecov native
+    /// format), so coverage can be consumed by CI without any
+    /// post-processing. It writes one `SF`/`end_of_record` block per
+    /// contract, named after that contract's first source file; its other
+    /// source files get no block of their own.
+    pub fn to_lcov(&self) -> String {
+        let mut out = String::new();
+        for (name, cov) in &self.coverage {
+            let source_file = self
+                .files
+                .get(name)
+                .and_then(|files| files.first())
+                .map(|(file, _)| file.clone())
+                .unwrap_or_else(|| name.clone());
+            let total_lines = self
+                .files
+                .get(name)
+                .and_then(|files| files.first())
+                .map(|(_, code)| code.lines().count())
+                .unwrap_or(0);
+
+            // Lines that a `SourceMapWithCode` entry says were never hit.
+            let uncovered_lines: HashMap<usize, &SourceMapWithCode> = cov
+                .uncovered
+                .iter()
+                .map(|u| (u.lines.0, u))
+                .collect();
+
+            out.push_str(&format!("SF:{}\n", source_file));
+
+            let mut lines_hit = 0usize;
+            for line in 1..=total_lines {
+                let hits = if uncovered_lines.contains_key(&line) {
+                    0
+                } else {
+                    1
+                };
+                lines_hit += hits;
+                out.push_str(&format!("DA:{},{}\n", line, hits));
+            }
+
+            // One never-taken BRDA record per uncovered source line; these
+            // come from uncovered instructions, not from individual JUMPI
+            // outcomes. BRF/BRH below still report the aggregate branch counts.
+            for (idx, (line, _)) in uncovered_lines.iter().enumerate() {
+                out.push_str(&format!("BRDA:{},0,{},0\n", line, idx));
+            }
+
+            out.push_str(&format!("LF:{}\n", total_lines));
+            out.push_str(&format!("LH:{}\n", lines_hit));
+            out.push_str(&format!("BRF:{}\n", cov.total_branches));
+            out.push_str(&format!("BRH:{}\n", cov.branch_coverage));
+            out.push_str("end_of_record\n");
+        }
+        out
+    }
+
+    /// Renders this report as a Cobertura XML document, the other coverage
+    /// format most CI dashboards (Codecov, GitLab, Jenkins) understand out
+    /// of the box.
+    pub fn to_cobertura_xml(&self) -> String {
+        let total_instructions: usize = self.coverage.values().map(|c| c.total_instructions).sum();
+        let total_covered: usize = self.coverage.values().map(|c| c.instruction_coverage).sum();
+        let total_branches: usize = self.coverage.values().map(|c| c.total_branches).sum();
+        let total_branch_hits: usize = self.coverage.values().map(|c| c.branch_coverage).sum();
+        let line_rate = ratio(total_covered, total_instructions);
+        let branch_rate = ratio(total_branch_hits, total_branches);
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\"?>\n");
+        out.push_str(&format!(
+            "<coverage line-rate=\"{:.4}\" branch-rate=\"{:.4}\" version=\"ityfuzz\">\n",
+            line_rate, branch_rate
+        ));
+        out.push_str("  <packages>\n");
+        for (name, cov) in &self.coverage {
+            let source_file = self
+                .files
+                .get(name)
+                .and_then(|files| files.first())
+                .map(|(file, _)| file.clone())
+                .unwrap_or_else(|| name.clone());
+            let total_lines = self
+                .files
+                .get(name)
+                .and_then(|files| files.first())
+                .map(|(_, code)| code.lines().count())
+                .unwrap_or(0);
+            let uncovered_lines: std::collections::HashSet<usize> =
+                cov.uncovered.iter().map(|u| u.lines.0).collect();
+            let pkg_line_rate = ratio(cov.instruction_coverage, cov.total_instructions);
+            let pkg_branch_rate = ratio(cov.branch_coverage, cov.total_branches);
+
+            out.push_str(&format!(
+                "    <package name=\"{}\" line-rate=\"{:.4}\" branch-rate=\"{:.4}\">\n",
+                xml_escape(name),
+                pkg_line_rate,
+                pkg_branch_rate
+            ));
+            out.push_str("      <classes>\n");
+            out.push_str(&format!(
+                "        <class name=\"{}\" filename=\"{}\" line-rate=\"{:.4}\" branch-rate=\"{:.4}\">\n",
+                xml_escape(name),
+                xml_escape(&source_file),
+                pkg_line_rate,
+                pkg_branch_rate
+            ));
+            out.push_str("          <lines>\n");
+            for line in 1..=total_lines {
+                let hits = if uncovered_lines.contains(&line) { 0 } else { 1 };
+                out.push_str(&format!(
+                    "            <line number=\"{}\" hits=\"{}\"/>\n",
+                    line, hits
+                ));
+            }
+            out.push_str("          </lines>\n");
+            out.push_str("        </class>\n");
+            out.push_str("      </classes>\n");
+            out.push_str("    </package>\n");
+        }
+        out.push_str("  </packages>\n");
+        out.push_str("</coverage>\n");
+        out
+    }
+
     pub fn dump_file(&self, work_dir: String) {
         let mut text_file = OpenOptions::new()
             .write(true)
@@ -188,6 +328,28 @@ impl CoverageReport {
             .write_all(serde_json::to_string(&self.files).unwrap().as_bytes())
             .unwrap();
         file_json_file.flush().unwrap();
+
+        let mut lcov_file = OpenOptions::new()
+            .write(true)
+            .append(false)
+            .create(true)
+            .truncate(true)
+            .open(format!("{}/cov.lcov", work_dir))
+            .unwrap();
+        lcov_file.write_all(self.to_lcov().as_bytes()).unwrap();
+        lcov_file.flush().unwrap();
+
+        let mut cobertura_file = OpenOptions::new()
+            .write(true)
+            .append(false)
+            .create(true)
+            .truncate(true)
+            .open(format!("{}/cobertura.xml", work_dir))
+            .unwrap();
+        cobertura_file
+            .write_all(self.to_cobertura_xml().as_bytes())
+            .unwrap();
+        cobertura_file.flush().unwrap();
     }
 
     pub fn summarize(&self) {
@@ -0,0 +1,160 @@
+use crate::dataflow::{DataflowTrace, DATAFLOW_ENABLED};
+use crate::evm::host::FuzzHost;
+use crate::evm::input::{ConciseEVMInput, EVMInputT};
+use crate::evm::middlewares::middleware::{Middleware, MiddlewareType};
+use crate::evm::middlewares::taint_engine::TaintEngine;
+use crate::evm::types::{EVMAddress, EVMU256};
+use crate::generic_vm::vm_state::VMStateT;
+use crate::input::VMInputT;
+use crate::state::{HasCaller, HasCurrentInputIdx, HasItyState};
+use bytes::Bytes;
+use libafl::inputs::Input;
+use libafl::prelude::{HasCorpus, HasMetadata, State};
+use revm_interpreter::opcode::JUMPI;
+use revm_interpreter::Interpreter;
+use revm_primitives::Bytecode;
+use std::fmt::Debug;
+
+/// Drives the generalized [`TaintEngine`] (the same engine
+/// `Sha3TaintAnalysis` uses) purely to populate a [`DataflowTrace`] for
+/// `DataflowStage`/`compute_mutation_mask` from a real execution, instead
+/// of the trace staying permanently empty. Recorded as `DataflowEvent`s:
+/// every `SLOAD`'s result, every `SSTORE`'s key/value (a sink, since it's
+/// attacker-observable persisted state) and every `JUMPI`'s condition (a
+/// sink, since it's a branch decision) - matching the sink definition in
+/// `crate::dataflow`'s module doc.
+#[derive(Clone, Debug, Default)]
+pub struct DataflowTracer {
+    engine: TaintEngine,
+}
+
+impl DataflowTracer {
+    pub fn new() -> Self {
+        Self {
+            engine: TaintEngine::new(),
+        }
+    }
+}
+
+fn storage_slot_name(address: EVMAddress, key: EVMU256) -> String {
+    format!("storage:{:?}:{:#x}", address, key)
+}
+
+impl<I, VS, S> Middleware<VS, I, S> for DataflowTracer
+where
+    I: Input + VMInputT<VS, EVMAddress, EVMAddress, ConciseEVMInput> + EVMInputT + 'static,
+    VS: VMStateT,
+    S: State
+        + HasCaller<EVMAddress>
+        + HasCorpus<I>
+        + HasItyState<EVMAddress, EVMAddress, VS, ConciseEVMInput>
+        + HasMetadata
+        + HasCurrentInputIdx
+        + Debug
+        + Clone,
+{
+    unsafe fn on_step(
+        &mut self,
+        interp: &mut Interpreter,
+        _host: &mut FuzzHost<VS, I, S>,
+        state: &mut S,
+    ) {
+        if !DATAFLOW_ENABLED {
+            return;
+        }
+
+        let opcode = *interp.instruction_pointer;
+        let address = interp.contract.address;
+
+        // Captured before `self.engine.on_step` runs, since that's what
+        // consumes (pops) the shadow-stack labels this opcode is about to
+        // read - `interp`'s own stack is untouched by the engine either
+        // way, so the storage key itself is fine to read after too, but
+        // the labels aren't.
+        let sstore_labels = (opcode == 0x55).then(|| {
+            let len = self.engine.dirty_stack.len();
+            (
+                self.engine.dirty_stack[len - 1].clone(), // key label
+                self.engine.dirty_stack[len - 2].clone(), // value label
+            )
+        });
+        let jumpi_label = (opcode == JUMPI).then(|| {
+            let len = self.engine.dirty_stack.len();
+            self.engine.dirty_stack[len - 2].clone() // condition label
+        });
+        let key = matches!(opcode, 0x54 | 0x55)
+            .then(|| interp.stack.peek(0).expect("stack is empty"));
+
+        self.engine.on_step(interp, &mut []);
+
+        if !state.metadata().contains::<DataflowTrace>() {
+            state.add_metadata(DataflowTrace::new());
+        }
+        let trace = state
+            .metadata_mut()
+            .get_mut::<DataflowTrace>()
+            .expect("just inserted above");
+
+        match opcode {
+            // SLOAD: just read, not itself a sink - it only becomes
+            // interesting (and live) if what it loads later reaches a
+            // sink event.
+            0x54 => {
+                let key = key.expect("SLOAD always has a key on the stack");
+                let slot = storage_slot_name(address, key);
+                let provenance = self
+                    .engine
+                    .dirty_stack
+                    .last()
+                    .map(|label| label.iter().map(|o| *o as usize).collect())
+                    .unwrap_or_default();
+                trace.record_read(slot, provenance, false);
+            }
+            // SSTORE: the value being persisted is a sink (it's now
+            // attacker-observable contract state), and the slot itself is
+            // (conservatively) a conditional write, since we don't track
+            // whether this site is reached unconditionally.
+            0x55 => {
+                let key = key.expect("SSTORE always has a key on the stack");
+                let (_key_label, value_label) =
+                    sstore_labels.expect("sstore_labels set for opcode 0x55");
+                let slot = storage_slot_name(address, key);
+                let provenance = value_label.iter().map(|o| *o as usize).collect();
+                trace.record_read(slot.clone(), provenance, true);
+                trace.record_write(slot, true);
+            }
+            // JUMPI: the branch condition is a sink - this is exactly
+            // what a mutator wants to bias toward.
+            JUMPI => {
+                let label = jumpi_label.expect("jumpi_label set for opcode JUMPI");
+                let slot = format!("branch:{:?}:{:#x}", address, interp.program_counter());
+                let provenance = label.iter().map(|o| *o as usize).collect();
+                trace.record_read(slot, provenance, true);
+            }
+            _ => {}
+        }
+    }
+
+    unsafe fn on_return(
+        &mut self,
+        _interp: &mut Interpreter,
+        _host: &mut FuzzHost<VS, I, S>,
+        _state: &mut S,
+        _by: &Bytes,
+    ) {
+        self.engine.pop_ctx();
+    }
+
+    unsafe fn on_insert(
+        &mut self,
+        _bytecode: &mut Bytecode,
+        _address: EVMAddress,
+        _host: &mut FuzzHost<VS, I, S>,
+        _state: &mut S,
+    ) {
+    }
+
+    fn get_type(&self) -> MiddlewareType {
+        MiddlewareType::DataflowTracer
+    }
+}
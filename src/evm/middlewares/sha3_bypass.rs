@@ -1,102 +1,149 @@
 use crate::evm::host::FuzzHost;
 use crate::evm::input::{ConciseEVMInput, EVMInputT};
 use crate::evm::middlewares::middleware::{Middleware, MiddlewareType};
-use crate::evm::types::{as_u64, EVMAddress, EVMU256};
+use crate::evm::middlewares::taint_engine::{Label, SinkPoint, TaintEngine, TaintSink};
+use crate::evm::types::{EVMAddress, EVMU256};
 use crate::generic_vm::vm_state::VMStateT;
 use crate::input::VMInputT;
 use crate::state::{HasCaller, HasCurrentInputIdx, HasItyState};
 use bytes::Bytes;
 use libafl::inputs::Input;
 use libafl::prelude::{HasCorpus, HasMetadata, State};
-use revm_interpreter::opcode::JUMPI;
+use revm_interpreter::opcode::{EQ, JUMPI};
 use revm_interpreter::Interpreter;
 use revm_primitives::Bytecode;
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::Path;
 use std::rc::Rc;
 
-#[derive(Clone, Debug)]
-pub struct Sha3TaintAnalysisCtx {
-    pub dirty_memory: Vec<bool>,
-    pub dirty_storage: HashMap<EVMU256, bool>,
-    pub dirty_stack: Vec<bool>,
-    pub input_data: Vec<bool>,
-}
+extern crate crypto;
+use self::crypto::digest::Digest;
+use self::crypto::sha3::Sha3;
 
-impl Sha3TaintAnalysisCtx {
-    pub fn read_input(&self, start: usize, length: usize) -> Vec<bool> {
-        let mut res = vec![false; length];
-        for i in 0..length {
-            res[i] = self.input_data[start + i];
-        }
-        res
-    }
+fn keccak256(preimage: &[u8]) -> EVMU256 {
+    let mut digest = [0u8; 32];
+    let mut hasher = Sha3::keccak256();
+    hasher.input(preimage);
+    hasher.result(&mut digest);
+    EVMU256::from_be_bytes(digest)
 }
 
-#[derive(Clone, Debug)]
-pub struct Sha3TaintAnalysis {
-    pub dirty_memory: Vec<bool>,
-    pub dirty_storage: HashMap<EVMU256, bool>,
-    pub dirty_stack: Vec<bool>,
-    pub tainted_jumpi: HashSet<(EVMAddress, usize)>,
+/// Global keccak preimage table: `Sha3TaintAnalysis` records every
+/// `SHA3`'s `hash_result -> input_bytes` here as it executes, so
+/// `Sha3Bypass` can recognize a branch that compares a freshly computed
+/// digest against one it (or an earlier campaign run) has already seen
+/// the preimage of, instead of coin-flipping every tainted branch
+/// regardless of whether the comparison is actually satisfiable. Shared
+/// rather than owned by one `Sha3TaintAnalysis` so preimages found on one
+/// input or contract help every later one.
+pub type PreimageTable = Rc<RefCell<HashMap<EVMU256, Vec<u8>>>>;
 
-    pub ctxs: Vec<Sha3TaintAnalysisCtx>,
+fn preimage_table_path(work_dir: &str) -> std::path::PathBuf {
+    Path::new(work_dir).join("sha3_preimages.json")
 }
 
-impl Sha3TaintAnalysis {
-    pub fn new() -> Self {
-        Self {
-            dirty_memory: vec![],
-            dirty_storage: HashMap::new(),
-            dirty_stack: vec![],
-            tainted_jumpi: HashSet::new(),
-            ctxs: vec![],
-        }
-    }
+/// Loads a table persisted by `save_preimages` in an earlier campaign run
+/// (an empty table if there isn't one yet), so hash guards already solved
+/// pay off immediately instead of needing to be rediscovered.
+pub fn load_preimages(work_dir: &str) -> PreimageTable {
+    let table = std::fs::read_to_string(preimage_table_path(work_dir))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<HashMap<String, String>>(&contents).ok())
+        .map(|encoded| {
+            encoded
+                .into_iter()
+                .filter_map(|(hash, preimage)| {
+                    let hash =
+                        EVMU256::from_str_radix(hash.trim_start_matches("0x"), 16).ok()?;
+                    let preimage = hex::decode(preimage).ok()?;
+                    Some((hash, preimage))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Rc::new(RefCell::new(table))
+}
 
-    pub fn cleanup(&mut self) {
-        self.dirty_memory.clear();
-        self.dirty_storage.clear();
-        self.dirty_stack.clear();
+/// Persists `table` to `{work_dir}/sha3_preimages.json` for `load_preimages`
+/// to pick back up on the next run.
+pub fn save_preimages(work_dir: &str, table: &PreimageTable) {
+    let encoded: HashMap<String, String> = table
+        .borrow()
+        .iter()
+        .map(|(hash, preimage)| (format!("{:#x}", hash), hex::encode(preimage)))
+        .collect();
+    if let Ok(contents) = serde_json::to_string(&encoded) {
+        let _ = std::fs::write(preimage_table_path(work_dir), contents);
     }
+}
 
-    pub fn write_input(&self, start: usize, length: usize) -> Vec<bool> {
-        let mut res = vec![false; length];
-        for i in 0..length {
-            res[i] = self.dirty_memory[start + i];
+/// Tracks which `JUMPI` sites fork on tainted calldata, and which of those
+/// are additionally known to be *solvable* hash guards (see
+/// `Sha3TaintAnalysis::preimages`). A [`TaintSink`] rather than a method on
+/// `Sha3TaintAnalysis` itself so `TaintEngine::on_step` can fire it the same
+/// way it fires any other sink - `Sha3TaintAnalysis` only supplies the one
+/// extra bit (`hash_guard_pending`) this sink can't derive on its own.
+#[derive(Clone, Debug, Default)]
+pub struct JumpiTracker {
+    /// Which branch sites have been observed to fork on a tainted value,
+    /// and the label set (source calldata offsets) that reached them -
+    /// consulted by the mutator to decide which calldata bytes are worth
+    /// targeting.
+    pub tainted_jumpi: HashMap<(EVMAddress, usize), Label>,
+    /// Tainted branch sites where the condition is additionally known to
+    /// come straight from an `EQ`/`ISZERO(EQ(..))` comparison against a
+    /// digest this table has a recorded preimage for - i.e. a *solvable*
+    /// hash guard rather than just "some tainted value". The value is the
+    /// preimage bytes themselves, so `Sha3Bypass` can patch them straight
+    /// into the calldata offsets `label` points at instead of forcing the
+    /// branch condition directly.
+    pub resolvable_jumpi: HashMap<(EVMAddress, usize), Vec<u8>>,
+    /// Set by `Sha3TaintAnalysis` as it steps `EQ` when one of
+    /// its operands is a digest this table has a recorded preimage for,
+    /// holding that preimage; cleared by any opcode that isn't part of the
+    /// `EQ` -> `ISZERO`? -> `JUMPI` idiom - a short-lived signal rather
+    /// than a persistent map, since it only describes "the value now on
+    /// top of the stack came from a resolvable hash comparison, and here's
+    /// the preimage that would satisfy it".
+    pub hash_guard_pending: Option<Vec<u8>>,
+}
+
+impl TaintSink for JumpiTracker {
+    fn on_tainted(&mut self, point: SinkPoint, site: (EVMAddress, usize), label: &Label) {
+        if point != SinkPoint::JumpiCondition {
+            return;
+        }
+        println!(
+            "new tainted jumpi: {:x} {:x} labels={:?}",
+            site.0, site.1, label
+        );
+        if let Some(preimage) = &self.hash_guard_pending {
+            self.resolvable_jumpi.insert(site, preimage.clone());
         }
-        res
+        self.tainted_jumpi.insert(site, label.clone());
     }
+}
 
-    pub fn push_ctx(&mut self, interp: &mut Interpreter) {
-        let (arg_offset, arg_len) = match unsafe { *interp.instruction_pointer } {
-            0xf1 | 0xf2 => (interp.stack.peek(3).unwrap(), interp.stack.peek(4).unwrap()),
-            0xf4 | 0xfa => (interp.stack.peek(2).unwrap(), interp.stack.peek(3).unwrap()),
-            _ => {
-                panic!("not supported opcode");
-            }
-        };
-
-        let arg_offset = as_u64(arg_offset) as usize;
-        let arg_len = as_u64(arg_len) as usize;
-
-        self.ctxs.push(Sha3TaintAnalysisCtx {
-            input_data: self.write_input(arg_offset, arg_len),
-            dirty_memory: self.dirty_memory.clone(),
-            dirty_storage: self.dirty_storage.clone(),
-            dirty_stack: self.dirty_stack.clone(),
-        });
-
-        self.cleanup();
-    }
+#[derive(Clone, Debug)]
+pub struct Sha3TaintAnalysis {
+    /// The shared opcode-level dataflow interpreter; see
+    /// [`crate::evm::middlewares::taint_engine::TaintEngine`].
+    pub engine: TaintEngine,
+    pub jumpi: JumpiTracker,
+    /// See [`PreimageTable`]. Populated on every `SHA3`, consulted on
+    /// every `EQ`.
+    pub preimages: PreimageTable,
+}
 
-    pub fn pop_ctx(&mut self) {
-        // println!("pop_ctx");
-        let ctx = self.ctxs.pop().expect("ctxs is empty");
-        self.dirty_memory = ctx.dirty_memory;
-        self.dirty_storage = ctx.dirty_storage;
-        self.dirty_stack = ctx.dirty_stack;
+impl Sha3TaintAnalysis {
+    pub fn new(preimages: PreimageTable) -> Self {
+        Self {
+            engine: TaintEngine::new(),
+            jumpi: JumpiTracker::default(),
+            preimages,
+        }
     }
 }
 
@@ -119,268 +166,42 @@ where
         _host: &mut FuzzHost<VS, I, S>,
         _state: &mut S,
     ) {
-        //
-        // println!("on_step: {:?} with {:x}", interp.program_counter(), *interp.instruction_pointer);
-        // println!("stack: {:?}", self.dirty_stack);
-        // println!("origin: {:?}", interp.stack);
-
-        macro_rules! pop_push {
-            ($pop_cnt: expr,$push_cnt: expr) => {{
-                let mut res = false;
-                for _ in 0..$pop_cnt {
-                    res |= self.dirty_stack.pop().expect("stack is empty");
-                }
-                for _ in 0..$push_cnt {
-                    self.dirty_stack.push(res);
-                }
-            }};
+        // `hash_guard_pending` only means something for the opcodes making
+        // up the `EQ` -> (optional `ISZERO`) -> `JUMPI` idiom; any other
+        // opcode means whatever's now on top of the stack isn't directly
+        // that comparison's result, so drop the signal.
+        if !matches!(*interp.instruction_pointer, EQ | 0x15 | JUMPI) {
+            self.jumpi.hash_guard_pending = None;
         }
 
-        macro_rules! stack_pop_n {
-            ($pop_cnt: expr) => {
-                for _ in 0..$pop_cnt {
-                    self.dirty_stack.pop().expect("stack is empty");
-                }
-            };
-        }
+        // EQ: check whether either operand is a digest this campaign has
+        // already seen the preimage of before the engine pops them -
+        // that makes this a solvable hash guard rather than an opaque
+        // tainted comparison. Captured here rather than in the engine
+        // since only this analysis knows about `preimages`.
+        let eq_operands = (*interp.instruction_pointer == EQ).then(|| {
+            (
+                interp.stack.peek(0).expect("stack is empty"),
+                interp.stack.peek(1).expect("stack is empty"),
+            )
+        });
 
-        macro_rules! push_false {
-            () => {
-                self.dirty_stack.push(false)
-            };
-        }
+        let sha3_result = self.engine.on_step(interp, &mut [&mut self.jumpi]);
 
-        macro_rules! ensure_size {
-            ($t: expr, $size: expr) => {
-                if $t.len() < $size {
-                    $t.resize($size, false);
-                }
-            };
+        if let Some((lhs, rhs)) = eq_operands {
+            let preimages = self.preimages.borrow();
+            self.jumpi.hash_guard_pending = preimages
+                .get(&lhs)
+                .or_else(|| preimages.get(&rhs))
+                .cloned();
         }
-
-        macro_rules! setup_mem {
-            () => {{
-                stack_pop_n!(3);
-                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
-                let len = as_u64(interp.stack.peek(2).expect("stack is empty")) as usize;
-                ensure_size!(self.dirty_memory, mem_offset + len);
-                self.dirty_memory[mem_offset..mem_offset + len]
-                    .copy_from_slice(vec![false; len as usize].as_slice());
-            }};
+        if *interp.instruction_pointer == JUMPI {
+            self.jumpi.hash_guard_pending = None;
         }
 
-        assert_eq!(interp.stack.len(), self.dirty_stack.len());
-
-        match *interp.instruction_pointer {
-            0x00 => {}
-            0x01..=0x7 => {
-                pop_push!(2, 1)
-            }
-            0x08..=0x09 => {
-                pop_push!(3, 1)
-            }
-            0xa | 0x0b | 0x10..=0x14 => {
-                pop_push!(2, 1);
-            }
-            0x15 => {
-                pop_push!(1, 1);
-            }
-            0x16..=0x18 => {
-                pop_push!(2, 1);
-            }
-            0x19 => {
-                pop_push!(1, 1);
-            }
-            0x1a..=0x1d => {
-                pop_push!(2, 1);
-            }
-            0x20 => {
-                // sha3
-                stack_pop_n!(2);
-                self.dirty_stack.push(true);
-            }
-            0x30 => push_false!(),
-            // BALANCE
-            0x31 => pop_push!(1, 1),
-            // ORIGIN
-            0x32 => push_false!(),
-            // CALLER
-            0x33 => push_false!(),
-            // CALLVALUE
-            0x34 => push_false!(),
-            // CALLDATALOAD
-            0x35 => {
-                self.dirty_stack.pop();
-                if self.ctxs.len() > 0 {
-                    let ctx = self.ctxs.last().unwrap();
-                    let offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
-                    if offset == 0 {
-                        push_false!()
-                    } else {
-                        let input = ctx.read_input(offset, 32).contains(&true);
-                        // println!("CALLDATALOAD: {:x} -> {}", offset, input);
-                        self.dirty_stack.push(input)
-                    }
-                } else {
-                    push_false!()
-                }
-            }
-            // CALLDATASIZE
-            0x36 => push_false!(),
-            // CALLDATACOPY
-            0x37 => setup_mem!(),
-            // CODESIZE
-            0x38 => push_false!(),
-            // CODECOPY
-            0x39 => setup_mem!(),
-            // GASPRICE
-            0x3a => push_false!(),
-            // EXTCODESIZE
-            0x3b | 0x3f => {
-                stack_pop_n!(1);
-                self.dirty_stack.push(false);
-            }
-            // EXTCODECOPY
-            0x3c => setup_mem!(),
-            // RETURNDATASIZE
-            0x3d => push_false!(),
-            // RETURNDATACOPY
-            0x3e => setup_mem!(),
-            // COINBASE
-            0x41..=0x48 => push_false!(),
-            // POP
-            0x50 => {
-                self.dirty_stack.pop();
-            }
-            // MLOAD
-            0x51 => {
-                self.dirty_stack.pop();
-                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
-                ensure_size!(self.dirty_memory, mem_offset + 32);
-                let is_dirty = self.dirty_memory[mem_offset..mem_offset + 32]
-                    .iter()
-                    .any(|x| *x);
-                self.dirty_stack.push(is_dirty);
-            }
-            // MSTORE
-            0x52 => {
-                stack_pop_n!(1);
-                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
-                let is_dirty = self.dirty_stack.pop().expect("stack is empty");
-                ensure_size!(self.dirty_memory, mem_offset + 32);
-                self.dirty_memory[mem_offset..mem_offset + 32]
-                    .copy_from_slice(vec![is_dirty; 32].as_slice());
-            }
-            // MSTORE8
-            0x53 => {
-                stack_pop_n!(1);
-                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
-                let is_dirty = self.dirty_stack.pop().expect("stack is empty");
-                ensure_size!(self.dirty_memory, mem_offset + 1);
-                self.dirty_memory[mem_offset] = is_dirty;
-            }
-            // SLOAD
-            0x54 => {
-                self.dirty_stack.pop();
-                let key = interp.stack.peek(0).expect("stack is empty");
-                let is_dirty = self.dirty_storage.get(&key).unwrap_or(&false);
-                self.dirty_stack.push(*is_dirty);
-            }
-            // SSTORE
-            0x55 => {
-                self.dirty_stack.pop();
-                let is_dirty = self.dirty_stack.pop().expect("stack is empty");
-                let key = interp.stack.peek(0).expect("stack is empty");
-                self.dirty_storage.insert(key, is_dirty);
-            }
-            // JUMP
-            0x56 => {
-                self.dirty_stack.pop();
-            }
-            // JUMPI
-            0x57 => {
-                self.dirty_stack.pop();
-                let v = self.dirty_stack.pop().expect("stack is empty");
-                if v {
-                    println!(
-                        "new tainted jumpi: {:x} {:x}",
-                        interp.contract.address,
-                        interp.program_counter()
-                    );
-                    self.tainted_jumpi
-                        .insert((interp.contract.address, interp.program_counter()));
-                }
-            }
-            // PC
-            0x58 | 0x59 | 0x5a => {
-                push_false!();
-            }
-            // JUMPDEST
-            0x5b => {}
-            // PUSH
-            0x5f..=0x7f => {
-                push_false!();
-            }
-            // DUP
-            0x80..=0x8f => {
-                let _n = (*interp.instruction_pointer) - 0x80 + 1;
-                self.dirty_stack
-                    .push(self.dirty_stack[self.dirty_stack.len() - _n as usize]);
-            }
-            // SWAP
-            0x90..=0x9f => {
-                let _n = (*interp.instruction_pointer) - 0x90 + 2;
-                let _l = self.dirty_stack.len();
-                let tmp = self.dirty_stack[_l - _n as usize];
-                self.dirty_stack[_l - _n as usize] = self.dirty_stack[_l - 1];
-                self.dirty_stack[_l - 1] = tmp;
-            }
-            // LOG
-            0xa0..=0xa4 => {
-                let _n = (*interp.instruction_pointer) - 0xa0 + 2;
-                stack_pop_n!(_n);
-            }
-            0xf0 => {
-                stack_pop_n!(3);
-                self.dirty_stack.push(false);
-            }
-            0xf1 => {
-                stack_pop_n!(7);
-                self.dirty_stack.push(false);
-                self.push_ctx(interp);
-            }
-            0xf2 => {
-                stack_pop_n!(7);
-                self.dirty_stack.push(false);
-                self.push_ctx(interp);
-            }
-            0xf3 => {
-                stack_pop_n!(2);
-            }
-            0xf4 => {
-                stack_pop_n!(6);
-                self.dirty_stack.push(false);
-                self.push_ctx(interp);
-            }
-            0xf5 => {
-                stack_pop_n!(4);
-                self.dirty_stack.push(false);
-            }
-            0xfa => {
-                stack_pop_n!(6);
-                self.dirty_stack.push(false);
-                self.push_ctx(interp);
-            }
-            0xfd => {
-                // stack_pop_n!(2);
-            }
-            0xfe => {
-                // stack_pop_n!(1);
-            }
-            0xff => {
-                // stack_pop_n!(1);
-            }
-            _ => panic!("unknown opcode: {:x}", *interp.instruction_pointer),
+        if let Some((_, preimage)) = sha3_result {
+            let hash = keccak256(&preimage);
+            self.preimages.borrow_mut().insert(hash, preimage);
         }
     }
 
@@ -391,7 +212,7 @@ where
         _state: &mut S,
         _by: &Bytes,
     ) {
-        self.pop_ctx();
+        self.engine.pop_ctx();
     }
 
     unsafe fn on_insert(
@@ -440,12 +261,26 @@ where
     ) {
         if *interp.instruction_pointer == JUMPI {
             let jumpi = interp.program_counter();
-            if self
-                .sha3_taints
-                .borrow()
-                .tainted_jumpi
-                .contains(&(interp.contract.address, jumpi))
-            {
+            let site = (interp.contract.address, jumpi);
+            let sha3_taints = self.sha3_taints.borrow();
+            if let Some(preimage) = sha3_taints.jumpi.resolvable_jumpi.get(&site) {
+                // A recognized hash guard - we know a satisfying preimage
+                // (see `Sha3TaintAnalysis::preimages`). Patch the calldata
+                // bytes the tainted operand actually derives from (`label`)
+                // so the comparison is satisfied because the input really
+                // hashes to the right digest, rather than overriding the
+                // branch condition and leaving calldata/contract semantics
+                // inconsistent with the path taken.
+                if let Some(label) = sha3_taints.jumpi.tainted_jumpi.get(&site) {
+                    let mut calldata = interp.contract.input.to_vec();
+                    for (offset, byte) in label.iter().zip(preimage.iter()) {
+                        if let Some(slot) = calldata.get_mut(*offset as usize) {
+                            *slot = *byte;
+                        }
+                    }
+                    interp.contract.input = Bytes::from(calldata);
+                }
+            } else if sha3_taints.jumpi.tainted_jumpi.contains_key(&site) {
                 let stack_len = interp.stack.len();
                 interp.stack.data[stack_len - 2] =
                     EVMU256::from((jumpi + host.randomness[0] as usize) % 2);
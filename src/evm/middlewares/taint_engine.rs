@@ -0,0 +1,558 @@
+//! A standalone, opcode-level calldata taint dataflow interpreter, factored
+//! out of `Sha3TaintAnalysis` so any `Middleware` can drive it instead of
+//! SHA3/JUMPI being the only thing it knows how to analyze. `TaintEngine`
+//! mirrors the real `Interpreter`'s stack/memory/storage with label sets
+//! (see [`Label`]) as it steps, and reports every point where a tainted
+//! operand reaches an attacker-relevant decision - a [`SinkPoint`] - to
+//! whichever [`TaintSink`]s the caller passes in for that step. This turns
+//! what used to be one hardcoded SHA3 analysis into a shared backbone other
+//! oracles can build on (e.g. "arbitrary external call" watching
+//! `CallTarget`, "arbitrary storage write" watching `SstoreKey`) without
+//! duplicating the propagation logic.
+use crate::evm::types::{as_u64, EVMAddress, EVMU256};
+use revm_interpreter::opcode::{EQ, JUMPI};
+use revm_interpreter::Interpreter;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Debug;
+
+/// A taint label: the set of top-level calldata byte offsets that flowed
+/// into a stack slot, memory byte, or storage slot. Where a plain "dirty
+/// bit" model could only say "something tainted reached here", a label set
+/// says *which* calldata bytes did, so a downstream mutator or oracle can
+/// target exactly the bytes that influence a finding instead of treating
+/// the whole input as equally suspect.
+pub type Label = BTreeSet<u32>;
+
+pub fn union(a: &Label, b: &Label) -> Label {
+    a.union(b).cloned().collect()
+}
+
+pub fn union_range<'a>(labels: impl IntoIterator<Item = &'a Label>) -> Label {
+    labels.into_iter().fold(Label::new(), |acc, l| union(&acc, l))
+}
+
+/// Returns the precompile id (1-9) if `address` names one of the standard
+/// precompiles, so `CALL`/`DELEGATECALL`/`STATICCALL` handlers can take the
+/// output-write-back path instead of `push_ctx`, since precompiles never
+/// execute bytecode and so never trigger an `on_step` of their own.
+pub fn precompile_id(address: EVMU256) -> Option<u64> {
+    let id = as_u64(address);
+    (1..=9).contains(&id).then_some(id)
+}
+
+/// A point in the analyzed contract's opcode dataflow where a tainted
+/// operand reaches something an oracle might care about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SinkPoint {
+    /// `JUMPI`'s branch condition.
+    JumpiCondition,
+    /// `CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`'s target address.
+    CallTarget,
+    /// `CALL`/`CALLCODE`'s `value` argument.
+    CallValue,
+    /// `SSTORE`'s key.
+    SstoreKey,
+    /// `SELFDESTRUCT`'s beneficiary address.
+    SelfdestructBeneficiary,
+}
+
+/// A consumer of [`TaintEngine`]'s analysis. Implementors only need to
+/// match on the [`SinkPoint`]s they care about; `site` is `(contract
+/// address, program counter)` of the opcode that reached `point`, and
+/// `label` is the calldata offsets that reached the tainted operand.
+pub trait TaintSink: Debug {
+    fn on_tainted(&mut self, point: SinkPoint, site: (EVMAddress, usize), label: &Label);
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TaintEngineCtx {
+    pub dirty_memory: Vec<Label>,
+    pub dirty_storage: HashMap<EVMU256, Label>,
+    pub dirty_stack: Vec<Label>,
+    pub input_data: Vec<Label>,
+}
+
+impl TaintEngineCtx {
+    pub fn read_input(&self, start: usize, length: usize) -> Label {
+        (0..length)
+            .map(|i| self.input_data.get(start + i).cloned().unwrap_or_default())
+            .fold(Label::new(), |acc, l| union(&acc, &l))
+    }
+}
+
+/// The opcode dataflow interpreter itself: owns the shadow
+/// stack/memory/storage and the call-frame context stack, and fires
+/// whichever [`TaintSink`]s are passed to [`Self::on_step`] when that
+/// step's opcode reaches a [`SinkPoint`] with a nonempty label. Stateless
+/// with respect to sinks - nothing is registered ahead of time - so a
+/// caller is free to pass a different sink set call to call, or none.
+#[derive(Clone, Debug, Default)]
+pub struct TaintEngine {
+    pub dirty_memory: Vec<Label>,
+    pub dirty_storage: HashMap<EVMU256, Label>,
+    pub dirty_stack: Vec<Label>,
+    pub ctxs: Vec<TaintEngineCtx>,
+}
+
+impl TaintEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cleanup(&mut self) {
+        self.dirty_memory.clear();
+        self.dirty_storage.clear();
+        self.dirty_stack.clear();
+    }
+
+    pub fn write_input(&self, start: usize, length: usize) -> Vec<Label> {
+        (0..length)
+            .map(|i| self.dirty_memory.get(start + i).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    pub fn push_ctx(&mut self, interp: &mut Interpreter) {
+        let (arg_offset, arg_len) = match unsafe { *interp.instruction_pointer } {
+            0xf1 | 0xf2 => (interp.stack.peek(3).unwrap(), interp.stack.peek(4).unwrap()),
+            0xf4 | 0xfa => (interp.stack.peek(2).unwrap(), interp.stack.peek(3).unwrap()),
+            _ => {
+                panic!("not supported opcode");
+            }
+        };
+
+        let arg_offset = as_u64(arg_offset) as usize;
+        let arg_len = as_u64(arg_len) as usize;
+
+        self.ctxs.push(TaintEngineCtx {
+            input_data: self.write_input(arg_offset, arg_len),
+            dirty_memory: self.dirty_memory.clone(),
+            dirty_storage: self.dirty_storage.clone(),
+            dirty_stack: self.dirty_stack.clone(),
+        });
+
+        self.cleanup();
+    }
+
+    pub fn pop_ctx(&mut self) {
+        let ctx = self.ctxs.pop().expect("ctxs is empty");
+        self.dirty_memory = ctx.dirty_memory;
+        self.dirty_storage = ctx.dirty_storage;
+        self.dirty_stack = ctx.dirty_stack;
+    }
+
+    fn ensure_memory_size(&mut self, size: usize) {
+        if self.dirty_memory.len() < size {
+            self.dirty_memory.resize(size, Label::new());
+        }
+    }
+
+    /// Writes a precompile's output taint directly into the caller's
+    /// return memory region, standing in for the `on_step`/`push_ctx` a
+    /// real contract call would otherwise get. Mirrors each precompile's
+    /// actual data dependency: `identity` (0x04) forwards input bytes
+    /// one-for-one; the hash precompiles (`sha256` 0x02, `ripemd160` 0x03)
+    /// collapse the whole input into one digest, like `SHA3`; the
+    /// remaining signature/EC/modexp precompiles (`ecrecover` 0x01,
+    /// `modexp` 0x05, `ecadd` 0x06, `ecmul` 0x07, `pairing` 0x08, `blake2`
+    /// 0x09) are treated conservatively as tainting their whole output if
+    /// any input byte is tainted.
+    pub fn apply_precompile_taint(
+        &mut self,
+        precompile: u64,
+        arg_offset: usize,
+        arg_len: usize,
+        ret_offset: usize,
+        ret_len: usize,
+    ) {
+        self.ensure_memory_size(arg_offset + arg_len);
+        let input_labels = self.dirty_memory[arg_offset..arg_offset + arg_len].to_vec();
+        self.ensure_memory_size(ret_offset + ret_len);
+        match precompile {
+            0x04 => {
+                let n = arg_len.min(ret_len);
+                for i in 0..n {
+                    self.dirty_memory[ret_offset + i] = input_labels[i].clone();
+                }
+                for i in n..ret_len {
+                    self.dirty_memory[ret_offset + i] = Label::new();
+                }
+            }
+            0x01 | 0x02 | 0x03 | 0x05 | 0x06 | 0x07 | 0x08 | 0x09 => {
+                let label = union_range(input_labels.iter());
+                for b in self.dirty_memory[ret_offset..ret_offset + ret_len].iter_mut() {
+                    *b = label.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Propagates one opcode's worth of taint, mirroring `interp`'s real
+    /// stack/memory/storage, and fires any of `sinks` whose [`SinkPoint`]
+    /// this opcode reached with a nonempty label. When the opcode is
+    /// `SHA3`, also returns the raw preimage bytes it hashed (along with
+    /// the label pushed for the digest) so a caller like
+    /// `Sha3TaintAnalysis` can record them in its own preimage table -
+    /// this engine doesn't know anything about keccak or preimage tables
+    /// itself.
+    pub unsafe fn on_step(
+        &mut self,
+        interp: &mut Interpreter,
+        sinks: &mut [&mut dyn TaintSink],
+    ) -> Option<(Label, Vec<u8>)> {
+        macro_rules! pop_push {
+            ($pop_cnt: expr,$push_cnt: expr) => {{
+                let mut label = Label::new();
+                for _ in 0..$pop_cnt {
+                    label = union(&label, &self.dirty_stack.pop().expect("stack is empty"));
+                }
+                for _ in 0..$push_cnt {
+                    self.dirty_stack.push(label.clone());
+                }
+            }};
+        }
+
+        macro_rules! stack_pop_n {
+            ($pop_cnt: expr) => {
+                for _ in 0..$pop_cnt {
+                    self.dirty_stack.pop().expect("stack is empty");
+                }
+            };
+        }
+
+        macro_rules! push_empty {
+            () => {
+                self.dirty_stack.push(Label::new())
+            };
+        }
+
+        macro_rules! ensure_size {
+            ($t: expr, $size: expr) => {
+                if $t.len() < $size {
+                    $t.resize($size, Label::new());
+                }
+            };
+        }
+
+        // CODECOPY/EXTCODECOPY/RETURNDATACOPY copy from a source this
+        // analysis doesn't label (code bytes, extcode, returndata), so the
+        // destination range is simply cleared rather than propagated.
+        macro_rules! clear_mem {
+            () => {{
+                stack_pop_n!(3);
+                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                let len = as_u64(interp.stack.peek(2).expect("stack is empty")) as usize;
+                ensure_size!(self.dirty_memory, mem_offset + len);
+                for b in self.dirty_memory[mem_offset..mem_offset + len].iter_mut() {
+                    *b = Label::new();
+                }
+            }};
+        }
+
+        macro_rules! fire {
+            ($point: expr, $site: expr, $label: expr) => {
+                if !$label.is_empty() {
+                    for sink in sinks.iter_mut() {
+                        sink.on_tainted($point, $site, &$label);
+                    }
+                }
+            };
+        }
+
+        assert_eq!(interp.stack.len(), self.dirty_stack.len());
+
+        let mut sha3_result = None;
+
+        match *interp.instruction_pointer {
+            0x00 => {}
+            0x01..=0x7 => {
+                pop_push!(2, 1)
+            }
+            0x08..=0x09 => {
+                pop_push!(3, 1)
+            }
+            0xa | 0x0b | 0x10..=0x13 | EQ => {
+                pop_push!(2, 1);
+            }
+            0x15 => {
+                pop_push!(1, 1);
+            }
+            0x16..=0x18 => {
+                pop_push!(2, 1);
+            }
+            0x19 => {
+                pop_push!(1, 1);
+            }
+            0x1a..=0x1d => {
+                pop_push!(2, 1);
+            }
+            0x20 => {
+                // SHA3: the hash's label is the union of its memory input
+                // range's labels, so a branch on the digest still traces
+                // back to the calldata bytes that fed the preimage.
+                stack_pop_n!(2);
+                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                let len = as_u64(interp.stack.peek(1).expect("stack is empty")) as usize;
+                ensure_size!(self.dirty_memory, mem_offset + len);
+                let label = union_range(self.dirty_memory[mem_offset..mem_offset + len].iter());
+                self.dirty_stack.push(label.clone());
+
+                let preimage = interp.memory.data[mem_offset..mem_offset + len].to_vec();
+                sha3_result = Some((label, preimage));
+            }
+            0x30 => push_empty!(),
+            // BALANCE
+            0x31 => pop_push!(1, 1),
+            // ORIGIN
+            0x32 => push_empty!(),
+            // CALLER
+            0x33 => push_empty!(),
+            // CALLVALUE
+            0x34 => push_empty!(),
+            // CALLDATALOAD
+            0x35 => {
+                self.dirty_stack.pop();
+                let offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                if offset == 0 {
+                    // Offset 0 is almost always the function selector, not
+                    // user-controlled argument data - tainting it would
+                    // flag every dispatcher jump table as a tainted
+                    // branch and drown out real findings.
+                    push_empty!()
+                } else if self.ctxs.len() > 0 {
+                    let ctx = self.ctxs.last().unwrap();
+                    self.dirty_stack.push(ctx.read_input(offset, 32));
+                } else {
+                    // Top-level call: the label *is* the calldata offset
+                    // range this load actually reads.
+                    self.dirty_stack
+                        .push((offset as u32..offset as u32 + 32).collect());
+                }
+            }
+            // CALLDATASIZE
+            0x36 => push_empty!(),
+            // CALLDATACOPY
+            0x37 => {
+                stack_pop_n!(3);
+                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                let data_offset = as_u64(interp.stack.peek(1).expect("stack is empty")) as usize;
+                let len = as_u64(interp.stack.peek(2).expect("stack is empty")) as usize;
+                ensure_size!(self.dirty_memory, mem_offset + len);
+                let labels: Vec<Label> = if self.ctxs.len() > 0 {
+                    let ctx = self.ctxs.last().unwrap();
+                    (0..len)
+                        .map(|i| {
+                            ctx.input_data
+                                .get(data_offset + i)
+                                .cloned()
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                } else {
+                    (0..len)
+                        .map(|i| Label::from([(data_offset + i) as u32]))
+                        .collect()
+                };
+                self.dirty_memory[mem_offset..mem_offset + len].clone_from_slice(&labels);
+            }
+            // CODESIZE
+            0x38 => push_empty!(),
+            // CODECOPY
+            0x39 => clear_mem!(),
+            // GASPRICE
+            0x3a => push_empty!(),
+            // EXTCODESIZE
+            0x3b | 0x3f => {
+                stack_pop_n!(1);
+                self.dirty_stack.push(Label::new());
+            }
+            // EXTCODECOPY: address, destOffset, offset, size
+            0x3c => {
+                stack_pop_n!(4);
+                let mem_offset = as_u64(interp.stack.peek(1).expect("stack is empty")) as usize;
+                let len = as_u64(interp.stack.peek(3).expect("stack is empty")) as usize;
+                ensure_size!(self.dirty_memory, mem_offset + len);
+                for b in self.dirty_memory[mem_offset..mem_offset + len].iter_mut() {
+                    *b = Label::new();
+                }
+            }
+            // RETURNDATASIZE
+            0x3d => push_empty!(),
+            // RETURNDATACOPY
+            0x3e => clear_mem!(),
+            // COINBASE
+            0x41..=0x48 => push_empty!(),
+            // POP
+            0x50 => {
+                self.dirty_stack.pop();
+            }
+            // MLOAD
+            0x51 => {
+                self.dirty_stack.pop();
+                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                ensure_size!(self.dirty_memory, mem_offset + 32);
+                let label = union_range(self.dirty_memory[mem_offset..mem_offset + 32].iter());
+                self.dirty_stack.push(label);
+            }
+            // MSTORE
+            0x52 => {
+                stack_pop_n!(1);
+                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                let label = self.dirty_stack.pop().expect("stack is empty");
+                ensure_size!(self.dirty_memory, mem_offset + 32);
+                for b in self.dirty_memory[mem_offset..mem_offset + 32].iter_mut() {
+                    *b = label.clone();
+                }
+            }
+            // MSTORE8
+            0x53 => {
+                stack_pop_n!(1);
+                let mem_offset = as_u64(interp.stack.peek(0).expect("stack is empty")) as usize;
+                let label = self.dirty_stack.pop().expect("stack is empty");
+                ensure_size!(self.dirty_memory, mem_offset + 1);
+                self.dirty_memory[mem_offset] = label;
+            }
+            // SLOAD
+            0x54 => {
+                self.dirty_stack.pop();
+                let key = interp.stack.peek(0).expect("stack is empty");
+                let label = self.dirty_storage.get(&key).cloned().unwrap_or_default();
+                self.dirty_stack.push(label);
+            }
+            // SSTORE
+            0x55 => {
+                let key_label = self.dirty_stack.pop().expect("stack is empty");
+                let label = self.dirty_stack.pop().expect("stack is empty");
+                let key = interp.stack.peek(0).expect("stack is empty");
+                self.dirty_storage.insert(key, label);
+                let site = (interp.contract.address, interp.program_counter());
+                fire!(SinkPoint::SstoreKey, site, key_label);
+            }
+            // JUMP
+            0x56 => {
+                self.dirty_stack.pop();
+            }
+            // JUMPI
+            JUMPI => {
+                self.dirty_stack.pop();
+                let label = self.dirty_stack.pop().expect("stack is empty");
+                let site = (interp.contract.address, interp.program_counter());
+                fire!(SinkPoint::JumpiCondition, site, label);
+            }
+            // PC
+            0x58 | 0x59 | 0x5a => {
+                push_empty!();
+            }
+            // JUMPDEST
+            0x5b => {}
+            // PUSH
+            0x5f..=0x7f => {
+                push_empty!();
+            }
+            // DUP
+            0x80..=0x8f => {
+                let _n = (*interp.instruction_pointer) - 0x80 + 1;
+                let label = self.dirty_stack[self.dirty_stack.len() - _n as usize].clone();
+                self.dirty_stack.push(label);
+            }
+            // SWAP
+            0x90..=0x9f => {
+                let _n = (*interp.instruction_pointer) - 0x90 + 2;
+                let _l = self.dirty_stack.len();
+                self.dirty_stack.swap(_l - _n as usize, _l - 1);
+            }
+            // LOG
+            0xa0..=0xa4 => {
+                let _n = (*interp.instruction_pointer) - 0xa0 + 2;
+                stack_pop_n!(_n);
+            }
+            0xf0 => {
+                stack_pop_n!(3);
+                self.dirty_stack.push(Label::new());
+            }
+            0xf1 | 0xf2 => {
+                let gas_label = self.dirty_stack.pop().expect("stack is empty");
+                let _ = gas_label;
+                let target_label = self.dirty_stack.pop().expect("stack is empty");
+                let value_label = self.dirty_stack.pop().expect("stack is empty");
+                stack_pop_n!(4);
+                self.dirty_stack.push(Label::new());
+
+                let site = (interp.contract.address, interp.program_counter());
+                fire!(SinkPoint::CallTarget, site, target_label);
+                fire!(SinkPoint::CallValue, site, value_label);
+
+                let address = interp.stack.peek(1).expect("stack is empty");
+                match precompile_id(address) {
+                    Some(precompile) => {
+                        let arg_offset =
+                            as_u64(interp.stack.peek(3).expect("stack is empty")) as usize;
+                        let arg_len =
+                            as_u64(interp.stack.peek(4).expect("stack is empty")) as usize;
+                        let ret_offset =
+                            as_u64(interp.stack.peek(5).expect("stack is empty")) as usize;
+                        let ret_len =
+                            as_u64(interp.stack.peek(6).expect("stack is empty")) as usize;
+                        self.apply_precompile_taint(
+                            precompile, arg_offset, arg_len, ret_offset, ret_len,
+                        );
+                    }
+                    None => self.push_ctx(interp),
+                }
+            }
+            0xf3 => {
+                stack_pop_n!(2);
+            }
+            0xf4 | 0xfa => {
+                let gas_label = self.dirty_stack.pop().expect("stack is empty");
+                let _ = gas_label;
+                let target_label = self.dirty_stack.pop().expect("stack is empty");
+                stack_pop_n!(4);
+                self.dirty_stack.push(Label::new());
+
+                let site = (interp.contract.address, interp.program_counter());
+                fire!(SinkPoint::CallTarget, site, target_label);
+
+                let address = interp.stack.peek(1).expect("stack is empty");
+                match precompile_id(address) {
+                    Some(precompile) => {
+                        let arg_offset =
+                            as_u64(interp.stack.peek(2).expect("stack is empty")) as usize;
+                        let arg_len =
+                            as_u64(interp.stack.peek(3).expect("stack is empty")) as usize;
+                        let ret_offset =
+                            as_u64(interp.stack.peek(4).expect("stack is empty")) as usize;
+                        let ret_len =
+                            as_u64(interp.stack.peek(5).expect("stack is empty")) as usize;
+                        self.apply_precompile_taint(
+                            precompile, arg_offset, arg_len, ret_offset, ret_len,
+                        );
+                    }
+                    None => self.push_ctx(interp),
+                }
+            }
+            0xf5 => {
+                stack_pop_n!(4);
+                self.dirty_stack.push(Label::new());
+            }
+            0xfd => {
+                // stack_pop_n!(2);
+            }
+            0xfe => {
+                // stack_pop_n!(1);
+            }
+            0xff => {
+                // SELFDESTRUCT halts execution, so there's no following
+                // on_step in this frame to keep dirty_stack in sync for -
+                // peeking (rather than popping) the beneficiary's label is
+                // enough to fire the sink.
+                let label = self.dirty_stack.last().cloned().unwrap_or_default();
+                let site = (interp.contract.address, interp.program_counter());
+                fire!(SinkPoint::SelfdestructBeneficiary, site, label);
+            }
+            _ => panic!("unknown opcode: {:x}", *interp.instruction_pointer),
+        }
+
+        sha3_result
+    }
+}
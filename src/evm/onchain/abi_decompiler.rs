@@ -1,14 +1,53 @@
 use std::collections::hash_map::DefaultHasher;
 
+use crate::evm::bytecode_analyzer::find_function_selectors;
 use crate::evm::contract_utils::ABIConfig;
 use heimdall::decompile::decompile_with_bytecode;
 use heimdall::decompile::out::solidity::ABIStructure;
+use revm_primitives::{Bytecode, Bytes};
 
 use std::hash::Hash;
 
+extern crate crypto;
+use self::crypto::digest::Digest;
+use self::crypto::sha3::Sha3;
+
+fn keccak256(preimage: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    let mut hasher = Sha3::keccak256();
+    hasher.input(preimage);
+    hasher.result(&mut digest);
+    digest
+}
+
+/// `heimdall` names a function it can't resolve `Unresolved_<selector hex>`,
+/// in which case the selector is already in the name; otherwise it hands
+/// back a real signature, and the only way to recover its selector is to
+/// hash it and check that hash is actually one the dispatcher branches on
+/// (`find_function_selectors`) - a name heimdall resolved from the wrong
+/// ABI, or guessed, won't match and is left as `[0; 4]` rather than guessed
+/// at.
+fn resolve_selector(name: &str, abi: &str, known_selectors: &std::collections::HashSet<[u8; 4]>) -> Option<[u8; 4]> {
+    if let Ok(raw) = hex::decode(name) {
+        if raw.len() == 4 {
+            return Some([raw[0], raw[1], raw[2], raw[3]]);
+        }
+    }
+
+    let canonical = format!("{}{}", name, abi);
+    let digest = keccak256(canonical.as_bytes());
+    let candidate = [digest[0], digest[1], digest[2], digest[3]];
+    known_selectors.contains(&candidate).then_some(candidate)
+}
+
 pub fn fetch_abi_heimdall(bytecode: String) -> Vec<ABIConfig> {
     let mut hasher = DefaultHasher::new();
     bytecode.hash(&mut hasher);
+
+    let known_selectors = hex::decode(bytecode.trim_start_matches("0x"))
+        .map(|bytes| find_function_selectors(&Bytecode::new_raw(Bytes::from(bytes))))
+        .unwrap_or_default();
+
     let heimdall_result = decompile_with_bytecode(bytecode, "".to_string());
     let mut result = vec![];
     for heimdall_abi in heimdall_result {
@@ -25,17 +64,16 @@ pub fn fetch_abi_heimdall(bytecode: String) -> Vec<ABIConfig> {
                 }
 
                 let name = func.name.replace("Unresolved_", "");
-                let mut abi_config = ABIConfig {
-                    abi: format!("({})", inputs.join(",")),
-                    function: [0; 4],
+                let abi = format!("({})", inputs.join(","));
+                let function = resolve_selector(&name, &abi, &known_selectors).unwrap_or([0; 4]);
+                let abi_config = ABIConfig {
+                    abi,
+                    function,
                     function_name: name.clone(),
                     is_static: func.state_mutability == "view",
                     is_payable: func.state_mutability == "payable",
                     is_constructor: false,
                 };
-                abi_config
-                    .function
-                    .copy_from_slice(hex::decode(name).unwrap().as_slice());
                 result.push(abi_config)
             }
             _ => {
@@ -0,0 +1,121 @@
+/// A registry of block-explorer / RPC data sources, keyed by chain id.
+///
+/// Every endpoint is parsed (and any path template joined) through the
+/// `url` crate so malformed config fails at load time instead of
+/// mid-fuzz, and `EndpointSpec::abi_url` percent-encodes its query params.
+/// `OnChainConfig::new_with_registry` takes only a spec's base URL from
+/// here; `OnChainConfig::fetch_abi_uncached` appends its query string to
+/// that base with `format!`, without percent-encoding.
+use crate::evm::onchain::endpoints::Chain;
+use std::collections::HashMap;
+use url::Url;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExplorerKind {
+    Etherscan,
+    Blockscout,
+    Arbiscan,
+    BscScan,
+    GenericRpc,
+}
+
+#[derive(Clone, Debug)]
+pub struct EndpointSpec {
+    pub kind: ExplorerKind,
+    pub base_url: Url,
+    pub headers: Vec<(String, String)>,
+}
+
+impl EndpointSpec {
+    pub fn new(kind: ExplorerKind, base_url: &str, headers: Vec<(String, String)>) -> Result<Self, String> {
+        let base_url = Url::parse(base_url).map_err(|e| format!("invalid endpoint url {}: {}", base_url, e))?;
+        Ok(Self { kind, base_url, headers })
+    }
+
+    /// Builds the URL used to fetch a contract's ABI/source, percent-encoding
+    /// the address and API key through `Url::query_pairs_mut`.
+    pub fn abi_url(&self, address: &str, api_key: &str) -> Url {
+        let mut url = self.base_url.clone();
+        if self.kind != ExplorerKind::GenericRpc {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("module", "contract")
+                .append_pair("action", "getabi")
+                .append_pair("address", address)
+                .append_pair("format", "json");
+            if !api_key.is_empty() {
+                query.append_pair("apikey", api_key);
+            }
+        }
+        url
+    }
+
+    /// Joins `path` onto the endpoint's base URL (for RPC-style backends
+    /// that expose a path per method rather than a query-string API).
+    pub fn join(&self, path: &str) -> Result<Url, String> {
+        self.base_url.join(path).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EndpointRegistry {
+    by_chain_id: HashMap<u32, EndpointSpec>,
+}
+
+impl EndpointRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, chain_id: u32, spec: EndpointSpec) {
+        self.by_chain_id.insert(chain_id, spec);
+    }
+
+    pub fn get(&self, chain_id: u32) -> Option<&EndpointSpec> {
+        self.by_chain_id.get(&chain_id)
+    }
+
+    /// Builds the default registry for every chain `Chain` knows about,
+    /// validating each endpoint URL up front.
+    pub fn default_for_known_chains() -> Result<Self, String> {
+        let chains = [
+            Chain::ETH,
+            Chain::GOERLI,
+            Chain::SEPOLIA,
+            Chain::BSC,
+            Chain::CHAPEL,
+            Chain::POLYGON,
+            Chain::MUMBAI,
+            Chain::FANTOM,
+            Chain::AVALANCHE,
+            Chain::OPTIMISM,
+            Chain::ARBITRUM,
+            Chain::GNOSIS,
+            Chain::BASE,
+            Chain::CELO,
+            Chain::ZKEVM,
+            Chain::ZKEVM_TESTNET,
+            Chain::LOCAL,
+        ];
+        let mut registry = Self::new();
+        for chain in chains {
+            let kind = match chain {
+                Chain::ARBITRUM => ExplorerKind::Arbiscan,
+                Chain::BSC | Chain::CHAPEL => ExplorerKind::BscScan,
+                Chain::LOCAL => ExplorerKind::GenericRpc,
+                _ => ExplorerKind::Etherscan,
+            };
+            let spec = EndpointSpec::new(kind, chain.get_chain_etherscan_base().as_str(), vec![])?;
+            registry.register(chain.get_chain_id(), spec);
+        }
+        Ok(registry)
+    }
+
+    /// Lets a user-specified `--explorer-url` (e.g. a self-hosted
+    /// Blockscout instance) override the default endpoint for a chain.
+    pub fn override_explorer(&mut self, chain_id: u32, explorer_url: &str) -> Result<(), String> {
+        let spec = EndpointSpec::new(ExplorerKind::Blockscout, explorer_url, vec![])?;
+        self.register(chain_id, spec);
+        Ok(())
+    }
+}
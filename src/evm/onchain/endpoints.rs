@@ -1,20 +1,23 @@
 use crate::cache::{Cache, FileSystemCache};
 use bytes::Bytes;
-use reqwest::header::HeaderMap;
-use retry::OperationResult;
-use retry::{delay::Fixed, retry_with_index};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
-use crate::evm::types::{EVMAddress, EVMU256};
+use crate::evm::onchain::endpoint_registry::EndpointRegistry;
+use crate::evm::types::{convert_u256_to_h160, EVMAddress, EVMU256};
+use crate::evm::uniswap::{get_uniswap_info, UniswapInfo, UniswapProvider};
+use std::collections::HashSet;
 use revm_interpreter::analysis::to_analysed;
 use revm_primitives::Bytecode;
 use serde::Deserialize;
 use serde_json::Value;
 use std::fmt::Debug;
 use std::panic;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Copy)]
@@ -137,6 +140,27 @@ impl Chain {
         .to_string()
     }
 
+    // Fallback RPC endpoints for the chain, tried in order after the primary
+    // one returned by `get_chain_rpc` starts failing.
+    pub fn get_chain_rpc_list(&self) -> Vec<String> {
+        let mut endpoints = vec![self.get_chain_rpc()];
+        match self {
+            Chain::ETH => {
+                endpoints.push("https://rpc.ankr.com/eth".to_string());
+                endpoints.push("https://cloudflare-eth.com".to_string());
+            }
+            Chain::BSC => {
+                endpoints.push("https://bsc-dataseed.binance.org".to_string());
+                endpoints.push("https://bsc-dataseed1.defibit.io".to_string());
+            }
+            Chain::POLYGON => {
+                endpoints.push("https://rpc.ankr.com/polygon".to_string());
+            }
+            _ => {}
+        }
+        endpoints
+    }
+
     pub fn get_chain_etherscan_base(&self) -> String {
         match self {
             Chain::ETH => "https://api.etherscan.io/api",
@@ -185,9 +209,135 @@ pub struct GetPairResponseDataPairToken {
     pub id: String,
 }
 
+// Controls how `get`/`post` interact with `rpc_cache`. The cache key already
+// excludes headers (it's salted only by chain id / block / url / body, see
+// `cache_key`), so a recording stays valid regardless of the volatile
+// browser-mimicking headers `get_header` injects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordReplayMode {
+    // fetch live and (as today) persist well-formed responses for next time
+    Record,
+    // serve exclusively from `rpc_cache`; a miss is a hard error rather than
+    // a silent network fetch, so CI/replay runs never touch the network
+    Replay,
+}
+
+// The result of comparing a single storage slot across two blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlotDiff {
+    Born(EVMU256),
+    Died(EVMU256),
+    Changed(EVMU256, EVMU256),
+    Same(EVMU256),
+}
+
+// endpoints that have failed this many times in a row are treated as dead
+// and skipped by the failover rotation until the process restarts
+const MAX_ENDPOINT_FAILURES: u32 = 3;
+
+// bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)
+const EIP1967_LOGIC_SLOT: &str =
+    "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+// bytes32(uint256(keccak256('eip1967.proxy.beacon')) - 1)
+const EIP1967_BEACON_SLOT: &str =
+    "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+// keccak256("PROXIABLE")
+const EIP1822_PROXIABLE_SLOT: &str =
+    "c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7";
+const MAX_PROXY_RESOLUTION_DEPTH: usize = 4;
+
+const RETRY_BASE_DELAY_MS: u64 = 250;
+const RETRY_MAX_DELAY_MS: u64 = 30_000;
+const RETRY_MAX_ATTEMPTS: u32 = 6;
+const DEFAULT_RATE_LIMIT_PER_SEC: f64 = 5.0;
+
+// Distinguishes failures worth retrying (timeouts, 429/5xx) from ones that
+// will never succeed no matter how many times we retry (404, bad API key).
+#[derive(Clone, Debug)]
+pub enum FetchError {
+    Permanent(String),
+    Transient(String),
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Permanent(msg) => write!(f, "permanent error: {}", msg),
+            FetchError::Transient(msg) => write!(f, "transient error: {}", msg),
+        }
+    }
+}
+
+// Exponential backoff (base 250ms, capped ~30s) with up to 25% jitter so a
+// thundering herd of fuzz workers doesn't retry in lockstep.
+fn backoff_delay(attempt: u32) -> Duration {
+    let exp = RETRY_BASE_DELAY_MS.saturating_mul(1u64 << attempt.saturating_sub(1).min(10));
+    let capped = exp.min(RETRY_MAX_DELAY_MS);
+    let jitter = rand::random::<u64>() % (capped / 4 + 1);
+    Duration::from_millis(capped + jitter)
+}
+
+fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+// A simple token-bucket limiter shared (per host) across every OnChainConfig
+// and fuzz worker thread in the process, so concurrent workers don't
+// collectively trip an explorer's rate limit.
+struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: Mutex<(f64, std::time::Instant)>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_sec: f64) -> Self {
+        let capacity = requests_per_sec.max(1.0);
+        Self {
+            capacity,
+            refill_per_sec: capacity,
+            tokens: Mutex::new((capacity, std::time::Instant::now())),
+        }
+    }
+
+    fn acquire(&self) {
+        loop {
+            {
+                let mut guard = self.tokens.lock().unwrap();
+                let (mut tokens, last) = *guard;
+                let now = std::time::Instant::now();
+                tokens = (tokens + now.duration_since(last).as_secs_f64() * self.refill_per_sec)
+                    .min(self.capacity);
+                if tokens >= 1.0 {
+                    *guard = (tokens - 1.0, now);
+                    return;
+                }
+                *guard = (tokens, now);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+fn rate_limiter_for_host(host: &str, requests_per_sec: f64) -> Arc<RateLimiter> {
+    static LIMITERS: OnceLock<Mutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+    let limiters = LIMITERS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = limiters.lock().unwrap();
+    guard
+        .entry(host.to_string())
+        .or_insert_with(|| Arc::new(RateLimiter::new(requests_per_sec)))
+        .clone()
+}
+
 #[derive(Clone, Debug)]
 pub struct OnChainConfig {
     pub endpoint_url: String,
+    pub endpoints: Vec<String>,
+    current_endpoint: RefCell<usize>,
+    endpoint_failures: RefCell<HashMap<usize, u32>>,
     pub client: reqwest::blocking::Client,
     pub chain_id: u32,
     pub block_number: String,
@@ -197,6 +347,15 @@ pub struct OnChainConfig {
     pub etherscan_base: String,
 
     pub chain_name: String,
+    // max requests/sec this config's host(s) may issue; shared globally
+    // across every OnChainConfig/thread via `rate_limiter_for_host`
+    pub rate_limit_per_sec: f64,
+    // `None` behaves as before (fetch, cache well-formed responses); see
+    // `RecordReplayMode` for the explicit record/replay modes
+    pub record_replay_mode: Option<RecordReplayMode>,
+    // user-supplied header overrides from config/CLI, merged on top of
+    // `RequestHeaders::defaults()`
+    pub extra_headers: Vec<(String, String)>,
 
     slot_cache: HashMap<(EVMAddress, EVMU256), EVMU256>,
     code_cache: HashMap<EVMAddress, Bytecode>,
@@ -210,7 +369,7 @@ pub struct OnChainConfig {
 impl OnChainConfig {
     pub fn new(chain: Chain, block_number: u64) -> Self {
         Self::new_raw(
-            chain.get_chain_rpc(),
+            chain.get_chain_rpc_list(),
             chain.get_chain_id(),
             block_number,
             chain.get_chain_etherscan_base(),
@@ -218,15 +377,30 @@ impl OnChainConfig {
         )
     }
 
+    /// Like `new`, but resolves the block-explorer base URL through an
+    /// `EndpointRegistry` instead of `Chain::get_chain_etherscan_base`, so a
+    /// user-supplied `--explorer-url` override is picked up transparently.
+    pub fn new_with_registry(chain: Chain, block_number: u64, registry: &EndpointRegistry) -> Self {
+        let mut config = Self::new(chain, block_number);
+        if let Some(spec) = registry.get(chain.get_chain_id()) {
+            config.etherscan_base = spec.base_url.to_string();
+        }
+        config
+    }
+
     pub fn new_raw(
-        endpoint_url: String,
+        endpoints: Vec<String>,
         chain_id: u32,
         block_number: u64,
         etherscan_base: String,
         chain_name: String,
     ) -> Self {
+        assert!(!endpoints.is_empty(), "at least one RPC endpoint is required");
         Self {
-            endpoint_url,
+            endpoint_url: endpoints[0].clone(),
+            endpoints,
+            current_endpoint: RefCell::new(0),
+            endpoint_failures: RefCell::new(HashMap::new()),
             client: reqwest::blocking::Client::builder()
                 .timeout(Duration::from_secs(20))
                 .build()
@@ -241,6 +415,9 @@ impl OnChainConfig {
             etherscan_api_key: vec![],
             etherscan_base,
             chain_name,
+            rate_limit_per_sec: DEFAULT_RATE_LIMIT_PER_SEC,
+            record_replay_mode: None,
+            extra_headers: vec![],
             slot_cache: Default::default(),
             code_cache: Default::default(),
             price_cache: Default::default(),
@@ -252,109 +429,185 @@ impl OnChainConfig {
         }
     }
 
-    fn get(&self, url: String) -> Option<String> {
+    // Cache keys are salted with the chain id and the pinned block so that
+    // switching forks (or re-running against a different block) can never
+    // return a response that was cached for a different chain state.
+    fn cache_key(&self, kind: &str, parts: &[&str]) -> String {
         let mut hasher = DefaultHasher::new();
-        let key = format!("post_{}", url.as_str());
+        let mut key = format!("{}_{}_{}", kind, self.chain_id, self.block_number);
+        if let Some(block_hash) = &self.block_hash {
+            key.push('_');
+            key.push_str(block_hash);
+        }
+        for part in parts {
+            key.push('_');
+            key.push_str(part);
+        }
         key.hash(&mut hasher);
-        let hash = hasher.finish().to_string();
+        hasher.finish().to_string()
+    }
+
+    // Builds the headers sent with every request: `RequestHeaders::defaults()`
+    // with `self.extra_headers` layered on top and, if set, an Etherscan API
+    // key pulled from the environment. Falls back to the unmodified defaults
+    // on a malformed override rather than panicking mid-fuzz.
+    fn build_headers(&self) -> HeaderMap {
+        RequestHeaders::defaults()
+            .with_overrides(&self.extra_headers)
+            .and_then(|h| h.with_env_secret("X-API-Key", "ITYFUZZ_ETHERSCAN_API_KEY"))
+            .unwrap_or_else(|e| {
+                println!("[endpoints] invalid header config ({}), falling back to defaults", e);
+                RequestHeaders::defaults().build()
+            })
+    }
+
+    fn get(&self, url: String) -> Option<String> {
+        let hash = self.cache_key("get", &[url.as_str()]);
         if let Ok(t) = self.rpc_cache.load(hash.as_str()) {
             return Some(t);
         }
-        match retry_with_index(Fixed::from_millis(1000), |current_try| {
-            if current_try > 5 {
-                return OperationResult::Err("did not succeed within 3 tries".to_string());
-            }
-            match self
-                .client
-                .get(url.to_string())
-                .headers(get_header())
-                .send()
-            {
-                Ok(resp) => {
-                    let text = resp.text();
-                    match text {
-                        Ok(t) => {
-                            if t.contains("Max rate limit reached") {
-                                println!("Etherscan max rate limit reached, retrying...");
-                                return OperationResult::Retry("Rate limit reached".to_string());
-                            } else {
-                                return OperationResult::Ok(t);
-                            }
-                        }
-                        Err(e) => {
-                            println!("{:?}", e);
-                            return OperationResult::Retry("failed to parse response".to_string());
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("Error: {}", e);
-                    return OperationResult::Retry("failed to send request".to_string());
-                }
-            }
+        if self.record_replay_mode == Some(RecordReplayMode::Replay) {
+            panic!(
+                "replay mode: no recorded response for GET {} (key {}); re-run in record mode first",
+                url, hash
+            );
+        }
+        match self.fetch_with_retry(url.as_str(), || {
+            self.client.get(url.as_str()).headers(self.build_headers()).send()
         }) {
             Ok(t) => {
-                if !t.contains("error") {
+                if is_well_formed_response(t.as_str()) {
                     self.rpc_cache.save(hash.as_str(), t.as_str()).unwrap();
                 }
-
                 Some(t)
             }
             Err(e) => {
-                println!("Error: {}", e);
+                println!("failed to GET {}: {}", url, e);
                 None
             }
         }
     }
 
     fn post(&self, url: String, data: String) -> Option<String> {
-        let mut hasher = DefaultHasher::new();
-        let key = format!("post_{}_{}", url.as_str(), data.as_str());
-        key.hash(&mut hasher);
-        let hash = hasher.finish().to_string();
+        let hash = self.cache_key("post", &[url.as_str(), data.as_str()]);
         if let Ok(t) = self.rpc_cache.load(hash.as_str()) {
             return Some(t);
         }
-        match retry_with_index(Fixed::from_millis(100), |current_try| {
-            if current_try > 3 {
-                return OperationResult::Err("did not succeed within 3 tries".to_string());
-            }
-            match self
-                .client
-                .post(url.to_string())
+        if self.record_replay_mode == Some(RecordReplayMode::Replay) {
+            panic!(
+                "replay mode: no recorded response for POST {} (key {}); re-run in record mode first",
+                url, hash
+            );
+        }
+        match self.fetch_with_retry(url.as_str(), || {
+            self.client
+                .post(url.as_str())
                 .header("Content-Type", "application/json")
-                .headers(get_header())
-                .body(data.to_string())
+                .headers(self.build_headers())
+                .body(data.clone())
                 .send()
-            {
+        }) {
+            Ok(t) => {
+                if is_well_formed_response(t.as_str()) {
+                    self.rpc_cache.save(hash.as_str(), t.as_str()).unwrap();
+                }
+                Some(t)
+            }
+            Err(e) => {
+                println!("failed to POST {}: {}", url, e);
+                None
+            }
+        }
+    }
+
+    // Runs `send` with a global per-host token-bucket rate limit and a retry
+    // loop that honors `Retry-After` on 429s, backs off exponentially (with
+    // jitter) on connection resets / 5xx, and gives up after
+    // `RETRY_MAX_ATTEMPTS`, distinguishing permanent failures (404, bad API
+    // key) from transient ones via `FetchError`.
+    fn fetch_with_retry<F>(&self, url: &str, mut send: F) -> Result<String, FetchError>
+    where
+        F: FnMut() -> Result<reqwest::blocking::Response, reqwest::Error>,
+    {
+        let limiter = rate_limiter_for_host(host_of(url).as_str(), self.rate_limit_per_sec);
+        let mut attempt: u32 = 0;
+        loop {
+            attempt += 1;
+            limiter.acquire();
+            match send() {
                 Ok(resp) => {
-                    let text = resp.text();
-                    match text {
-                        Ok(t) => {
-                            return OperationResult::Ok(t);
+                    let status = resp.status();
+                    if status.as_u16() == 404 {
+                        return Err(FetchError::Permanent(format!("{} returned 404", url)));
+                    }
+                    if status.as_u16() == 429 {
+                        if attempt >= RETRY_MAX_ATTEMPTS {
+                            return Err(FetchError::Transient(
+                                "rate limited (429) and out of retries".to_string(),
+                            ));
+                        }
+                        let delay = resp
+                            .headers()
+                            .get(reqwest::header::RETRY_AFTER)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|s| s.parse::<u64>().ok())
+                            .map(Duration::from_secs)
+                            .unwrap_or_else(|| backoff_delay(attempt));
+                        println!("{} rate limited, retrying in {:?}", url, delay);
+                        std::thread::sleep(delay);
+                        continue;
+                    }
+                    if status.is_server_error() {
+                        if attempt >= RETRY_MAX_ATTEMPTS {
+                            return Err(FetchError::Transient(format!(
+                                "{} kept returning {} after {} attempts",
+                                url, status, attempt
+                            )));
+                        }
+                        std::thread::sleep(backoff_delay(attempt));
+                        continue;
+                    }
+                    match resp.text() {
+                        Ok(text) => {
+                            if text.contains("Max rate limit reached") {
+                                if attempt >= RETRY_MAX_ATTEMPTS {
+                                    return Err(FetchError::Transient(
+                                        "etherscan rate limit, out of retries".to_string(),
+                                    ));
+                                }
+                                println!("Etherscan max rate limit reached, retrying...");
+                                std::thread::sleep(backoff_delay(attempt));
+                                continue;
+                            }
+                            if text.to_lowercase().contains("invalid api key") {
+                                return Err(FetchError::Permanent(
+                                    "invalid API key".to_string(),
+                                ));
+                            }
+                            return Ok(text);
                         }
                         Err(e) => {
-                            println!("{:?}", e);
-                            return OperationResult::Retry("failed to parse response".to_string());
+                            if attempt >= RETRY_MAX_ATTEMPTS {
+                                return Err(FetchError::Transient(format!(
+                                    "failed to read response body after {} attempts: {}",
+                                    attempt, e
+                                )));
+                            }
+                            std::thread::sleep(backoff_delay(attempt));
                         }
                     }
                 }
                 Err(e) => {
+                    if attempt >= RETRY_MAX_ATTEMPTS {
+                        return Err(FetchError::Transient(format!(
+                            "{} failed after {} attempts: {}",
+                            url, attempt, e
+                        )));
+                    }
                     println!("Error: {}", e);
-                    return OperationResult::Retry("failed to send request".to_string());
+                    std::thread::sleep(backoff_delay(attempt));
                 }
             }
-        }) {
-            Ok(t) => {
-                if !t.contains("error") {
-                    self.rpc_cache.save(hash.as_str(), t.as_str()).unwrap();
-                }
-                Some(t)
-            }
-            Err(e) => {
-                println!("Error: {}", e);
-                None
-            }
         }
     }
 
@@ -362,6 +615,10 @@ impl OnChainConfig {
         self.etherscan_api_key.push(key);
     }
 
+    pub fn set_record_replay_mode(&mut self, mode: Option<RecordReplayMode>) {
+        self.record_replay_mode = mode;
+    }
+
     pub fn fetch_storage_all(
         &mut self,
         address: EVMAddress,
@@ -383,10 +640,18 @@ impl OnChainConfig {
             self.block_number, "latest",
             "fetch_full_storage only works with latest block"
         );
+        self.fetch_storage_all_uncached_at(address, self.block_number.as_str())
+    }
+
+    fn fetch_storage_all_uncached_at(
+        &self,
+        address: EVMAddress,
+        block_number: &str,
+    ) -> Option<Arc<HashMap<String, EVMU256>>> {
         let resp = {
             let mut params = String::from("[");
             params.push_str(&format!("\"0x{:x}\",", address));
-            params.push_str(&format!("\"{}\"", self.block_number));
+            params.push_str(&format!("\"{}\"", block_number));
             params.push_str("]");
             self._request("eth_getStorageAll".to_string(), params)
         };
@@ -409,6 +674,50 @@ impl OnChainConfig {
         }
     }
 
+    // Classifies every storage slot of `address` relative to its value at `block_a`
+    // vs. `block_b`. Pass `0` for either block to mean "latest".
+    pub fn diff_storage(
+        &self,
+        address: EVMAddress,
+        block_a: u64,
+        block_b: u64,
+    ) -> HashMap<EVMU256, SlotDiff> {
+        let fmt_block = |b: u64| {
+            if b == 0 {
+                "latest".to_string()
+            } else {
+                format!("0x{:x}", b)
+            }
+        };
+        let storage_a = self
+            .fetch_storage_all_uncached_at(address, fmt_block(block_a).as_str())
+            .unwrap_or_default();
+        let storage_b = self
+            .fetch_storage_all_uncached_at(address, fmt_block(block_b).as_str())
+            .unwrap_or_default();
+
+        let parse_key = |k: &str| EVMU256::from_str_radix(k, 16).unwrap();
+
+        let mut diff = HashMap::new();
+        for (k, v_a) in storage_a.iter() {
+            let key = parse_key(k);
+            diff.insert(
+                key,
+                match storage_b.get(k) {
+                    Some(v_b) if v_b == v_a => SlotDiff::Same(*v_a),
+                    Some(v_b) => SlotDiff::Changed(*v_a, *v_b),
+                    None => SlotDiff::Died(*v_a),
+                },
+            );
+        }
+        for (k, v_b) in storage_b.iter() {
+            if !storage_a.contains_key(k) {
+                diff.insert(parse_key(k), SlotDiff::Born(*v_b));
+            }
+        }
+        diff
+    }
+
     pub fn fetch_blk_hash(&mut self) -> &String {
         if self.block_hash == None {
             self.block_hash = {
@@ -528,18 +837,68 @@ impl OnChainConfig {
         if self.abi_cache.contains_key(&address) {
             return self.abi_cache.get(&address).unwrap().clone();
         }
-        let abi = self.fetch_abi_uncached(address);
+        let target = self.resolve_implementation(address).unwrap_or(address);
+        let abi = self.fetch_abi_uncached(target);
         self.abi_cache.insert(address, abi.clone());
         abi
     }
 
+    // If `address` is an EIP-1967 (storage or beacon slot) or EIP-1822
+    // (PROXIABLE) proxy, returns the implementation address it currently
+    // delegates to (following the chain, depth-capped and cycle-guarded).
+    pub fn resolve_implementation(&mut self, address: EVMAddress) -> Option<EVMAddress> {
+        self.resolve_implementation_rec(address, &mut HashSet::new(), 0)
+    }
+
+    fn resolve_implementation_rec(
+        &mut self,
+        address: EVMAddress,
+        seen: &mut HashSet<EVMAddress>,
+        depth: usize,
+    ) -> Option<EVMAddress> {
+        if depth >= MAX_PROXY_RESOLUTION_DEPTH || !seen.insert(address) {
+            return None;
+        }
+        for slot_hex in [
+            EIP1967_LOGIC_SLOT,
+            EIP1967_BEACON_SLOT,
+            EIP1822_PROXIABLE_SLOT,
+        ] {
+            let slot = EVMU256::from_str_radix(slot_hex, 16).unwrap();
+            let value = self.get_contract_slot(address, slot, false);
+            if value == EVMU256::ZERO {
+                continue;
+            }
+            let mut implementation = convert_u256_to_h160(value);
+            if slot_hex == EIP1967_BEACON_SLOT {
+                // the beacon slot holds a UpgradeableBeacon address; ask it
+                // for the logic contract it currently points to
+                match self.eth_call(implementation, "5c60da1b") {
+                    Some(ret) if ret.len() >= 32 => {
+                        implementation =
+                            EVMAddress::from_slice(&ret[ret.len() - 20..]);
+                    }
+                    _ => continue,
+                }
+            }
+            if implementation == address {
+                continue;
+            }
+            return Some(
+                self.resolve_implementation_rec(implementation, seen, depth + 1)
+                    .unwrap_or(implementation),
+            );
+        }
+        None
+    }
+
     fn _request(&self, method: String, params: String) -> Option<Value> {
         let data = format!(
             "{{\"jsonrpc\":\"2.0\", \"method\": \"{}\", \"params\": {}, \"id\": {}}}",
             method, params, self.chain_id
         );
 
-        match self.post(self.endpoint_url.clone(), data) {
+        match self.post_with_failover(data) {
             Some(resp) => {
                 let json: Result<Value, _> = serde_json::from_str(&resp);
 
@@ -555,7 +914,7 @@ impl OnChainConfig {
             }
 
             None => {
-                println!("failed to fetch from {}", self.endpoint_url);
+                println!("failed to fetch from all RPC endpoints");
                 None
             }
         }
@@ -567,7 +926,7 @@ impl OnChainConfig {
             method, params, id
         );
 
-        match self.post(self.endpoint_url.clone(), data) {
+        match self.post_with_failover(data) {
             Some(resp) => {
                 let json: Result<Value, _> = serde_json::from_str(&resp);
 
@@ -583,12 +942,172 @@ impl OnChainConfig {
             }
 
             None => {
-                println!("failed to fetch from {}", self.endpoint_url);
+                println!("failed to fetch from all RPC endpoints");
                 None
             }
         }
     }
 
+    // Posts `data` to the current RPC endpoint, rotating to the next live
+    // endpoint (mirroring the `etherscan_api_key` round-robin) on timeout,
+    // rate-limiting, or a malformed response, and marking an endpoint dead
+    // once it has failed `MAX_ENDPOINT_FAILURES` times in a row.
+    fn post_with_failover(&self, data: String) -> Option<String> {
+        let n = self.endpoints.len();
+        let start = *self.current_endpoint.borrow();
+        let mut last_resp = None;
+        for attempt in 0..n {
+            let idx = (start + attempt) % n;
+            if n > 1 && self.endpoint_is_dead(idx) {
+                continue;
+            }
+            let url = self.endpoints[idx].clone();
+            match self.post(url.clone(), data.clone()) {
+                Some(resp) if is_well_formed_response(&resp) => {
+                    *self.current_endpoint.borrow_mut() = idx;
+                    self.endpoint_failures.borrow_mut().remove(&idx);
+                    return Some(resp);
+                }
+                Some(resp) => {
+                    println!("endpoint {} returned a malformed response, rotating", url);
+                    last_resp = Some(resp);
+                    self.mark_endpoint_failure(idx);
+                }
+                None => {
+                    println!("endpoint {} failed, rotating", url);
+                    self.mark_endpoint_failure(idx);
+                }
+            }
+        }
+        last_resp
+    }
+
+    fn endpoint_is_dead(&self, idx: usize) -> bool {
+        self.endpoint_failures
+            .borrow()
+            .get(&idx)
+            .copied()
+            .unwrap_or(0)
+            >= MAX_ENDPOINT_FAILURES
+    }
+
+    fn mark_endpoint_failure(&self, idx: usize) {
+        *self
+            .endpoint_failures
+            .borrow_mut()
+            .entry(idx)
+            .or_insert(0) += 1;
+    }
+
+    // Packs multiple JSON-RPC calls into a single batched POST and
+    // demultiplexes the responses by `id`, returning `None` at the
+    // positions of calls the node didn't answer.
+    pub fn batch_request(&self, calls: Vec<(String, String)>) -> Vec<Option<Value>> {
+        if calls.is_empty() {
+            return vec![];
+        }
+        let body = {
+            let entries: Vec<String> = calls
+                .iter()
+                .enumerate()
+                .map(|(id, (method, params))| {
+                    format!(
+                        "{{\"jsonrpc\":\"2.0\", \"method\": \"{}\", \"params\": {}, \"id\": {}}}",
+                        method, params, id
+                    )
+                })
+                .collect();
+            format!("[{}]", entries.join(","))
+        };
+
+        let mut results = vec![None; calls.len()];
+        if let Some(resp) = self.post_with_failover(body) {
+            match serde_json::from_str::<Value>(&resp) {
+                Ok(Value::Array(items)) => {
+                    for item in items {
+                        if let Some(id) = item.get("id").and_then(Value::as_u64) {
+                            if (id as usize) < results.len() {
+                                results[id as usize] = item.get("result").cloned();
+                            }
+                        }
+                    }
+                }
+                other => println!("unexpected batch response: {:?}", other),
+            }
+        }
+        results
+    }
+
+    // Fetches many storage slots of `address` in a single batched RPC call,
+    // serving cached slots for free and warming `slot_cache` with the rest.
+    pub fn get_contract_slots(
+        &mut self,
+        address: EVMAddress,
+        slots: &[EVMU256],
+    ) -> HashMap<EVMU256, EVMU256> {
+        let mut result = HashMap::new();
+        let mut to_fetch = vec![];
+        for &slot in slots {
+            if let Some(v) = self.slot_cache.get(&(address, slot)) {
+                result.insert(slot, *v);
+            } else {
+                to_fetch.push(slot);
+            }
+        }
+        if to_fetch.is_empty() {
+            return result;
+        }
+
+        let calls = to_fetch
+            .iter()
+            .map(|slot| {
+                (
+                    "eth_getStorageAt".to_string(),
+                    format!("[\"0x{:x}\",\"0x{:x}\",\"{}\"]", address, slot, self.block_number),
+                )
+            })
+            .collect();
+        for (slot, resp) in to_fetch.iter().zip(self.batch_request(calls)) {
+            let value = parse_hex_u256(resp);
+            self.slot_cache.insert((address, *slot), value);
+            result.insert(*slot, value);
+        }
+        result
+    }
+
+    // Warms `code_cache` and `slot_cache` for `address` in one batched call
+    // instead of one RPC round-trip per slot.
+    pub fn prefetch(&mut self, address: EVMAddress, slots: &[EVMU256]) {
+        let mut calls = vec![(
+            "eth_getCode".to_string(),
+            format!("[\"0x{:x}\",\"{}\"]", address, self.block_number),
+        )];
+        calls.extend(slots.iter().map(|slot| {
+            (
+                "eth_getStorageAt".to_string(),
+                format!("[\"0x{:x}\",\"0x{:x}\",\"{}\"]", address, slot, self.block_number),
+            )
+        }));
+
+        let mut responses = self.batch_request(calls).into_iter();
+        if let Some(code_resp) = responses.next() {
+            let code_hex = code_resp
+                .and_then(|v| v.as_str().map(|s| s.trim_start_matches("0x").to_string()))
+                .unwrap_or_default();
+            let bytecode = if code_hex.is_empty() {
+                Bytecode::new()
+            } else {
+                to_analysed(Bytecode::new_raw(Bytes::from(
+                    hex::decode(code_hex).unwrap_or_default(),
+                )))
+            };
+            self.code_cache.insert(address, bytecode);
+        }
+        for (slot, resp) in slots.iter().zip(responses) {
+            self.slot_cache.insert((address, *slot), parse_hex_u256(resp));
+        }
+    }
+
     pub fn get_contract_code(&mut self, address: EVMAddress, force_cache: bool) -> Bytecode {
         if self.code_cache.contains_key(&address) {
             return self.code_cache[&address].clone();
@@ -597,11 +1116,12 @@ impl OnChainConfig {
             return Bytecode::default();
         }
 
-        println!("fetching code from {}", hex::encode(address));
+        let target = self.resolve_implementation(address).unwrap_or(address);
+        println!("fetching code from {}", hex::encode(target));
 
         let resp_string = {
             let mut params = String::from("[");
-            params.push_str(&format!("\"0x{:x}\",", address));
+            params.push_str(&format!("\"0x{:x}\",", target));
             params.push_str(&format!("\"{}\"", self.block_number));
             params.push(']');
             let resp = self._request("eth_getCode".to_string(), params);
@@ -665,9 +1185,126 @@ impl OnChainConfig {
     }
 }
 
+// function selectors used to probe an AMM pair / ERC20 token for pricing purposes
+const SELECTOR_GET_RESERVES: &str = "0902f1ac";
+const SELECTOR_DECIMALS: &str = "313ce567";
+
 impl OnChainConfig {
-    fn fetch_token_price_uncached(&self, _token_address: EVMAddress) -> Option<(u32, u32)> {
-        panic!("not implemented");
+    // (base token address, base token decimals), ordered by preference (most liquid first)
+    fn known_base_tokens(&self) -> Vec<(EVMAddress, u32)> {
+        macro_rules! addr {
+            ($s: expr) => {
+                EVMAddress::from_str($s).unwrap()
+            };
+        }
+        match self.chain_name.as_str() {
+            "eth" => vec![
+                (addr!("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"), 6), // USDC
+                (addr!("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"), 18), // WETH
+                (addr!("0xdac17f958d2ee523a2206206994597c13d831ec7"), 6), // USDT
+            ],
+            "bsc" => vec![
+                (addr!("0xe9e7cea3dedca5984780bafc599bd69add087d56"), 18), // BUSD
+                (addr!("0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c"), 18), // WBNB
+                (addr!("0x55d398326f99059ff775485246999027b3197955"), 18), // USDT
+            ],
+            _ => vec![],
+        }
+    }
+
+    fn uniswap_info_for_chain(&self) -> Option<UniswapInfo> {
+        let chain = Chain::from_str(&self.chain_name)?;
+        let provider = match chain {
+            Chain::ETH => UniswapProvider::UniswapV2,
+            Chain::BSC => UniswapProvider::PancakeSwap,
+            _ => return None,
+        };
+        Some(get_uniswap_info(&provider, &chain))
+    }
+
+    fn eth_call(&self, to: EVMAddress, selector: &str) -> Option<Vec<u8>> {
+        let params = format!(
+            "[{{\"to\":\"0x{:x}\",\"data\":\"0x{}\"}},\"{}\"]",
+            to, selector, self.block_number
+        );
+        let resp = self._request("eth_call".to_string(), params)?;
+        let data = resp.as_str()?.trim_start_matches("0x");
+        if data.is_empty() {
+            return None;
+        }
+        hex::decode(data).ok()
+    }
+
+    fn fetch_reserves(&self, pair: EVMAddress) -> Option<(EVMU256, EVMU256)> {
+        let ret = self.eth_call(pair, SELECTOR_GET_RESERVES)?;
+        if ret.len() < 64 {
+            return None;
+        }
+        let reserve0 = EVMU256::try_from_be_slice(&ret[0..32])?;
+        let reserve1 = EVMU256::try_from_be_slice(&ret[32..64])?;
+        if reserve0 == EVMU256::ZERO && reserve1 == EVMU256::ZERO {
+            return None;
+        }
+        Some((reserve0, reserve1))
+    }
+
+    fn fetch_decimals(&self, token: EVMAddress) -> u32 {
+        match self.eth_call(token, SELECTOR_DECIMALS) {
+            Some(ret) if ret.len() >= 32 => {
+                EVMU256::try_from_be_slice(&ret[ret.len() - 32..])
+                    .map(|v| v.as_limbs()[0] as u32)
+                    .unwrap_or(18)
+            }
+            _ => 18,
+        }
+    }
+
+    // Resolves a token's USD-ish price by finding the highest-liquidity pair between
+    // `token_address` and a known base/stable asset on the configured chain, reading
+    // `getReserves()` off that pair, and normalizing to the `(price x 10^5, decimals)`
+    // contract `PriceOracle` documents.
+    fn fetch_token_price_uncached(&self, token_address: EVMAddress) -> Option<(u32, u32)> {
+        let uniswap_info = self.uniswap_info_for_chain()?;
+        let token_decimals = self.fetch_decimals(token_address);
+
+        let mut best: Option<(EVMU256, EVMU256, u32)> = None; // (reserve_base, reserve_token, base_decimals)
+        for (base_token, base_decimals) in self.known_base_tokens() {
+            if base_token == token_address {
+                // the token itself is a base asset: price is 1
+                return Some((100_000, base_decimals));
+            }
+            let pair = uniswap_info.get_pair_address(token_address, base_token);
+            let (reserve0, reserve1) = match self.fetch_reserves(pair) {
+                Some(reserves) => reserves,
+                None => continue,
+            };
+            let (reserve_token, reserve_base) = if token_address < base_token {
+                (reserve0, reserve1)
+            } else {
+                (reserve1, reserve0)
+            };
+            if reserve_token == EVMU256::ZERO {
+                continue;
+            }
+            let is_better = match &best {
+                None => true,
+                Some((best_reserve_base, _, _)) => reserve_base > *best_reserve_base,
+            };
+            if is_better {
+                best = Some((reserve_base, reserve_token, base_decimals));
+            }
+        }
+
+        let (reserve_base, reserve_token, base_decimals) = best?;
+        // price (in base units) = reserve_base / reserve_token, normalized to int(price x 10^5)
+        // adjusted for the decimals difference between the two tokens.
+        let scale = EVMU256::from(100_000u64) * EVMU256::from(10u64).pow(EVMU256::from(token_decimals));
+        let denom = EVMU256::from(10u64).pow(EVMU256::from(base_decimals)) * reserve_token;
+        if denom == EVMU256::ZERO {
+            return None;
+        }
+        let price = reserve_base * scale / denom;
+        Some((price.as_limbs()[0] as u32, token_decimals))
     }
 }
 
@@ -682,29 +1319,102 @@ impl PriceOracle for OnChainConfig {
     }
 }
 
-fn get_header() -> HeaderMap {
-    let mut headers = HeaderMap::new();
-    headers.insert("authority", "etherscan.io".parse().unwrap());
-    headers.insert("accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.9".parse().unwrap());
-    headers.insert(
-        "accept-language",
-        "zh-CN,zh;q=0.9,en;q=0.8".parse().unwrap(),
-    );
-    headers.insert("cache-control", "max-age=0".parse().unwrap());
-    headers.insert(
-        "sec-ch-ua",
-        "\"Not?A_Brand\";v=\"8\", \"Chromium\";v=\"108\", \"Google Chrome\";v=\"108\""
-            .parse()
-            .unwrap(),
-    );
-    headers.insert("sec-ch-ua-mobile", "?0".parse().unwrap());
-    headers.insert("sec-ch-ua-platform", "\"macOS\"".parse().unwrap());
-    headers.insert("sec-fetch-dest", "document".parse().unwrap());
-    headers.insert("sec-fetch-mode", "navigate".parse().unwrap());
-    headers.insert("sec-fetch-site", "none".parse().unwrap());
-    headers.insert("sec-fetch-user", "?1".parse().unwrap());
-    headers.insert("upgrade-insecure-requests", "1".parse().unwrap());
-    headers.insert("user-agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36".parse().unwrap());
-    headers.insert("Content-Type", "application/json".parse().unwrap());
-    headers
+// Parses `body` as JSON (JSON-RPC or Etherscan-style) and only treats it as
+// cacheable when it carries a non-null `result` and no `error` member. This
+// replaces a naive `!body.contains("error")` check, which corrupts the cache
+// whenever a legitimate result string happens to contain the word "error".
+fn is_well_formed_response(body: &str) -> bool {
+    match serde_json::from_str::<Value>(body) {
+        Ok(json) => {
+            let has_error = json.get("error").map_or(false, |e| !e.is_null());
+            let has_result = json.get("result").map_or(false, |r| !r.is_null());
+            has_result && !has_error
+        }
+        Err(_) => false,
+    }
 }
+
+fn parse_hex_u256(resp: Option<Value>) -> EVMU256 {
+    match resp.as_ref().and_then(Value::as_str) {
+        Some(s) if !s.trim_start_matches("0x").is_empty() => {
+            EVMU256::from_str_radix(s.trim_start_matches("0x"), 16).unwrap_or(EVMU256::ZERO)
+        }
+        _ => EVMU256::ZERO,
+    }
+}
+
+// Builds the `HeaderMap` sent with every RPC/Etherscan request, starting
+// from sensible (browser-mimicking) defaults and layering on user-supplied
+// overrides and API keys pulled from the environment. Header names/values
+// are validated through `http::HeaderName`/`HeaderValue` so a malformed
+// override is reported as a `Result`, instead of panicking via `.parse().unwrap()`.
+pub struct RequestHeaders {
+    headers: HeaderMap,
+}
+
+impl RequestHeaders {
+    pub fn defaults() -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert("authority", HeaderValue::from_static("etherscan.io"));
+        headers.insert("accept", HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.9"));
+        headers.insert(
+            "accept-language",
+            HeaderValue::from_static("zh-CN,zh;q=0.9,en;q=0.8"),
+        );
+        headers.insert("cache-control", HeaderValue::from_static("max-age=0"));
+        headers.insert(
+            "sec-ch-ua",
+            HeaderValue::from_static(
+                "\"Not?A_Brand\";v=\"8\", \"Chromium\";v=\"108\", \"Google Chrome\";v=\"108\"",
+            ),
+        );
+        headers.insert("sec-ch-ua-mobile", HeaderValue::from_static("?0"));
+        headers.insert("sec-ch-ua-platform", HeaderValue::from_static("\"macOS\""));
+        headers.insert("sec-fetch-dest", HeaderValue::from_static("document"));
+        headers.insert("sec-fetch-mode", HeaderValue::from_static("navigate"));
+        headers.insert("sec-fetch-site", HeaderValue::from_static("none"));
+        headers.insert("sec-fetch-user", HeaderValue::from_static("?1"));
+        headers.insert(
+            "upgrade-insecure-requests",
+            HeaderValue::from_static("1"),
+        );
+        headers.insert("user-agent", HeaderValue::from_static("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/108.0.0.0 Safari/537.36"));
+        headers.insert(
+            "Content-Type",
+            HeaderValue::from_static("application/json"),
+        );
+        Self { headers }
+    }
+
+    /// Merges user-supplied `(name, value)` overrides from config/CLI on top
+    /// of the defaults.
+    pub fn with_overrides(mut self, overrides: &[(String, String)]) -> Result<Self, String> {
+        for (name, value) in overrides {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| format!("invalid header name {}: {}", name, e))?;
+            let header_value = HeaderValue::from_str(value)
+                .map_err(|e| format!("invalid header value for {}: {}", name, e))?;
+            self.headers.insert(header_name, header_value);
+        }
+        Ok(self)
+    }
+
+    /// Injects a secret (e.g. an Etherscan API key) read from `env_var` into
+    /// `header_name`, leaving the headers untouched if the variable isn't set.
+    pub fn with_env_secret(mut self, header_name: &str, env_var: &str) -> Result<Self, String> {
+        let Ok(secret) = std::env::var(env_var) else {
+            return Ok(self);
+        };
+        let header_name = HeaderName::from_bytes(header_name.as_bytes())
+            .map_err(|e| format!("invalid header name {}: {}", header_name, e))?;
+        let header_value = HeaderValue::from_str(&secret)
+            .map_err(|e| format!("invalid value in env var {}: {}", env_var, e))?;
+        self.headers.insert(header_name, header_value);
+        Ok(self)
+    }
+
+    pub fn build(self) -> HeaderMap {
+        self.headers
+    }
+}
+
@@ -0,0 +1,146 @@
+use crate::evm::input::{ConciseEVMInput, EVMInput};
+use crate::evm::oracle::EVMBugResult;
+use crate::evm::types::{EVMAddress, EVMFuzzState, EVMOracleCtx};
+use crate::evm::vm::EVMState;
+use crate::oracle::{Oracle, OracleCtx};
+use crate::state::HasExecutionResult;
+use bytes::Bytes;
+use revm_primitives::Bytecode;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Flags a transaction whose gas consumption (`EVMState::last_gas_used`,
+/// `last_gas_per_repeat`) looks like an unbounded-loop DoS or a
+/// gas-griefing setup rather than ordinary execution: either it blew past
+/// an absolute ceiling, or - for inputs with `get_repeat() > 1` - each
+/// repeat cost strictly more than the last, i.e. the call gets more
+/// expensive the more it's invoked (a storage-filling loop, an
+/// ever-growing array push, ...) instead of costing the same each time.
+pub struct GasUsageOracle {
+    /// Absolute ceiling above which a single transaction's `last_gas_used`
+    /// is considered suspicious on its own, regardless of growth. Callers
+    /// size this relative to the block gas limit they're fuzzing against.
+    pub gas_threshold: u64,
+}
+
+impl GasUsageOracle {
+    pub fn new(gas_threshold: u64) -> Self {
+        Self { gas_threshold }
+    }
+}
+
+impl Oracle<EVMState, EVMAddress, Bytecode, Bytes, Vec<u8>, EVMInput, EVMFuzzState, ConciseEVMInput>
+    for GasUsageOracle
+{
+    fn transition(&self, _ctx: &mut EVMOracleCtx<'_>, _stage: u64) -> u64 {
+        0
+    }
+
+    fn oracle(
+        &self,
+        ctx: &mut OracleCtx<
+            EVMState,
+            EVMAddress,
+            Bytecode,
+            Bytes,
+            Vec<u8>,
+            EVMInput,
+            EVMFuzzState,
+            ConciseEVMInput,
+        >,
+        _stage: u64,
+    ) -> Vec<u64> {
+        let gas_used = ctx.post_state.last_gas_used;
+        let per_repeat = &ctx.post_state.last_gas_per_repeat;
+
+        let over_threshold = gas_used > self.gas_threshold;
+        let superlinear = per_repeat.len() > 1 && per_repeat.windows(2).all(|w| w[1] > w[0]);
+
+        if !over_threshold && !superlinear {
+            return vec![];
+        }
+
+        let mut hasher = DefaultHasher::new();
+        ctx.input.get_contract().hash(&mut hasher);
+        "gas_usage".hash(&mut hasher);
+        let bug_idx = (hasher.finish() as u64) << 8;
+
+        let reason = match (over_threshold, superlinear) {
+            (true, true) => format!(
+                "gas used {} exceeds threshold {} and grows every repeat {:?}",
+                gas_used, self.gas_threshold, per_repeat
+            ),
+            (true, false) => format!("gas used {} exceeds threshold {}", gas_used, self.gas_threshold),
+            (false, true) => format!("gas used grows every repeat {:?}", per_repeat),
+            (false, false) => unreachable!(),
+        };
+
+        EVMBugResult::new(
+            "gas_usage".to_string(),
+            bug_idx,
+            reason,
+            ConciseEVMInput::from_input(ctx.input, ctx.fuzz_state.get_execution_result()),
+            None,
+            None,
+        )
+        .push_to_output();
+        vec![bug_idx]
+    }
+}
+
+/// Flags a `CALL` that forwarded less than 63/64 of its available gas (the
+/// EIP-150 stipend rule) to a callee that then ran out of gas -
+/// `FuzzHost::call` records these into `EVMState::underfunded_calls` the
+/// same way it tracks `arbitrary_calls`. A contract relying on a fixed
+/// forwarded-gas amount (rather than `gasleft()`) for e.g. a `.transfer()`-
+/// style payout is griefable by a caller (or a gas-price/opcode-repricing
+/// change) that leaves it just under what the callee needs.
+pub struct GasGriefingOracle;
+
+impl Oracle<EVMState, EVMAddress, Bytecode, Bytes, Vec<u8>, EVMInput, EVMFuzzState, ConciseEVMInput>
+    for GasGriefingOracle
+{
+    fn transition(&self, _ctx: &mut EVMOracleCtx<'_>, _stage: u64) -> u64 {
+        0
+    }
+
+    fn oracle(
+        &self,
+        ctx: &mut OracleCtx<
+            EVMState,
+            EVMAddress,
+            Bytecode,
+            Bytes,
+            Vec<u8>,
+            EVMInput,
+            EVMFuzzState,
+            ConciseEVMInput,
+        >,
+        _stage: u64,
+    ) -> Vec<u64> {
+        ctx.post_state
+            .underfunded_calls
+            .iter()
+            .map(|(caller, callee, pc)| {
+                let mut hasher = DefaultHasher::new();
+                caller.hash(&mut hasher);
+                callee.hash(&mut hasher);
+                pc.hash(&mut hasher);
+                let bug_idx = (hasher.finish() as u64) << 8;
+                EVMBugResult::new(
+                    "gas_griefing".to_string(),
+                    bug_idx,
+                    format!(
+                        "{:?} forwarded < 63/64 gas to {:?} at pc {}, callee ran out of gas",
+                        caller, callee, pc
+                    ),
+                    ConciseEVMInput::from_input(ctx.input, ctx.fuzz_state.get_execution_result()),
+                    None,
+                    None,
+                )
+                .push_to_output();
+                bug_idx
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,189 @@
+use bytes::Bytes;
+extern crate crypto;
+use self::crypto::digest::Digest;
+use self::crypto::ripemd160::Ripemd160;
+use self::crypto::sha2::Sha256;
+
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+
+use crate::evm::types::{fixed_address, EVMAddress};
+
+/// Flat gas charge [`execute`] used to bill for any precompile it models,
+/// kept only as the fallback [`gas_cost`] uses for an `id` it doesn't know a
+/// real schedule for.
+pub const BASE_GAS_COST: u64 = 700;
+
+/// The real mainnet gas schedule for the precompiles [`execute`] models, so
+/// a direct call into one (see [`execute`]'s doc comment) bills the same as
+/// going through revm's own precompile table would, instead of the flat
+/// [`BASE_GAS_COST`] every `id` used to pay regardless of input size.
+pub fn gas_cost(id: u8, input_len: usize) -> u64 {
+    let words = ((input_len + 31) / 32) as u64;
+    match id {
+        1 => 3000,
+        2 => 60 + 12 * words,
+        3 => 600 + 120 * words,
+        4 => 15 + 3 * words,
+        _ => BASE_GAS_COST,
+    }
+}
+
+/// The standard precompile address range (`0x01`-`0x09`), as a 1-indexed id.
+pub fn match_precompile(address: &EVMAddress) -> Option<u8> {
+    for id in 1..=9u8 {
+        if *address == fixed_address(&format!("{:040x}", id)) {
+            return Some(id);
+        }
+    }
+    None
+}
+
+/// Result of concretely running one of the precompiles this module models,
+/// mirroring `EVMState::arbitrary_calls`'s "was this forged" bookkeeping:
+/// `forged` is set when `execute`'s caller asked for (and got) a
+/// fuzz-controlled `ecrecover` result rather than the real recovery.
+pub struct PrecompileOutput {
+    pub data: Bytes,
+    pub forged: bool,
+}
+
+/// Concretely executes the precompile at `id` (as returned by
+/// [`match_precompile`]) against `input`, for call targets `execute_from_pc`
+/// dispatches directly rather than through revm's own interpreter loop
+/// (e.g. [`EVMCorpusInitializer::seed_ecrecover_guard`](crate::evm::corpus_initializer::EVMCorpusInitializer::seed_ecrecover_guard)'s
+/// direct call into `ecrecover`). Nested `CALL`s a contract makes
+/// internally already reach revm's own built-in precompile table, so this
+/// isn't a general-purpose precompile reimplementation - just the pieces
+/// needed where the fuzzer bypasses that path, plus the `ecrecover`
+/// forging hook.
+///
+/// `forced_recovery` is an attacker-controlled address (normally drawn from
+/// the calling input's `HasCaller`-seeded caller) to substitute as the
+/// `ecrecover` result instead of doing the real recovery, so
+/// `require(ecrecover(...) == owner)`-style guards become reachable without
+/// needing a valid signature over the exact hash being checked. Only
+/// applies to `id == 1`; ignored otherwise.
+///
+/// Returns `None` for `id`s this module doesn't model concretely (modexp,
+/// the alt_bn128 operations, and blake2f - none of which this tree has a
+/// verified pairing/bignum crate for), so callers can fall back to
+/// whatever they'd otherwise do for an unhandled address.
+pub fn execute(id: u8, input: &[u8], forced_recovery: Option<EVMAddress>) -> Option<PrecompileOutput> {
+    match id {
+        1 => Some(ecrecover(input, forced_recovery)),
+        2 => Some(PrecompileOutput {
+            data: Bytes::from(sha256(input)),
+            forged: false,
+        }),
+        3 => Some(PrecompileOutput {
+            data: Bytes::from(ripemd160(input)),
+            forged: false,
+        }),
+        4 => Some(PrecompileOutput {
+            data: Bytes::copy_from_slice(input),
+            forged: false,
+        }),
+        _ => None,
+    }
+}
+
+/// `ecrecover(hash, v, r, s) -> address`, encoded as the precompile expects:
+/// 128 bytes in (`hash32 || v32 || r32 || s32`), 32 bytes out (the recovered
+/// address, left-padded with zeros). A malformed input - wrong length, `v`
+/// not `27`/`28`, or a signature that doesn't recover - yields empty output,
+/// matching the real precompile's behavior on invalid input.
+fn ecrecover(input: &[u8], forced_recovery: Option<EVMAddress>) -> PrecompileOutput {
+    if let Some(address) = forced_recovery {
+        return PrecompileOutput {
+            data: pad_address(&address),
+            forged: true,
+        };
+    }
+
+    if input.len() != 128 {
+        return PrecompileOutput {
+            data: Bytes::new(),
+            forged: false,
+        };
+    }
+
+    let hash = &input[0..32];
+    let v = input[63];
+    let r = &input[64..96];
+    let s = &input[96..128];
+
+    // the 31 padding bytes ahead of `v` must be zero, and `v` must be the
+    // Ethereum-convention 27/28
+    if input[32..63].iter().any(|b| *b != 0) || (v != 27 && v != 28) {
+        return PrecompileOutput {
+            data: Bytes::new(),
+            forged: false,
+        };
+    }
+
+    let Ok(recovery_id) = RecoveryId::from_i32((v - 27) as i32) else {
+        return PrecompileOutput {
+            data: Bytes::new(),
+            forged: false,
+        };
+    };
+
+    let mut sig_bytes = [0u8; 64];
+    sig_bytes[..32].copy_from_slice(r);
+    sig_bytes[32..].copy_from_slice(s);
+
+    let (Ok(message), Ok(signature)) = (
+        Message::from_slice(hash),
+        RecoverableSignature::from_compact(&sig_bytes, recovery_id),
+    ) else {
+        return PrecompileOutput {
+            data: Bytes::new(),
+            forged: false,
+        };
+    };
+
+    let secp = Secp256k1::new();
+    let Ok(public) = secp.recover_ecdsa(&message, &signature) else {
+        return PrecompileOutput {
+            data: Bytes::new(),
+            forged: false,
+        };
+    };
+
+    let uncompressed = public.serialize_uncompressed();
+    let mut digest = [0u8; 32];
+    let mut hasher = crypto::sha3::Sha3::keccak256();
+    hasher.input(&uncompressed[1..]);
+    hasher.result(&mut digest);
+
+    PrecompileOutput {
+        data: pad_address(&EVMAddress::from_slice(&digest[12..])),
+        forged: false,
+    }
+}
+
+fn pad_address(address: &EVMAddress) -> Bytes {
+    let mut out = [0u8; 32];
+    out[12..].copy_from_slice(&address.0);
+    Bytes::copy_from_slice(&out)
+}
+
+fn sha256(input: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input(input);
+    let mut out = vec![0u8; 32];
+    hasher.result(&mut out);
+    out
+}
+
+fn ripemd160(input: &[u8]) -> Vec<u8> {
+    let mut hasher = Ripemd160::new();
+    hasher.input(input);
+    // the hash is only 20 bytes; the precompile left-pads it to a full word
+    let mut digest = [0u8; 20];
+    hasher.result(&mut digest);
+    let mut out = vec![0u8; 32];
+    out[12..].copy_from_slice(&digest);
+    out
+}
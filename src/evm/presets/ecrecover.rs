@@ -0,0 +1,72 @@
+use crate::evm::types::EVMAddress;
+
+extern crate crypto;
+use self::crypto::digest::Digest;
+use self::crypto::sha3::Sha3;
+
+use secp256k1::ecdsa::RecoverableSignature;
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+/// A secp256k1 keypair the fuzzer fully controls, used to forge valid
+/// signatures against `require(ecrecover(...) == owner)`-style guards
+/// (see `contract_utils::uses_ecrecover_precompile`) that a randomly
+/// mutated 65-byte signature would otherwise never satisfy.
+pub struct ControlledKeypair {
+    secret: SecretKey,
+    pub address: EVMAddress,
+}
+
+impl ControlledKeypair {
+    /// Derives a keypair from a small integer seed, so the same seed always
+    /// controls the same address across runs - the address just needs to be
+    /// known ahead of time so it can be registered as a caller.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&seed.to_be_bytes());
+        // any nonzero seed below the curve order is a valid secp256k1 key
+        let secret = SecretKey::from_slice(&bytes).expect("seed produced an invalid secret key");
+        let address = Self::derive_address(&secret);
+        Self { secret, address }
+    }
+
+    fn derive_address(secret: &SecretKey) -> EVMAddress {
+        let secp = Secp256k1::new();
+        let public = PublicKey::from_secret_key(&secp, secret);
+        // drop the 0x04 uncompressed-point prefix, keccak the remaining 64
+        // bytes, and keep the low 20 - the standard EVM address derivation
+        let uncompressed = public.serialize_uncompressed();
+        let mut hash = [0u8; 32];
+        let mut hasher = Sha3::keccak256();
+        hasher.input(&uncompressed[1..]);
+        hasher.result(&mut hash);
+        EVMAddress::from_slice(&hash[12..])
+    }
+
+    /// Signs `hash` and returns the `(v, r, s)` triple `ecrecover` expects,
+    /// with `v` already offset by 27 per Ethereum's convention.
+    pub fn sign(&self, hash: [u8; 32]) -> (u8, [u8; 32], [u8; 32]) {
+        let secp = Secp256k1::new();
+        let message = Message::from_slice(&hash).expect("hash is not 32 bytes");
+        let recoverable: RecoverableSignature = secp.sign_ecdsa_recoverable(&message, &self.secret);
+        let (recovery_id, sig) = recoverable.serialize_compact();
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&sig[..32]);
+        s.copy_from_slice(&sig[32..]);
+        (27 + recovery_id.to_i32() as u8, r, s)
+    }
+
+    /// Builds the 128-byte calldata the `ecrecover` precompile (address
+    /// `0x1`) expects: `hash32 || v32 || r32 || s32`, `v` left-padded to a
+    /// full word like every other precompile argument.
+    pub fn ecrecover_calldata(&self, hash: [u8; 32]) -> Vec<u8> {
+        let (v, r, s) = self.sign(hash);
+        let mut calldata = Vec::with_capacity(128);
+        calldata.extend_from_slice(&hash);
+        calldata.extend_from_slice(&[0u8; 31]);
+        calldata.push(v);
+        calldata.extend_from_slice(&r);
+        calldata.extend_from_slice(&s);
+        calldata
+    }
+}
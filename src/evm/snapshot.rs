@@ -0,0 +1,138 @@
+/// Portable snapshot/replay format for a deployed environment:
+/// `EVMExecutor::deploy` builds up `host.code` entries and
+/// `bytecode_analyzer` results as a campaign runs, but none of that was
+/// ever serializable on its own, so there was no way to persist a
+/// reproducible environment and hand it to someone else (or warm-start a
+/// new campaign from it) without replaying every deploy transaction.
+///
+/// [`CampaignSnapshot::encode`]/[`decode`] support two tiers the same way
+/// a corpus-on-disk format usually needs to: a compact `bincode` form for
+/// fast local storage, and `CBOR`/`JSON` forms for cross-tool or
+/// human-inspectable dumps.
+use revm_primitives::Bytecode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::evm::{types::EVMAddress, vm::EVMState};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeployedContractSnapshot {
+    pub address: EVMAddress,
+    /// The contract's post-constructor runtime bytecode (what the
+    /// constructor's init code returned at deploy time), *not* the init
+    /// code itself - restoring re-runs `on_insert` over this directly
+    /// rather than re-executing a constructor or trying to serialize the
+    /// already-analyzed `Bytecode`.
+    pub code: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CampaignSnapshot {
+    pub contracts: Vec<DeployedContractSnapshot>,
+    pub state: EVMState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// Compact binary encoding for fast on-disk corpus storage.
+    Bincode,
+    /// Structured, cross-tool-readable binary encoding.
+    Cbor,
+    /// Human-inspectable text encoding, for diffing/sharing crash cases.
+    Json,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Encode(String),
+    Decode(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Encode(e) => write!(f, "failed to encode snapshot: {e}"),
+            SnapshotError::Decode(e) => write!(f, "failed to decode snapshot: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl CampaignSnapshot {
+    pub fn encode(&self, format: SnapshotFormat) -> Result<Vec<u8>, SnapshotError> {
+        match format {
+            SnapshotFormat::Bincode => {
+                bincode::serialize(self).map_err(|e| SnapshotError::Encode(e.to_string()))
+            }
+            SnapshotFormat::Cbor => {
+                let mut out = Vec::new();
+                ciborium::ser::into_writer(self, &mut out)
+                    .map_err(|e| SnapshotError::Encode(e.to_string()))?;
+                Ok(out)
+            }
+            SnapshotFormat::Json => {
+                serde_json::to_vec_pretty(self).map_err(|e| SnapshotError::Encode(e.to_string()))
+            }
+        }
+    }
+
+    pub fn decode(bytes: &[u8], format: SnapshotFormat) -> Result<Self, SnapshotError> {
+        match format {
+            SnapshotFormat::Bincode => {
+                bincode::deserialize(bytes).map_err(|e| SnapshotError::Decode(e.to_string()))
+            }
+            SnapshotFormat::Cbor => ciborium::de::from_reader(bytes)
+                .map_err(|e| SnapshotError::Decode(e.to_string())),
+            SnapshotFormat::Json => {
+                serde_json::from_slice(bytes).map_err(|e| SnapshotError::Decode(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Builds a snapshot entry straight from a `Bytecode`, for call sites that
+/// only have the analyzed form in hand rather than the raw bytes
+/// `EVMExecutor::deployed_bytecode` tracks.
+impl From<(EVMAddress, &Bytecode)> for DeployedContractSnapshot {
+    fn from((address, code): (EVMAddress, &Bytecode)) -> Self {
+        Self {
+            address,
+            code: code.bytes().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm::types::fixed_address;
+
+    /// `CampaignSnapshot::encode`/`decode` must round-trip each
+    /// `DeployedContractSnapshot`'s `address` and `code` through every
+    /// format - `code` being the post-constructor runtime bytecode
+    /// `restore()` re-runs `on_insert` over directly, it must come back
+    /// byte-for-byte identical. `state` is not compared here.
+    #[test]
+    fn campaign_snapshot_round_trips_through_every_format() {
+        let snapshot = CampaignSnapshot {
+            contracts: vec![DeployedContractSnapshot {
+                address: fixed_address("0000000000000000000000000000000000001234"),
+                code: vec![0x60, 0x80, 0x60, 0x40, 0x52, 0x00],
+            }],
+            state: EVMState::default(),
+        };
+
+        for format in [
+            SnapshotFormat::Bincode,
+            SnapshotFormat::Cbor,
+            SnapshotFormat::Json,
+        ] {
+            let encoded = snapshot.encode(format).expect("encode should succeed");
+            let decoded = CampaignSnapshot::decode(&encoded, format).expect("decode should succeed");
+            assert_eq!(decoded.contracts.len(), snapshot.contracts.len());
+            assert_eq!(decoded.contracts[0].address, snapshot.contracts[0].address);
+            assert_eq!(decoded.contracts[0].code, snapshot.contracts[0].code);
+        }
+    }
+}
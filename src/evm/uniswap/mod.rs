@@ -5,7 +5,6 @@ use crate::evm::onchain::endpoints::Chain;
 use crypto::digest::Digest;
 use crypto::sha3::Sha3;
 
-use permutator::CartesianProductIterator;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
@@ -15,12 +14,19 @@ use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::Arc;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum UniswapVer {
     V1,
     V2,
     V3,
 }
 
+impl Default for UniswapVer {
+    fn default() -> Self {
+        UniswapVer::V2
+    }
+}
+
 pub fn is_uniswap() -> Option<UniswapVer> {
     None
 }
@@ -54,6 +60,16 @@ pub struct UniswapInfo {
     pub router: EVMAddress,
     pub factory: EVMAddress,
     pub init_code_hash: Vec<u8>,
+    /// Which swap math [`PairContext::get_amount_out`] uses for pairs built
+    /// from this info - `V2`'s constant-product curve, or `V3`'s
+    /// concentrated-liquidity single-tick formulas (see
+    /// [`UniswapInfo::calculate_amounts_out_v3`]). `get_amount_in` uses the
+    /// `V2` formula for either version.
+    pub version: UniswapVer,
+    /// V3 fee tier in hundredths of a bip (e.g. `500`/`3000`/`10000`),
+    /// unlike `pool_fee` which is in basis points out of `10000` for the V2
+    /// constant-product formula. Unused when `version` is `V2`.
+    pub fee_pips: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -69,6 +85,11 @@ pub struct PairContext {
     pub next_hop: EVMAddress,
     pub side: u8,
     pub uniswap_info: Arc<UniswapInfo>,
+    /// `(reserve0, reserve1)` for a V2 pair. For a V3 pair (`uniswap_info.version
+    /// == UniswapVer::V3`) this instead holds `(sqrtPriceX96, liquidity)` -
+    /// reused rather than given its own field since both are "the two
+    /// numbers needed to price the next swap" and every call site already
+    /// threads this tuple through opaquely via `reserve_data`.
     pub initial_reserves: (EVMU256, EVMU256),
 }
 
@@ -127,12 +148,25 @@ impl PairContext {
         reserve0: EVMU256,
         reserve1: EVMU256,
     ) -> SwapResult {
+        let amount_in = if amount_in > EVMU256::from(u128::MAX) {
+            EVMU256::from(u128::MAX)
+        } else {
+            amount_in
+        };
+        if self.uniswap_info.version == UniswapVer::V3 {
+            // `reserve0`/`reserve1` are really `(sqrtPriceX96, liquidity)`
+            // for a V3 pair - see the field doc on
+            // `PairContext::initial_reserves` for why the same tuple slot
+            // is reused rather than adding a separate field.
+            return self.uniswap_info.calculate_amounts_out_v3(
+                amount_in,
+                reserve0,
+                reserve1,
+                self.side == 0,
+            );
+        }
         self.uniswap_info.calculate_amounts_out(
-            if amount_in > EVMU256::from(u128::MAX) {
-                EVMU256::from(u128::MAX)
-            } else {
-                amount_in
-            },
+            amount_in,
             if self.side == 0 { reserve0 } else { reserve1 },
             if self.side == 0 { reserve1 } else { reserve0 },
         )
@@ -144,6 +178,11 @@ impl PairContext {
         reserve0: EVMU256,
         reserve1: EVMU256,
     ) -> SwapResult {
+        // Exact-output V3 quoting needs the same tick-crossing machinery as
+        // exact-input and isn't modeled here (see
+        // `UniswapInfo::calculate_amounts_out_v3`); falling back to the V2
+        // constant-product formula gives liquidation search a usable,
+        // if approximate, quote instead of panicking.
         self.uniswap_info.calculate_amounts_in(
             if amount_out > EVMU256::from(u128::MAX) {
                 EVMU256::from(u128::MAX)
@@ -346,55 +385,99 @@ pub fn generate_uniswap_router_call(
     }
 }
 
+/// Replays `chosen[i]`'s path for `tokens[i]` in order against
+/// `initial_reserve_data`, threading reserve depletion across hops that
+/// share a pair the same way the brute-force Cartesian search did, and
+/// returns the total output plus the resulting reserve state.
+fn replay_liquidation(
+    tokens: &[(&TokenContext, EVMU256)],
+    chosen: &[usize],
+    initial_reserve_data: &HashMap<EVMAddress, (EVMU256, EVMU256)>,
+) -> (EVMU256, HashMap<EVMAddress, (EVMU256, EVMU256)>) {
+    let mut reserve_data = initial_reserve_data.clone();
+    let mut total_amount_out = EVMU256::ZERO;
+    for (i, (token, amt)) in tokens.iter().enumerate() {
+        let path = &token.swaps[chosen[i]];
+        total_amount_out += path.get_amount_out(*amt, &mut reserve_data);
+    }
+    (total_amount_out, reserve_data)
+}
+
+/// Maximum number of local-search refinement passes over all tokens, so a
+/// pathological instance that never converges still terminates in bounded
+/// (rather than unbounded) time.
+const MAX_REFINEMENT_PASSES: usize = 8;
+
 pub fn liquidate_all_token(
     tokens: Vec<(&TokenContext, EVMU256)>,
     initial_reserve_data: HashMap<EVMAddress, (EVMU256, EVMU256)>,
 ) -> (EVMU256, HashMap<EVMAddress, (EVMU256, EVMU256)>) {
-    let mut swap_combos: Vec<Vec<(PathContext, EVMU256)>> = Vec::new();
-    for (token, amt) in tokens {
-        let swaps: Vec<(PathContext, EVMU256)> =
-            token.swaps.iter().map(|swap| (swap.clone(), amt)).collect();
-        if swaps.len() > 0 {
-            swap_combos.push(swaps);
-        }
-    }
+    let tokens: Vec<(&TokenContext, EVMU256)> = tokens
+        .into_iter()
+        .filter(|(token, _)| !token.swaps.is_empty())
+        .collect();
 
-    if swap_combos.len() == 0 {
+    if tokens.is_empty() {
         return (EVMU256::ZERO, initial_reserve_data);
     }
 
-    let mut possible_amount_out = vec![];
+    // Greedy construction: process tokens one at a time, picking the path
+    // that maximizes marginal output against the reserve state left behind
+    // by every token processed so far, then committing it before moving on.
+    let mut chosen: Vec<usize> = vec![0; tokens.len()];
+    let mut reserve_data = initial_reserve_data.clone();
+    for (i, (token, amt)) in tokens.iter().enumerate() {
+        let mut best_idx = 0;
+        let mut best_out = EVMU256::ZERO;
+        let mut best_reserve_data = reserve_data.clone();
+        for (j, path) in token.swaps.iter().enumerate() {
+            let mut trial_reserve_data = reserve_data.clone();
+            let out = path.get_amount_out(*amt, &mut trial_reserve_data);
+            if j == 0 || out > best_out {
+                best_idx = j;
+                best_out = out;
+                best_reserve_data = trial_reserve_data;
+            }
+        }
+        chosen[i] = best_idx;
+        reserve_data = best_reserve_data;
+    }
 
-    CartesianProductIterator::new(
-        swap_combos
-            .iter()
-            .map(|x| x.as_slice())
-            .collect::<Vec<&[(PathContext, EVMU256)]>>()
-            .as_slice(),
-    )
-    .into_iter()
-    .for_each(|swaps| {
-        let mut reserve_data = initial_reserve_data.clone();
-        let mut total_amount_out = EVMU256::ZERO;
-        for (path, amt) in &swaps {
-            total_amount_out += path.get_amount_out(amt.clone(), &mut reserve_data);
+    // Bounded local-search refinement: repeatedly try swapping each
+    // token's chosen path for an alternative, keeping the swap only if it
+    // improves total output, until a full pass finds no improvement (or
+    // the pass budget runs out). Each candidate swap is evaluated by a
+    // full replay rather than assuming independence between tokens, since
+    // two tokens routed through the same pair affect each other's
+    // reserves.
+    let (mut best_total, mut best_reserve_data) =
+        replay_liquidation(&tokens, &chosen, &initial_reserve_data);
+    for _ in 0..MAX_REFINEMENT_PASSES {
+        let mut improved = false;
+        for i in 0..tokens.len() {
+            let (token, _) = &tokens[i];
+            for alt in 0..token.swaps.len() {
+                if alt == chosen[i] {
+                    continue;
+                }
+                let mut candidate = chosen.clone();
+                candidate[i] = alt;
+                let (total, reserve_data) =
+                    replay_liquidation(&tokens, &candidate, &initial_reserve_data);
+                if total > best_total {
+                    best_total = total;
+                    best_reserve_data = reserve_data;
+                    chosen = candidate;
+                    improved = true;
+                }
+            }
         }
-        possible_amount_out.push((total_amount_out, reserve_data));
-    });
-
-    let mut best_quote = EVMU256::ZERO;
-    let mut best_reserve_data = None;
-    for (amount_out, reserve_data) in possible_amount_out {
-        if amount_out > best_quote {
-            best_quote = amount_out;
-            best_reserve_data = Some(reserve_data);
+        if !improved {
+            break;
         }
     }
 
-    (
-        best_quote,
-        best_reserve_data.unwrap_or(initial_reserve_data),
-    )
+    (best_total, best_reserve_data)
 }
 
 pub fn get_uniswap_info(provider: &UniswapProvider, chain: &Chain) -> UniswapInfo {
@@ -407,6 +490,8 @@ pub fn get_uniswap_info(provider: &UniswapProvider, chain: &Chain) -> UniswapInf
                 "00fb7f630766e6a796048ea87d01acd3068e8ff67d078148a3fa3f4a84f69bd5",
             )
             .unwrap(),
+            version: UniswapVer::V2,
+            fee_pips: 0,
         },
         (&UniswapProvider::UniswapV2, &Chain::ETH) => UniswapInfo {
             pool_fee: 3,
@@ -416,6 +501,19 @@ pub fn get_uniswap_info(provider: &UniswapProvider, chain: &Chain) -> UniswapInf
                 "96e8ac4277198ff8b6f785478aa9a39f403cb768dd02cbee326c3e7da348845f",
             )
             .unwrap(),
+            version: UniswapVer::V2,
+            fee_pips: 0,
+        },
+        (&UniswapProvider::UniswapV3, &Chain::ETH) => UniswapInfo {
+            pool_fee: 0,
+            router: EVMAddress::from_str("0xe592427a0aece92de3edee1f18e0157c05861564").unwrap(),
+            factory: EVMAddress::from_str("0x1f98431c8ad98523631ae4a59f267346ea31f984").unwrap(),
+            init_code_hash: hex::decode(
+                "e34f199b19b2b4f47f68442619d555527d244f78a3297ea89325f843f87b8b54",
+            )
+            .unwrap(),
+            version: UniswapVer::V3,
+            fee_pips: 3000,
         },
         _ => panic!(
             "Uniswap provider {:?} @ chain {:?} not supported",
@@ -485,6 +583,76 @@ impl UniswapInfo {
         }
     }
 
+    /// Single-tick exact-in swap against a V3 pool's concentrated
+    /// liquidity, following the same `getNextSqrtPriceFromInput` /
+    /// `getAmountDelta` math the real pool uses for a swap that doesn't
+    /// cross a tick boundary: `sqrt_price_x96`/`liquidity` are the active
+    /// tick's `slot0.sqrtPriceX96` and `liquidity`, `fee_pips` is taken
+    /// from `self.fee_pips`, and `zero_for_one` selects which token is
+    /// being sold (`true` = token0 in, price moves down). Doesn't model
+    /// crossing into the next initialized tick, so this underestimates
+    /// `amount_out` for swaps large enough to exhaust the active tick's
+    /// liquidity - acceptable for fuzzing purposes the same way the V2
+    /// path here ignores multi-hop price impact beyond the immediate pair.
+    pub fn calculate_amounts_out_v3(
+        &self,
+        amount_in: EVMU256,
+        sqrt_price_x96: EVMU256,
+        liquidity: EVMU256,
+        zero_for_one: bool,
+    ) -> SwapResult {
+        if liquidity == EVMU256::ZERO {
+            return SwapResult {
+                amount: EVMU256::ZERO,
+                new_reserve_in: sqrt_price_x96,
+                new_reserve_out: liquidity,
+            };
+        }
+
+        let million = EVMU256::from(1_000_000u64);
+        let amount_in_after_fee = amount_in * (million - EVMU256::from(self.fee_pips)) / million;
+        let q96 = EVMU256::from(1u64) << 96;
+
+        if zero_for_one {
+            // token0 in: price (token1 per token0) decreases.
+            let liquidity_q96 = liquidity * q96;
+            let denominator = liquidity_q96 + amount_in_after_fee * sqrt_price_x96;
+            if denominator == EVMU256::ZERO {
+                return SwapResult {
+                    amount: EVMU256::ZERO,
+                    new_reserve_in: sqrt_price_x96,
+                    new_reserve_out: liquidity,
+                };
+            }
+            let sqrt_price_next = (liquidity_q96 * sqrt_price_x96) / denominator;
+            let amount_out = if sqrt_price_x96 > sqrt_price_next {
+                (liquidity * (sqrt_price_x96 - sqrt_price_next)) / q96
+            } else {
+                EVMU256::ZERO
+            };
+            SwapResult {
+                amount: amount_out,
+                new_reserve_in: sqrt_price_next,
+                new_reserve_out: liquidity,
+            }
+        } else {
+            // token1 in: price increases.
+            let sqrt_price_next = sqrt_price_x96 + (amount_in_after_fee * q96) / liquidity;
+            let amount_out = if sqrt_price_next > sqrt_price_x96 && sqrt_price_next != EVMU256::ZERO
+            {
+                (liquidity * q96 * (sqrt_price_next - sqrt_price_x96))
+                    / (sqrt_price_x96 * sqrt_price_next)
+            } else {
+                EVMU256::ZERO
+            };
+            SwapResult {
+                amount: amount_out,
+                new_reserve_in: sqrt_price_next,
+                new_reserve_out: liquidity,
+            }
+        }
+    }
+
     pub fn keccak(data: Vec<u8>) -> Vec<u8> {
         let mut hasher = Sha3::keccak256();
         let mut output = [0u8; 32];
@@ -4,10 +4,11 @@ use crate::{
         host::{FuzzHost, COVERAGE_NOT_CHANGED, STATE_CHANGE},
         input::{ConciseEVMInput, EVMInputT},
         middlewares::middleware::Middleware,
+        precompiles, snapshot,
         types::{EVMAddress, EVMU256},
     },
     generic_vm::{
-        vm_executor::{ExecutionResult, GenericVM},
+        vm_executor::{DeployError, ExecutionResult, GenericVM, VmFactory},
         vm_state::VMStateT,
     },
     input::{ConciseSerde, VMInputT},
@@ -45,6 +46,74 @@ use std::{
 pub const MEM_LIMIT: u64 = 10 * 1024;
 const MAX_POST_EXECUTION: usize = 10;
 
+/// A return value that remembers where it came from: `mem` is the
+/// producing frame's memory/output buffer, `offset`/`size` the slice of it
+/// this value actually is. `IntermediateExecutionResult::output` and
+/// `SinglePostExecution::return_data_buffer` use this instead of a bare
+/// `Bytes` so a continuation doesn't need to re-copy the buffer just to
+/// shrink or re-slice it, and so `RETURNDATACOPY` has real offset/size
+/// provenance to bounds-check an out-of-range copy against (which should
+/// revert, rather than silently zero-filling past the end - see
+/// `FuzzHost::run_inspect`'s `RETURNDATACOPY` handling).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ReturnData {
+    mem: Bytes,
+    offset: usize,
+    size: usize,
+}
+
+impl ReturnData {
+    pub fn new(mem: Bytes, offset: usize, size: usize) -> Self {
+        Self { mem, offset, size }
+    }
+
+    /// Wrap an already-extracted buffer as a return value that is its own
+    /// whole extent, for call sites that don't have a producing frame to
+    /// slice into (e.g. a precompile's freshly computed output).
+    pub fn whole(data: Bytes) -> Self {
+        let size = data.len();
+        Self {
+            mem: data,
+            offset: 0,
+            size,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// `true` if `[copy_offset, copy_offset + copy_size)` falls entirely
+    /// within this value's extent, i.e. `RETURNDATACOPY` starting at
+    /// `copy_offset` for `copy_size` bytes is in-range. Out-of-range should
+    /// be treated as a revert rather than zero-filled, per EIP-211.
+    pub fn in_bounds(&self, copy_offset: usize, copy_size: usize) -> bool {
+        copy_offset
+            .checked_add(copy_size)
+            .map_or(false, |end| end <= self.size)
+    }
+}
+
+impl Deref for ReturnData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        let start = self.offset.min(self.mem.len());
+        let end = (self.offset + self.size).min(self.mem.len());
+        &self.mem[start..end]
+    }
+}
+
+impl From<Bytes> for ReturnData {
+    fn from(data: Bytes) -> Self {
+        Self::whole(data)
+    }
+}
+
 /// Get the token context from the flashloan middleware,
 /// which contains uniswap pairs of that token
 #[macro_export]
@@ -121,6 +190,25 @@ pub struct SinglePostExecution {
     pub output_len: usize,
     /// Output Offset
     pub output_offset: usize,
+
+    /// EIP-211 return-data buffer (`RETURNDATASIZE`/`RETURNDATACOPY`) that
+    /// was live at the moment control was leaked, so resuming via
+    /// [`Self::get_interpreter`] doesn't silently hand the contract an
+    /// empty buffer. A [`ReturnData`] rather than a bare `Bytes` so the
+    /// offset/size provenance captured at the leak point survives into the
+    /// continuation for `RETURNDATACOPY` bounds-checking. `#[serde(default)]`
+    /// so corpora serialized before this field existed still deserialize,
+    /// just without a captured buffer.
+    #[serde(default)]
+    pub return_data_buffer: ReturnData,
+
+    /// Gas left at the moment control was leaked, so resuming via
+    /// [`Self::get_interpreter`] continues against the budget that was
+    /// actually still available instead of `Gas::new(0)` (which made every
+    /// continuation look instantly out of gas). `#[serde(default)]` for the
+    /// same reason as `return_data_buffer`.
+    #[serde(default)]
+    pub gas_remaining: u64,
 }
 
 impl SinglePostExecution {
@@ -137,6 +225,7 @@ impl SinglePostExecution {
         self.value.hash(hasher);
         self.output_len.hash(hasher);
         self.output_offset.hash(hasher);
+        self.return_data_buffer.hash(hasher);
     }
 
     /// Convert the post execution context to revm [`CallContext`]
@@ -162,10 +251,10 @@ impl SinglePostExecution {
         Interpreter {
             instruction_pointer: unsafe { contract.bytecode.as_ptr().add(self.program_counter) },
             instruction_result: self.instruction_result,
-            gas: Gas::new(0),
+            gas: Gas::new(self.gas_remaining),
             memory: self.memory.clone(),
             stack,
-            return_data_buffer: Bytes::new(),
+            return_data_buffer: Bytes::copy_from_slice(&self.return_data_buffer),
             return_range: self.return_range.clone(),
             is_static: self.is_static,
             contract,
@@ -189,6 +278,8 @@ impl SinglePostExecution {
             value: interp.contract.value,
             output_len: out_len,
             output_offset: out_offset,
+            return_data_buffer: ReturnData::whole(interp.return_data_buffer.clone()),
+            gas_remaining: interp.gas.remaining(),
         }
     }
 }
@@ -209,6 +300,108 @@ impl PostExecutionCtx {
     }
 }
 
+/// Kind of call that opened a [`CallFrame`], mirroring the EVM's own call
+/// opcodes so an oracle can tell e.g. an unexpected `DELEGATECALL` target
+/// apart from an ordinary `CALL`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CallType {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    Create2,
+}
+
+/// One frame of the call tree [`CallTracer`](crate::evm::middlewares::call_tracer::CallTracer)
+/// builds up over a transaction: everything needed to tell who called whom,
+/// with what, and what came back, without re-running the transaction.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CallFrame {
+    /// Call-stack depth this frame executed at, 0 being the top-level call.
+    pub depth: usize,
+    pub from: EVMAddress,
+    pub to: EVMAddress,
+    pub call_type: CallType,
+    pub value: EVMU256,
+    pub input: Bytes,
+    pub return_data: Bytes,
+    pub gas_used: u64,
+    pub reverted: bool,
+}
+
+/// One opcode executed during a transaction, in execution order. Kept
+/// separate from [`CallFrame`] so an oracle can still see step-by-step
+/// control flow (e.g. to tell exactly where inside a frame a reentrant call
+/// was made) without paying for per-step storage inside every frame.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TraceStep {
+    pub depth: usize,
+    pub pc: usize,
+    pub opcode: u8,
+}
+
+/// Structured call-tree + opcode trace of a transaction, built up by
+/// `CallTracer::on_step`/`on_return` and attached to the [`EVMState`] it
+/// produced, following the `Tracer`/`VMTracer` model mature EVM executives
+/// use. Lets an [`Oracle`](crate::oracle::Oracle) pattern-match on internal
+/// call structure - e.g. reentrancy is the same `(from, to)` pair showing
+/// up as a frame nested inside an earlier, still-open frame with that same
+/// pair - instead of only seeing the final state and output.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CallTrace {
+    /// Completed call frames, in the order their calls *returned* (so a
+    /// frame's children all appear before it).
+    pub frames: Vec<CallFrame>,
+    /// Every opcode executed, in program order.
+    pub steps: Vec<TraceStep>,
+}
+
+impl CallTrace {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+/// A single emitted event captured in a [`Substate`] frame, keyed loosely
+/// like `LOG0`-`LOG4`: the emitting contract, its topics, and raw data.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SubstateLog {
+    pub address: EVMAddress,
+    pub topics: Vec<EVMU256>,
+    pub data: Bytes,
+}
+
+/// Effects of one open call frame, following the Substate/CleanupMode model
+/// mature EVM executives (e.g. go-ethereum, OpenEthereum) use to let a
+/// revert discard exactly the frame that failed without losing what the
+/// frames below it already committed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Substate {
+    /// Balances touched this frame, keyed by address, last-write-wins.
+    pub touched_balances: HashMap<EVMAddress, EVMU256>,
+    /// Storage slots written this frame, keyed by (address, slot).
+    pub storage_writes: HashMap<EVMAddress, HashMap<EVMU256, EVMU256>>,
+    /// Events emitted this frame, in emission order.
+    pub logs: Vec<SubstateLog>,
+    /// `SELFDESTRUCT`s this frame, same `(address, pc)` shape as
+    /// [`EVMState::self_destruct`].
+    pub self_destructs: HashSet<(EVMAddress, usize)>,
+}
+
+impl Substate {
+    /// Folds `other` (a child frame) into `self` (its parent), e.g. on a
+    /// normal return from a sub-call.
+    fn merge(&mut self, other: Substate) {
+        self.touched_balances.extend(other.touched_balances);
+        for (address, slots) in other.storage_writes {
+            self.storage_writes.entry(address).or_default().extend(slots);
+        }
+        self.logs.extend(other.logs);
+        self.self_destructs.extend(other.self_destructs);
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct EVMState {
     /// State of the EVM, which is mapping of EVMU256 slot to EVMU256 value for each contract
@@ -232,6 +425,70 @@ pub struct EVMState {
     pub typed_bug: HashSet<(String, (EVMAddress, usize))>,
     #[serde(skip)]
     pub arbitrary_calls: HashSet<(EVMAddress, EVMAddress, usize)>,
+
+    /// `(caller, forged_address, precompile id)` for every `ecrecover` call
+    /// `execute_from_pc` answered with a fuzz-controlled address instead of
+    /// a real signature recovery (see `precompiles::execute`'s
+    /// `forced_recovery`), so an oracle can flag the finding as a forged-
+    /// signature exploit rather than a genuine one. Same shape as
+    /// `arbitrary_calls` for the same reason.
+    #[serde(skip)]
+    pub precompile_substitutions: HashSet<(EVMAddress, EVMAddress, usize)>,
+
+    /// `IntermediateExecutionResult::gas_used`/`gas_per_repeat` of the most
+    /// recent top-level transaction. Replaced wholesale every transaction
+    /// rather than accumulated, mirroring `reverted_substate` below - an
+    /// oracle only ever cares about the call that just happened (see
+    /// `oracles::gas_usage::GasUsageOracle`).
+    #[serde(skip)]
+    pub last_gas_used: u64,
+    #[serde(skip)]
+    pub last_gas_per_repeat: Vec<u64>,
+
+    /// `(caller, callee, pc)` of every `CALL` this transaction made that
+    /// forwarded less than 63/64 of the gas it had available (the EIP-150
+    /// "stipend" rule) and whose callee then ran out of gas, set by
+    /// `FuzzHost::call` the same way it tracks `arbitrary_calls`. Lets
+    /// `oracles::gas_usage::GasGriefingOracle` flag a forwarding site that
+    /// can be starved into failing the victim call without reverting the
+    /// caller.
+    #[serde(skip)]
+    pub underfunded_calls: HashSet<(EVMAddress, EVMAddress, usize)>,
+
+    /// Interpreter-vs-JIT divergences `EVMExecutor::execute_abi` recorded
+    /// for the most recent top-level transaction, when `FuzzHost`'s
+    /// `differential` backend is configured (see
+    /// `differential::DifferentialExecutor::check`). Not a `HashSet` like
+    /// `arbitrary_calls` since a `DifferentialFinding` isn't `Hash`/`Eq` -
+    /// every divergence this tx produced is kept, same dedup-free
+    /// semantics as `Vec` elsewhere in this struct.
+    #[serde(skip)]
+    pub compiler_mismatches: Vec<crate::evm::differential::DifferentialFinding>,
+
+    /// Substate journal, one open frame per live call depth:
+    /// `substate_scopes[0]` is the top-level transaction, and each
+    /// `CALL`/`CREATE` pushes a frame via [`Self::checkpoint`] that either
+    /// merges down into its caller on [`Self::commit_checkpoint`] (normal
+    /// return) or is dropped on [`Self::revert_checkpoint`] (`REVERT`, an
+    /// out-of-gas, or a Solidity `throw`), mirroring the discard-or-merge
+    /// journaling `ConcolicHost::push_ctx`/`pop_ctx` already do for path
+    /// constraints.
+    #[serde(skip)]
+    substate_scopes: Vec<Substate>,
+    /// The merged substate of the most recent top-level transaction that
+    /// reverted, captured right before its outermost frame would otherwise
+    /// have been discarded. Lets an oracle still see events emitted and
+    /// storage transiently written on the way down, e.g. "a privileged
+    /// event was emitted before the call reverted".
+    #[serde(skip)]
+    pub reverted_substate: Option<Substate>,
+
+    /// Structured call-tree + opcode trace of the transaction that produced
+    /// this state, when `CallTracer` is enabled (`evm_fuzzer` turns it on
+    /// when `REPLAY` is set). `None` otherwise, so a normal
+    /// fuzzing run doesn't pay for building a trace nothing reads.
+    #[serde(skip)]
+    pub call_trace: Option<CallTrace>,
 }
 
 impl Default for EVMState {
@@ -311,6 +568,14 @@ impl EVMState {
             self_destruct: Default::default(),
             typed_bug: Default::default(),
             arbitrary_calls: Default::default(),
+            precompile_substitutions: Default::default(),
+            last_gas_used: 0,
+            last_gas_per_repeat: Default::default(),
+            underfunded_calls: Default::default(),
+            compiler_mismatches: Default::default(),
+            substate_scopes: vec![Substate::default()],
+            reverted_substate: None,
+            call_trace: None,
         }
     }
 
@@ -328,6 +593,75 @@ impl EVMState {
     pub fn insert(&mut self, address: EVMAddress, storage: HashMap<EVMU256, EVMU256>) {
         self.state.insert(address, storage);
     }
+
+    /// Opens a new substate journal frame on entering a sub-call
+    /// (`CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`/`CREATE`).
+    pub fn checkpoint(&mut self) {
+        self.substate_scopes.push(Substate::default());
+    }
+
+    /// Folds the innermost frame into the one below it on a normal return:
+    /// everything the sub-call touched stays visible to its caller.
+    pub fn commit_checkpoint(&mut self) {
+        if let Some(frame) = self.substate_scopes.pop() {
+            match self.substate_scopes.last_mut() {
+                Some(parent) => parent.merge(frame),
+                None => self.substate_scopes.push(frame),
+            }
+        }
+    }
+
+    /// Drops the innermost frame on `REVERT`/out-of-gas/`throw`. If it was
+    /// the top-level transaction's own frame (nothing left open beneath
+    /// it), the frame is stashed in [`Self::reverted_substate`] instead of
+    /// being silently discarded, so an oracle can still inspect what the
+    /// reverted call did on its way down.
+    pub fn revert_checkpoint(&mut self) {
+        if let Some(frame) = self.substate_scopes.pop() {
+            if self.substate_scopes.is_empty() {
+                self.reverted_substate = Some(frame);
+                self.substate_scopes.push(Substate::default());
+            }
+        }
+    }
+
+    /// Records a storage write in the innermost open substate frame.
+    pub fn record_storage_write(&mut self, address: EVMAddress, slot: EVMU256, value: EVMU256) {
+        self.substate_scopes
+            .last_mut()
+            .expect("substate journal has no open frame")
+            .storage_writes
+            .entry(address)
+            .or_default()
+            .insert(slot, value);
+    }
+
+    /// Records a balance change in the innermost open substate frame.
+    pub fn record_balance_touch(&mut self, address: EVMAddress, balance: EVMU256) {
+        self.substate_scopes
+            .last_mut()
+            .expect("substate journal has no open frame")
+            .touched_balances
+            .insert(address, balance);
+    }
+
+    /// Records an emitted `LOGn` in the innermost open substate frame.
+    pub fn record_log(&mut self, address: EVMAddress, topics: Vec<EVMU256>, data: Bytes) {
+        self.substate_scopes
+            .last_mut()
+            .expect("substate journal has no open frame")
+            .logs
+            .push(SubstateLog { address, topics, data });
+    }
+
+    /// Records a `SELFDESTRUCT` in the innermost open substate frame.
+    pub fn record_self_destruct(&mut self, address: EVMAddress, pc: usize) {
+        self.substate_scopes
+            .last_mut()
+            .expect("substate journal has no open frame")
+            .self_destructs
+            .insert((address, pc));
+    }
 }
 
 /// Is current EVM execution fast call
@@ -351,6 +685,17 @@ where
     deployer: EVMAddress,
     /// Known arbitrary (caller,pc)
     pub _known_arbitrary: HashSet<(EVMAddress, usize)>,
+    /// Raw `Bytecode` for every address this executor has deployed,
+    /// mirrored alongside `host.set_code`'s `Arc<BytecodeLocked>` copy
+    /// because [`snapshot::CampaignSnapshot`] needs the original bytes to
+    /// serialize, not the locked/jump-table-analyzed form.
+    pub deployed_bytecode: HashMap<EVMAddress, Bytecode>,
+    /// Constructor calldata each address was last deployed with, so a
+    /// fuzzer-mutated re-encoding (see
+    /// [`EVMExecutor::redeploy_with_constructor_args`]) can be compared
+    /// against what it replaced and an interesting configuration kept as
+    /// part of the corpus.
+    pub deployed_constructor_args: HashMap<EVMAddress, Bytes>,
     phandom: PhantomData<(I, S, VS, CI)>,
 }
 
@@ -367,8 +712,10 @@ pub fn is_reverted_or_control_leak(ret: &InstructionResult) -> bool {
 /// Contains raw information of revm output and execution
 #[derive(Clone, Debug)]
 pub struct IntermediateExecutionResult {
-    /// Output of the execution
-    pub output: Bytes,
+    /// Output of the execution. A [`ReturnData`] view into the producing
+    /// frame's buffer rather than an eager copy, carrying the offset/size
+    /// provenance `RETURNDATACOPY` needs to bounds-check against.
+    pub output: ReturnData,
     /// The new state after execution
     pub new_state: EVMState,
     /// Program counter after execution
@@ -379,6 +726,14 @@ pub struct IntermediateExecutionResult {
     pub stack: Vec<EVMU256>,
     /// Memory after execution
     pub memory: Vec<u8>,
+    /// Total gas spent against the limit `execute_from_pc` handed the
+    /// interpreter (see `EVMInputT::get_gas_limit`), summed across every
+    /// `repeat`.
+    pub gas_used: u64,
+    /// `gas_used` broken out per `repeat` iteration, so an oracle can tell
+    /// a single expensive call apart from one whose cost grows each time
+    /// it's repeated (see `oracles::gas_usage::GasUsageOracle`).
+    pub gas_per_repeat: Vec<u64>,
 }
 
 impl<VS, I, S, CI> EVMExecutor<I, S, VS, CI>
@@ -404,6 +759,8 @@ where
             host: fuzz_host,
             deployer,
             _known_arbitrary: Default::default(),
+            deployed_bytecode: Default::default(),
+            deployed_constructor_args: Default::default(),
             phandom: PhantomData,
         }
     }
@@ -438,6 +795,7 @@ where
             self.host.jumpi_trace = 37;
             self.host.current_self_destructs = vec![];
             self.host.current_arbitrary_calls = vec![];
+            self.host.current_underfunded_calls = vec![];
             // Initially, there is no state change
             unsafe {
                 STATE_CHANGE = false;
@@ -451,6 +809,54 @@ where
         self.host.randomness = input.get_randomness();
         let mut repeats = input.get_repeat();
 
+        // A direct call into one of the standard precompile addresses
+        // (`0x1`-`0x9`) has no deployed bytecode to execute, so it has to
+        // be dispatched before the "no code" bailout below. Nested `CALL`s
+        // a contract makes internally still reach revm's own built-in
+        // precompile table in `FuzzHost::call`; this only covers the
+        // fuzzer's own direct calls (e.g. `seed_ecrecover_guard`'s corpus
+        // entry calling `ecrecover` directly to forge a signature).
+        if let Some(id) = precompiles::match_precompile(&call_ctx.code_address) {
+            let forced_recovery = if id == 1 && self.host.forge_ecrecover {
+                Some(input.get_caller())
+            } else {
+                None
+            };
+            let mut new_state = vm_state.clone();
+            let gas_used = precompiles::gas_cost(id, data.len());
+            return match precompiles::execute(id, &data[..], forced_recovery) {
+                Some(result) => {
+                    if result.forged {
+                        new_state.precompile_substitutions.insert((
+                            input.get_caller(),
+                            input.get_caller(),
+                            id as usize,
+                        ));
+                    }
+                    IntermediateExecutionResult {
+                        output: ReturnData::whole(result.data),
+                        new_state,
+                        pc: 0,
+                        ret: InstructionResult::Return,
+                        stack: Default::default(),
+                        memory: Default::default(),
+                        gas_used,
+                        gas_per_repeat: vec![gas_used],
+                    }
+                }
+                None => IntermediateExecutionResult {
+                    output: ReturnData::default(),
+                    new_state,
+                    pc: 0,
+                    ret: InstructionResult::Revert,
+                    stack: Default::default(),
+                    memory: Default::default(),
+                    gas_used: 0,
+                    gas_per_repeat: vec![],
+                },
+            };
+        }
+
         // Get the bytecode
         let bytecode = match self.host.code.get(&call_ctx.code_address) {
             Some(i) => i.clone(),
@@ -460,12 +866,14 @@ where
                     call_ctx.code_address
                 );
                 return IntermediateExecutionResult {
-                    output: Bytes::new(),
+                    output: ReturnData::default(),
                     new_state: EVMState::default(),
                     pc: 0,
                     ret: InstructionResult::Revert,
                     stack: Default::default(),
                     memory: Default::default(),
+                    gas_used: 0,
+                    gas_per_repeat: vec![],
                 };
             }
         };
@@ -478,9 +886,16 @@ where
             {
                 // setup the pc, memory, and stack as the post execution context
                 let mut interp = post_exec_ctx.get_interpreter(bytecode);
-                // set return buffer as the input
-                // we remove the first 4 bytes because the first 4 bytes is the function hash (00000000 here)
-                interp.return_data_buffer = data.slice(4..);
+                // `data` is the function hash (00000000 here) followed by the
+                // return buffer of the sub-call the step continuation wants
+                // to feed back in. Only overwrite the buffer captured at the
+                // leak point when the continuation actually supplies new call
+                // output - otherwise (e.g. a bare 4-byte selector) leave the
+                // EIP-211 buffer as it was when control was leaked.
+                let stepped_return_data = data.slice(4..);
+                if !stepped_return_data.is_empty() {
+                    interp.return_data_buffer = stepped_return_data;
+                }
                 let target_len = min(post_exec_ctx.output_len, interp.return_data_buffer.len());
                 interp.memory.set(
                     post_exec_ctx.output_offset,
@@ -492,14 +907,22 @@ where
             // if there is no post execution context, then we create the interpreter from the
             // beginning
             let call = Contract::new_with_context_analyzed(data, bytecode, call_ctx);
-            Interpreter::new_with_memory_limit(call, 1e10 as u64, false, MEM_LIMIT)
+            Interpreter::new_with_memory_limit(call, input.get_gas_limit(), false, MEM_LIMIT)
         };
 
-        // Execute the contract for `repeats` times or until revert
+        // Execute the contract for `repeats` times or until revert, tracking
+        // gas spent per repeat (not just the total) so an oracle can tell a
+        // single expensive call apart from one whose cost grows each time
+        // it's repeated (e.g. a storage-filling loop).
         let mut r = InstructionResult::Stop;
+        let mut gas_per_repeat = Vec::with_capacity(repeats);
+        let mut gas_before = interp.gas.remaining();
         for _v in 0..repeats - 1 {
             // println!("repeat: {:?}", v);
             r = self.host.run_inspect(&mut interp, state);
+            let gas_after = interp.gas.remaining();
+            gas_per_repeat.push(gas_before.saturating_sub(gas_after));
+            gas_before = gas_after;
             interp.stack.data.clear();
             interp.memory.data.clear();
             interp.instruction_pointer = interp.contract.bytecode.as_ptr();
@@ -510,16 +933,19 @@ where
         }
         if is_call_success!(r) {
             r = self.host.run_inspect(&mut interp, state);
+            gas_per_repeat.push(gas_before.saturating_sub(interp.gas.remaining()));
         }
 
         // Build the result
         let result = IntermediateExecutionResult {
-            output: interp.return_value(),
+            output: ReturnData::whole(interp.return_value()),
             new_state: self.host.evmstate.clone(),
             pc: interp.program_counter(),
             ret: r,
             stack: interp.stack.data().clone(),
             memory: interp.memory.data().clone(),
+            gas_used: gas_per_repeat.iter().sum(),
+            gas_per_repeat,
         };
 
         unsafe {
@@ -554,6 +980,12 @@ where
         if data.len() == 0 {
             data = Bytes::from(input.get_direct_data());
         }
+        // Kept for the differential-execution check below: the JIT path
+        // only ever sees the original call, not a control-leak
+        // continuation's stepped-in calldata, so it's only meaningful to
+        // compare when the whole call resolved in one iteration (see
+        // `cleanup` below).
+        let original_data = data.clone();
 
         let mut cleanup = true;
 
@@ -638,6 +1070,36 @@ where
                 .cloned()
                 .chain(self.host.current_arbitrary_calls.iter().cloned()),
         );
+        r.new_state.underfunded_calls = HashSet::from_iter(
+            vm_state
+                .underfunded_calls
+                .iter()
+                .cloned()
+                .chain(self.host.current_underfunded_calls.iter().cloned()),
+        );
+        r.new_state.last_gas_used = r.gas_used;
+        r.new_state.last_gas_per_repeat = r.gas_per_repeat.clone();
+
+        // Differential check: only meaningful when the call resolved in a
+        // single iteration, since the JIT path replays the original
+        // calldata, not a control-leak continuation's stepped-in data.
+        r.new_state.compiler_mismatches.clear();
+        if cleanup {
+            if let Some(differential) = &self.host.differential {
+                let contract_address = input.get_contract();
+                let pre_storage = vm_state.get(&contract_address).cloned().unwrap_or_default();
+                if let Some(finding) = differential.check(
+                    contract_address,
+                    &original_data,
+                    &pre_storage,
+                    r.ret,
+                    &r.output,
+                    r.gas_used,
+                ) {
+                    r.new_state.compiler_mismatches.push(finding);
+                }
+            }
+        }
 
         // println!("r.ret: {:?}", r.ret);
 
@@ -676,6 +1138,89 @@ where
         self.execute(input, state);
         self.host.remove_middlewares(middleware);
     }
+
+    /// Capture every deployed contract's bytecode and `vm_state`'s storage
+    /// into a [`snapshot::CampaignSnapshot`], so a reproducible environment
+    /// can be serialized and shared instead of re-running every deploy
+    /// transaction to reach it again.
+    pub fn snapshot(&self, vm_state: &EVMState) -> snapshot::CampaignSnapshot {
+        snapshot::CampaignSnapshot {
+            contracts: self
+                .deployed_bytecode
+                .iter()
+                .map(|(address, code)| snapshot::DeployedContractSnapshot {
+                    address: *address,
+                    code: code.bytes().to_vec(),
+                })
+                .collect(),
+            state: vm_state.clone(),
+        }
+    }
+
+    /// Restore a [`snapshot::CampaignSnapshot`]'s contracts directly,
+    /// instead of through [`GenericVM::deploy`]: `contract.code` is the
+    /// post-constructor runtime bytecode `snapshot()` captured (see
+    /// [`snapshot::DeployedContractSnapshot`]), not init code, so running
+    /// it through `deploy` would execute it *as a constructor* and install
+    /// whatever that happens to return rather than `contract.code` itself.
+    /// Instead this re-runs the same post-execution bookkeeping `deploy`
+    /// does once a constructor returns - analysis, `on_insert`
+    /// middlewares, `set_code` - directly over the already-final bytecode,
+    /// so every analysis-derived index (constant pool, function
+    /// selectors, ...) still comes back populated. Returns the restored
+    /// storage state; callers that want it as the active `vm_state` for
+    /// subsequent execution are responsible for feeding it back in, the
+    /// same way corpus initialization already does.
+    pub fn restore(&mut self, snap: &snapshot::CampaignSnapshot, state: &mut S) -> EVMState {
+        for contract in &snap.contracts {
+            let mut contract_code = Bytecode::new_raw(Bytes::copy_from_slice(&contract.code));
+            bytecode_analyzer::add_analysis_result_to_state(&contract_code, state);
+            unsafe {
+                invoke_middlewares!(
+                    &mut contract_code,
+                    contract.address,
+                    &mut self.host,
+                    state,
+                    on_insert
+                );
+            }
+            if let Some(differential) = &mut self.host.differential {
+                differential.compile_lazily(contract.address, &contract_code);
+            }
+            self.deployed_bytecode
+                .insert(contract.address, contract_code.clone());
+            self.host.set_code(contract.address, contract_code, state);
+        }
+        snap.state.clone()
+    }
+
+    /// Re-runs `init_code`'s constructor at `deployed_address` with
+    /// `new_constructor_args` instead of whatever it was last deployed
+    /// with, so a fuzzer-mutated ABI encoding of the constructor can be
+    /// tried without replaying the rest of the campaign. Deploying again
+    /// through [`GenericVM::deploy`] (rather than poking `host.code`
+    /// directly) keeps this on the same path every other deploy goes
+    /// through, so `deployed_constructor_args`/`deployed_bytecode` and all
+    /// `on_insert` middleware stay consistent with whichever encoding ends
+    /// up live.
+    pub fn redeploy_with_constructor_args(
+        &mut self,
+        init_code: Bytecode,
+        new_constructor_args: Bytes,
+        deployed_address: EVMAddress,
+        state: &mut S,
+    ) -> Result<EVMAddress, DeployError>
+    where
+        Self: GenericVM<VS, Bytecode, Bytes, EVMAddress, Vec<u8>, I, S, CI>,
+    {
+        GenericVM::deploy(
+            self,
+            init_code,
+            Some(new_constructor_args),
+            deployed_address,
+            state,
+        )
+    }
 }
 
 pub static mut IN_DEPLOY: bool = false;
@@ -705,9 +1250,10 @@ where
         constructor_args: Option<Bytes>,
         deployed_address: EVMAddress,
         state: &mut S,
-    ) -> Option<EVMAddress> {
+    ) -> Result<EVMAddress, DeployError> {
+        let constructor_args = constructor_args.unwrap_or(Bytes::new());
         let deployer = Contract::new(
-            constructor_args.unwrap_or(Bytes::new()),
+            constructor_args.clone(),
             code,
             deployed_address,
             deployed_address,
@@ -726,8 +1272,19 @@ where
             IN_DEPLOY = false;
         }
         if r != InstructionResult::Return {
-            println!("deploy failed: {:?}", r);
-            return None;
+            let err = match r {
+                InstructionResult::Revert => DeployError::ConstructorReverted {
+                    revert_data: interp.return_value().to_vec(),
+                },
+                InstructionResult::OutOfGas
+                | InstructionResult::MemoryOOG => DeployError::OutOfGas,
+                InstructionResult::MemoryLimitOOG => DeployError::MemoryLimitExceeded,
+                other => DeployError::Halted {
+                    reason: format!("{:?}", other),
+                },
+            };
+            println!("deploy failed: {}", err);
+            return Err(err);
         }
         println!(
             "deployer = 0x{} contract = {:?}",
@@ -745,8 +1302,15 @@ where
                 on_insert
             );
         }
+        if let Some(differential) = &mut self.host.differential {
+            differential.compile_lazily(deployed_address, &contract_code);
+        }
+        self.deployed_bytecode
+            .insert(deployed_address, contract_code.clone());
+        self.deployed_constructor_args
+            .insert(deployed_address, constructor_args);
         self.host.set_code(deployed_address, contract_code, state);
-        Some(deployed_address)
+        Ok(deployed_address)
     }
 
     fn execute(
@@ -765,3 +1329,60 @@ where
         self
     }
 }
+
+/// The default [`VmFactory`] backend: wraps an already-configured
+/// [`FuzzHost`] (middlewares, spec ID, etc. all set up by the caller) and
+/// hands back an [`EVMExecutor`] as a boxed [`GenericVM`] once a deployer
+/// address is known. Picking a different `VmBackend` (see
+/// `crate::evm::config::VmBackend`) would mean constructing a different
+/// `VmFactory` here instead; corpus initialization downcasts the
+/// `GenericVM` it is given to `EVMExecutor`, so it works only with this one.
+pub struct EvmVmFactory<VS, I, S, CI>
+where
+    S: State + HasCaller<EVMAddress> + Debug + Clone + 'static,
+    I: VMInputT<VS, EVMAddress, ConciseEVMInput> + EVMInputT,
+    VS: VMStateT,
+{
+    host: FuzzHost<VS, I, S>,
+    phantom: PhantomData<CI>,
+}
+
+impl<VS, I, S, CI> EvmVmFactory<VS, I, S, CI>
+where
+    S: State + HasCaller<EVMAddress> + Debug + Clone + 'static,
+    I: VMInputT<VS, EVMAddress, ConciseEVMInput> + EVMInputT,
+    VS: VMStateT,
+{
+    pub fn new(host: FuzzHost<VS, I, S>) -> Self {
+        Self {
+            host,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<VS, I, S, CI> VmFactory<VS, Bytecode, Bytes, EVMAddress, Vec<u8>, I, S, CI>
+    for EvmVmFactory<VS, I, S, CI>
+where
+    I: VMInputT<VS, EVMAddress, ConciseEVMInput> + EVMInputT + 'static,
+    S: State
+        + HasRand
+        + HasCorpus<I>
+        + HasItyState<EVMAddress, VS, ConciseEVMInput>
+        + HasMetadata
+        + HasCaller<EVMAddress>
+        + HasCurrentInputIdx
+        + Default
+        + Clone
+        + Debug
+        + 'static,
+    VS: VMStateT + Default + 'static,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde + 'static,
+{
+    fn build(
+        self: Box<Self>,
+        deployer: EVMAddress,
+    ) -> Box<dyn GenericVM<VS, Bytecode, Bytes, EVMAddress, Vec<u8>, I, S, CI>> {
+        Box::new(EVMExecutor::<I, S, VS, CI>::new(self.host, deployer))
+    }
+}
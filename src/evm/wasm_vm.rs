@@ -0,0 +1,282 @@
+/// Second [`GenericVM`] backend, for contracts compiled to WebAssembly
+/// (e.g. Arbitrum Stylus, or eWASM-era chains) rather than EVM bytecode.
+///
+/// Mirrors the OpenEthereum `Exec`/`Vm` split: [`EVMExecutor`] and
+/// [`WasmExecutor`] are two interchangeable engines behind the same
+/// `GenericVM` surface, so a target that deploys both EVM and WASM
+/// contracts can host each address with whichever engine it needs while
+/// `FuzzHost` stays the single place that resolves an address to a code
+/// entry and routes cross-VM calls. The VM state is the same
+/// [`EVMState`] the EVM side uses (see [`WasmExecutor`] for what a module
+/// can and cannot do with it), so bug/coverage bookkeeping
+/// (`typed_bug`, `self_destruct`, `arbitrary_calls`, ...) stays unified and
+/// existing oracles keep working unmodified against either engine's
+/// output.
+use std::{fmt::Debug, marker::PhantomData};
+
+use bytes::Bytes;
+use libafl::{
+    prelude::{HasMetadata, HasRand},
+    state::{HasCorpus, State},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    evm::{
+        types::EVMAddress,
+        vm::EVMState,
+    },
+    generic_vm::{
+        vm_executor::{DeployError, ExecutionResult, GenericVM},
+        vm_state::VMStateT,
+    },
+    input::{ConciseSerde, VMInputT},
+    state::{HasCaller, HasCurrentInputIdx, HasItyState},
+    state_input::StagedVMState,
+};
+
+/// A WASM module's raw bytes, keyed by the deployed address the same way
+/// [`EVMExecutor`] keys `Bytecode` in `FuzzHost::code`.
+#[derive(Clone)]
+pub struct WasmModule {
+    pub bytes: Vec<u8>,
+}
+
+/// Continuation state for a WASM host call that leaked control back to the
+/// fuzzer, analogous to [`SinglePostExecution`] on the EVM side: instead of
+/// a program counter into bytecode, WASM resumes at a host-call
+/// continuation id the engine hands back when a host import traps back
+/// into the fuzzer (e.g. a cross-contract `call` import).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WasmPostExecution {
+    /// Opaque continuation id the wasm engine uses to resume the paused
+    /// instance (e.g. a Wasmi `ResumableCall` handle serialized to an
+    /// index into a side table, since the live instance itself isn't
+    /// `Serialize`).
+    pub continuation_id: u64,
+    /// Linear memory snapshot at the point of the host call, so resuming
+    /// later (possibly after this state round-trips through the corpus)
+    /// restores the instance's memory rather than starting it zeroed.
+    pub memory: Vec<u8>,
+    /// Return data the paused host-call import is waiting on, fed back in
+    /// the same way `data` feeds a control-leak continuation on the EVM
+    /// side.
+    pub return_data: Bytes,
+}
+
+/// Executes WASM contract code with [`EVMState`] as its VM state.
+/// `WasmRuntime` has no storage import, so a module cannot read or write
+/// that state's storage, and `execute` returns the input's `EVMState`
+/// unchanged.
+///
+/// `R` is generic over "whatever WASM runtime is vendored" (e.g. `wasmi`
+/// for a pure-Rust, no-JIT interpreter that's easy to fuzz
+/// deterministically) - this type only needs the runtime to expose
+/// instantiate/call/resume and linear-memory access, which is behind the
+/// [`WasmRuntime`] trait below so swapping interpreters doesn't ripple into
+/// `FuzzHost`. `D` is the dispatcher a cross-VM host call is routed
+/// through; see [`HostDispatch`].
+pub struct WasmExecutor<R, D, I, S, CI> {
+    pub modules: std::collections::HashMap<EVMAddress, WasmModule>,
+    pub deployer: EVMAddress,
+    /// The concrete WASM runtime instance this executor drives - without
+    /// owning one of these, `GenericVM::execute` has nothing to call into
+    /// and can only ever be a stub.
+    pub engine: R,
+    /// Resolves a `WasmRunResult::HostCall`'s `target` to whichever engine
+    /// hosts it (this same `WasmExecutor`, or an `EVMExecutor`) and runs
+    /// the call - normally `FuzzHost`, since it's the one place that
+    /// already knows which engine every deployed address belongs to.
+    pub dispatch: D,
+    state_changed: bool,
+    _phantom: PhantomData<(I, S, CI)>,
+}
+
+/// Resolves a cross-VM call's target to whichever engine hosts it and runs
+/// it to completion - the seam [`FuzzHost`](crate::evm::host::FuzzHost) is
+/// meant to implement, so a `WasmExecutor`'s outgoing host calls can reach
+/// either another WASM module or an EVM contract without `WasmExecutor`
+/// needing to know which engine actually owns `target`.
+pub trait HostDispatch {
+    fn dispatch(&mut self, target: EVMAddress, input: Bytes) -> Bytes;
+}
+
+/// Minimal surface a WASM runtime has to provide for [`WasmExecutor`] to
+/// drive it; kept separate from the concrete engine crate so this module
+/// compiles against any runtime that can instantiate a module, call an
+/// export, and resume a paused call.
+pub trait WasmRuntime {
+    /// Instantiate `module`, call `export` with `args`, and run to
+    /// completion or a host-call trap. Returns the output bytes on normal
+    /// return, or a pending continuation when a host import (e.g. a
+    /// cross-contract call) needs the fuzzer to answer before resuming.
+    fn call(
+        &mut self,
+        module: &WasmModule,
+        export: &str,
+        args: &[u8],
+    ) -> WasmRunResult;
+
+    /// Resume a previously paused call, feeding back `host_call_result` as
+    /// the host import's return value.
+    fn resume(&mut self, post_exec: &WasmPostExecution, host_call_result: &[u8]) -> WasmRunResult;
+}
+
+pub enum WasmRunResult {
+    Return { output: Bytes },
+    Trap { reason: String },
+    /// The module made a host import call (e.g. a cross-contract `call`)
+    /// that needs to be routed through `FuzzHost` before the instance can
+    /// continue.
+    HostCall { post_exec: WasmPostExecution, target: EVMAddress, input: Bytes },
+}
+
+/// Export name the Stylus/eWASM entrypoint convention uses; `execute`
+/// calls this export the same way `EVMExecutor::execute_abi` always enters
+/// bytecode at its first opcode.
+pub const WASM_ENTRYPOINT: &str = "user_entrypoint";
+
+impl<R, D, I, S, CI> WasmExecutor<R, D, I, S, CI>
+where
+    R: WasmRuntime,
+    D: HostDispatch,
+    I: VMInputT<EVMState, EVMAddress, CI> + 'static,
+    S: State
+        + HasRand
+        + HasCorpus<I>
+        + HasItyState<EVMAddress, EVMState, CI>
+        + HasMetadata
+        + HasCaller<EVMAddress>
+        + HasCurrentInputIdx
+        + Default
+        + Clone
+        + Debug
+        + 'static,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde + 'static,
+{
+    pub fn new(deployer: EVMAddress, engine: R, dispatch: D) -> Self {
+        Self {
+            modules: Default::default(),
+            deployer,
+            engine,
+            dispatch,
+            state_changed: false,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Run `export` of the module deployed at `address` against `calldata`,
+    /// looping on `WasmRunResult::HostCall` the same way
+    /// `EVMExecutor::execute_abi` loops on `ControlLeak`: each host call's
+    /// `target`/`input` is handed to `self.dispatch` (normally `FuzzHost`,
+    /// which resolves `target` to whichever engine hosts it - this same
+    /// `WasmExecutor` or an `EVMExecutor` - and runs the call), and its
+    /// return data resumes the paused instance, until the module returns or
+    /// traps.
+    pub fn execute_export(
+        &mut self,
+        address: &EVMAddress,
+        export: &str,
+        calldata: &[u8],
+    ) -> Option<Bytes> {
+        let module = self.modules.get(address)?.clone();
+        let mut run_result = self.engine.call(&module, export, calldata);
+        loop {
+            match run_result {
+                WasmRunResult::Return { output } => return Some(output),
+                WasmRunResult::Trap { reason } => {
+                    println!("wasm trap @ {:?}: {}", address, reason);
+                    return None;
+                }
+                WasmRunResult::HostCall { post_exec, target, input } => {
+                    let result = self.dispatch.dispatch(target, input);
+                    self.state_changed = true;
+                    run_result = self.engine.resume(&post_exec, &result);
+                }
+            }
+        }
+    }
+}
+
+impl<R, D, Code, By, I, S, CI> GenericVM<EVMState, Code, By, EVMAddress, Vec<u8>, I, S, CI>
+    for WasmExecutor<R, D, I, S, CI>
+where
+    R: WasmRuntime,
+    D: HostDispatch,
+    Code: Into<WasmModule>,
+    I: VMInputT<EVMState, EVMAddress, CI> + 'static,
+    S: State
+        + HasRand
+        + HasCorpus<I>
+        + HasItyState<EVMAddress, EVMState, CI>
+        + HasMetadata
+        + HasCaller<EVMAddress>
+        + HasCurrentInputIdx
+        + Default
+        + Clone
+        + Debug
+        + 'static,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde + 'static,
+{
+    fn deploy(
+        &mut self,
+        code: Code,
+        _constructor_args: Option<By>,
+        deployed_address: EVMAddress,
+        _state: &mut S,
+    ) -> Result<EVMAddress, DeployError> {
+        self.modules.insert(deployed_address, code.into());
+        Ok(deployed_address)
+    }
+
+    fn execute(
+        &mut self,
+        input: &I,
+        _state: &mut S,
+    ) -> ExecutionResult<EVMAddress, EVMState, Vec<u8>, CI>
+    where
+        EVMState: VMStateT,
+        CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde + 'static,
+    {
+        let address = input.get_contract();
+        let mut calldata = input.to_bytes();
+        if calldata.is_empty() {
+            calldata = input.get_direct_data();
+        }
+
+        let vm_state = unsafe {
+            input
+                .get_state()
+                .as_any()
+                .downcast_ref_unchecked::<EVMState>()
+                .clone()
+        };
+
+        match self.execute_export(&address, WASM_ENTRYPOINT, &calldata) {
+            Some(output) => ExecutionResult {
+                output: output.to_vec(),
+                reverted: false,
+                new_state: StagedVMState::new_with_state(vm_state),
+                additional_info: None,
+            },
+            // A trap or an unknown `address` both mean this call didn't
+            // produce output - reported as reverted rather than silently
+            // returning a clean empty result, so callers can tell "this
+            // call failed" from "this call legitimately returned nothing".
+            None => ExecutionResult {
+                output: Vec::new(),
+                reverted: true,
+                new_state: StagedVMState::new_with_state(vm_state),
+                additional_info: None,
+            },
+        }
+    }
+
+    fn state_changed(&self) -> bool {
+        self.state_changed
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -16,7 +16,9 @@ use libafl::{
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
     cell::RefCell,
+    collections::hash_map::DefaultHasher,
     fmt::{Debug, Formatter},
+    hash::{Hash, Hasher},
     marker::PhantomData,
     ops::Deref,
     rc::Rc,
@@ -104,8 +106,10 @@ where
         Ok(())
     }
 
-    /// Called after every execution.
-    /// It executes the producers and then oracles after each successful execution.
+    /// Called after every execution, including a top-level transaction that
+    /// reverted (oracles can still inspect its pre-revert substate via
+    /// `OracleCtx::reverted`).
+    /// It executes the producers and then oracles after each execution.
     /// Returns true if any of the oracle returns true.
     fn is_interesting<EMI, OT>(
         &mut self,
@@ -119,9 +123,7 @@ where
         EMI: EventFirer<I>,
         OT: ObserversTuple<I, S>,
     {
-        if state.get_execution_result().reverted {
-            return Ok(false);
-        }
+        let reverted = state.get_execution_result().reverted;
         {
             if !state.has_metadata::<BugMetadata>() {
                 state.metadata_mut().insert(BugMetadata::default());
@@ -135,17 +137,25 @@ where
                 .clear();
         }
 
-        // set up oracle context
+        // set up oracle context. We no longer bail out here on a reverted
+        // top-level transaction: `OracleCtx::reverted` plus whatever
+        // revert-substate journaling `VS` keeps (e.g. `EVMState::reverted_substate`)
+        // lets oracles still inspect the pre-revert substate - partial state
+        // changes and events emitted before the revert unwound everything -
+        // instead of discarding it outright.
         let mut oracle_ctx: OracleCtx<VS, Addr, Code, By, SlotTy, Out, I, S, CI> =
             OracleCtx::new(state, input.get_state(), &mut self.executor, input);
 
         let mut is_any_bug_hit = false;
-        let has_post_exec = oracle_ctx
-            .fuzz_state
-            .get_execution_result()
-            .new_state
-            .state
-            .has_post_execution();
+        // a reverted transaction never leaks control, so it can't have a
+        // post-execution continuation to finish
+        let has_post_exec = !reverted
+            && oracle_ctx
+                .fuzz_state
+                .get_execution_result()
+                .new_state
+                .state
+                .has_post_execution();
 
         // execute oracles and update stages if needed
         for idx in 0..self.oracle.len() {
@@ -196,3 +206,159 @@ where
         Ok(())
     }
 }
+
+/// Runs every input through two independently-configured [`GenericVM`]s
+/// (e.g. two hardfork rulesets, or an optimized executor checked against a
+/// reference one) and reports a bug whenever their observable outcomes
+/// diverge: the `reverted` flag, the `Out` return value, or the
+/// post-execution `VS`'s `VMStateT::get_hash` (for `EVMState`, that hashes
+/// contracts in address order but each contract's slots in `HashMap`
+/// iteration order, and covers no balances). Unlike
+/// [`OracleFeedback`], which reads whatever the fuzzer's own executor just
+/// produced out of `HasExecutionResult`, this feedback drives `executor_a`
+/// and `executor_b` itself so the two runs are genuinely independent.
+pub struct DifferentialOracleFeedback<VS, Addr, Code, By, SlotTy, Out, I, S, CI>
+where
+    I: VMInputT<VS, Addr, CI>,
+    VS: Default + VMStateT,
+    Addr: Serialize + DeserializeOwned + Debug + Clone,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde,
+{
+    executor_a: Rc<RefCell<dyn GenericVM<VS, Code, By, Addr, SlotTy, Out, I, S, CI>>>,
+    executor_b: Rc<RefCell<dyn GenericVM<VS, Code, By, Addr, SlotTy, Out, I, S, CI>>>,
+    phantom: PhantomData<Out>,
+}
+
+impl<VS, Addr, Code, By, SlotTy, Out, I, S, CI> Debug
+    for DifferentialOracleFeedback<VS, Addr, Code, By, SlotTy, Out, I, S, CI>
+where
+    I: VMInputT<VS, Addr, CI>,
+    VS: Default + VMStateT,
+    Addr: Serialize + DeserializeOwned + Debug + Clone,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DifferentialOracleFeedback").finish()
+    }
+}
+
+impl<VS, Addr, Code, By, SlotTy, Out, I, S, CI> Named
+    for DifferentialOracleFeedback<VS, Addr, Code, By, SlotTy, Out, I, S, CI>
+where
+    I: VMInputT<VS, Addr, CI>,
+    VS: Default + VMStateT,
+    Addr: Serialize + DeserializeOwned + Debug + Clone,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde,
+{
+    fn name(&self) -> &str {
+        "DifferentialOracleFeedback"
+    }
+}
+
+impl<VS, Addr, Code, By, SlotTy, Out, I, S, CI>
+    DifferentialOracleFeedback<VS, Addr, Code, By, SlotTy, Out, I, S, CI>
+where
+    I: VMInputT<VS, Addr, CI>,
+    VS: Default + VMStateT,
+    Addr: Serialize + DeserializeOwned + Debug + Clone,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde,
+{
+    /// Create a new [`DifferentialOracleFeedback`] comparing `executor_a`
+    /// against `executor_b`.
+    pub fn new(
+        executor_a: Rc<RefCell<dyn GenericVM<VS, Code, By, Addr, SlotTy, Out, I, S, CI>>>,
+        executor_b: Rc<RefCell<dyn GenericVM<VS, Code, By, Addr, SlotTy, Out, I, S, CI>>>,
+    ) -> Self {
+        Self {
+            executor_a,
+            executor_b,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<VS, Addr, Code, By, SlotTy, Out, I, S, CI> Feedback<I, S>
+    for DifferentialOracleFeedback<VS, Addr, Code, By, SlotTy, Out, I, S, CI>
+where
+    S: State
+        + HasClientPerfMonitor
+        + HasExecutionResult<Addr, VS, Out, CI>
+        + HasCorpus<I>
+        + HasMetadata
+        + 'static,
+    I: VMInputT<VS, Addr, CI> + 'static,
+    VS: Default + VMStateT,
+    Addr: Serialize + DeserializeOwned + Debug + Clone,
+    Out: Default + PartialEq,
+    CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde,
+{
+    /// Just a wrapper around two stateless executors, nothing to initialize.
+    fn init_state(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Replays `input` through `executor_a` and `executor_b` and flags a bug
+    /// the moment their outcomes disagree.
+    fn is_interesting<EMI, OT>(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EMI,
+        input: &I,
+        _observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EMI: EventFirer<I>,
+        OT: ObserversTuple<I, S>,
+    {
+        let result_a = self.executor_a.deref().borrow_mut().execute(input, state);
+        let result_b = self.executor_b.deref().borrow_mut().execute(input, state);
+
+        let hash_a = result_a.new_state.state.get_hash();
+        let hash_b = result_b.new_state.state.get_hash();
+
+        let diverged =
+            result_a.reverted != result_b.reverted || result_a.output != result_b.output || hash_a != hash_b;
+
+        if !diverged {
+            return Ok(false);
+        }
+
+        if !state.has_metadata::<BugMetadata>() {
+            state.metadata_mut().insert(BugMetadata::default());
+        }
+
+        // the divergence itself (which flag disagreed, and the two state
+        // hashes) identifies the bug, so re-hitting the same mismatch on a
+        // different input doesn't get reported twice
+        let mut hasher = DefaultHasher::new();
+        result_a.reverted.hash(&mut hasher);
+        result_b.reverted.hash(&mut hasher);
+        hash_a.hash(&mut hasher);
+        hash_b.hash(&mut hasher);
+        let bug_idx = hasher.finish();
+
+        let metadata = state.metadata_mut().get_mut::<BugMetadata>().unwrap();
+        if metadata.known_bugs.contains(&bug_idx) {
+            return Ok(false);
+        }
+        metadata.known_bugs.insert(bug_idx);
+        metadata.current_bugs.push(bug_idx);
+
+        Ok(true)
+    }
+
+    // dummy method
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    // dummy method
+    fn discard_metadata(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -12,13 +12,15 @@ use libafl::{
 
 use crate::{
     cairo::{
+        abi::Conversion,
         config::CairoFuzzConfig,
         corpus_initializer::CairoCorpusInitializer,
-        input::{CairoInput, ConciseCairoInput},
+        input::{CairoDictMetadata, CairoInput, ConciseCairoInput},
         oracle::TypedBugOracle,
         types::{CairoAddress, CairoFuzzMutator, CairoFuzzState, Function},
         vm::{CairoExecutor, CairoState},
     },
+    control_socket::ControlSocketStage,
     evm::host::JMP_MAP,
     executor::FuzzExecutor,
     feedback::OracleFeedback,
@@ -27,6 +29,7 @@ use crate::{
     scheduler::SortedDroppingScheduler,
 };
 
+use libafl::state::HasMetadata;
 use serde_json::Value;
 
 pub fn cairo_fuzzer(
@@ -70,7 +73,7 @@ pub fn cairo_fuzzer(
         CairoFuzzState,
         CairoState,
         ConciseCairoInput,
-    > = CairoExecutor::new(program, function);
+    > = CairoExecutor::new(program, function, config.work_dir.clone());
 
     let mut corpus_initializer = CairoCorpusInitializer::new(
         &mut cairo_executor,
@@ -80,12 +83,29 @@ pub fn cairo_fuzzer(
         config.work_dir.clone(),
     );
 
-    corpus_initializer.initialize(config.func_name);
+    corpus_initializer.initialize(config.corpus_seed_file.as_deref());
+
+    // merge a user-supplied dictionary in on top of the constants
+    // `initialize` already scraped from the compiled program
+    if let Some(dict_path) = &config.dict {
+        let file_dict = CairoDictMetadata::from_file(dict_path);
+        match state.metadata_mut().get_mut::<CairoDictMetadata>() {
+            Some(meta) => meta.extend(file_dict.felts),
+            None => {
+                state.metadata_mut().insert(file_dict);
+            }
+        }
+    }
 
     let cairo_executor_ref = Rc::new(RefCell::new(cairo_executor));
 
     let objective = OracleFeedback::new(&mut oracles, cairo_executor_ref.clone());
 
+    // Lets an operator `stats`/`dump`/`stop` a long-running campaign over a
+    // Unix domain socket at `{work_dir}/control.sock` without ever blocking
+    // the loop (see `control_socket::ControlSocket::ready`).
+    let control_socket_stage = ControlSocketStage::new(config.work_dir.clone());
+
     let mut fuzzer = ItyFuzzer::new(
         scheduler,
         &infant_scheduler,
@@ -97,7 +117,7 @@ pub fn cairo_fuzzer(
     let mutator: CairoFuzzMutator<'_> = FuzzMutator::new(&infant_scheduler);
 
     let std_stage = StdMutationalStage::new(mutator);
-    let mut stages = tuple_list!(std_stage);
+    let mut stages = tuple_list!(std_stage, control_socket_stage);
 
     let mut executor = FuzzExecutor::new(cairo_executor_ref.clone(), tuple_list!(jmp_observer));
 
@@ -109,16 +129,24 @@ pub fn cairo_fuzzer(
         .expect("Fuzzing failed");
 }
 
-/// Function that returns a vector of the args type of the function the user want to fuzz
-fn get_type_args(members: &Value) -> Vec<String> {
-    let mut type_args = Vec::<String>::new();
-    for (_, value) in members
+/// Function that resolves the args type of the function the user wants to
+/// fuzz, one `Conversion` per `Args` member in declaration (offset) order,
+/// recursively resolving any member that names a struct against
+/// `identifiers` instead of flattening everything to a raw `cairo_type`
+/// string.
+fn get_type_args(members: &Value, identifiers: &Value) -> Vec<Conversion> {
+    let mut entries: Vec<(i64, Conversion)> = members
         .as_object()
         .expect("Failed get member type_args as object from json")
-    {
-        type_args.push(value["cairo_type"].to_string().replace("\"", ""));
-    }
-    return type_args;
+        .values()
+        .map(|value| {
+            let offset = value.get("offset").and_then(|o| o.as_i64()).unwrap_or(0);
+            let cairo_type = value["cairo_type"].as_str().unwrap_or("felt");
+            (offset, Conversion::resolve(cairo_type, identifiers))
+        })
+        .collect();
+    entries.sort_by_key(|(offset, _)| *offset);
+    entries.into_iter().map(|(_, conversion)| conversion).collect()
 }
 
 /// Function to parse cairo json artifact
@@ -149,7 +177,7 @@ pub fn parse_json(data: &String, function_name: &String) -> Option<Function> {
                             num_args: size
                                 .as_u64()
                                 .expect("Failed to get number of arguments from json"),
-                            type_args: get_type_args(members),
+                            type_args: get_type_args(members, identifiers),
                         });
                     }
                 }
@@ -7,19 +7,28 @@ use crate::{
         host::{FuzzHost, ACTIVE_MATCH_EXT_CALL, JMP_MAP, WRITE_RELATIONSHIPS},
         input::{ConciseEVMInput, EVMInput},
         middlewares::{
+            call_tracer::CallTracer,
             coverage::Coverage,
+            dataflow_tracer::DataflowTracer,
             middleware::Middleware,
-            sha3_bypass::{Sha3Bypass, Sha3TaintAnalysis},
+            sha3_bypass::{self, Sha3Bypass, Sha3TaintAnalysis},
         },
         mutator::FuzzMutator,
-        oracles::typed_bug::TypedBugOracle,
+        oracles::{
+            gas_usage::{GasGriefingOracle, GasUsageOracle},
+            typed_bug::TypedBugOracle,
+        },
         srcmap::parser::BASE_PATH,
         types::{fixed_address, EVMAddress, EVMFuzzMutator, EVMFuzzState},
         vm::{EVMExecutor, EVMState},
     },
+    control_socket::ControlSocketStage,
+    dataflow::{DataflowStage, DATAFLOW_ENABLED},
+    evm::config::FuzzerTypes,
     executor::FuzzExecutor,
     feedback::OracleFeedback,
-    fuzzer::ItyFuzzer,
+    fuzzer::{ItyFuzzer, REPLAY},
+    generic_vm::vm_executor::GenericVM,
     oracle::BugMetadata,
     scheduler::SortedDroppingScheduler,
 };
@@ -54,6 +63,10 @@ pub fn evm_fuzzer(
         std::fs::create_dir(path).unwrap();
     }
 
+    unsafe {
+        DATAFLOW_ENABLED = config.fuzzer_type == FuzzerTypes::DATAFLOW;
+    }
+
     let monitor = SimpleMonitor::new(|s| println!("{}", s));
     let mut mgr = SimpleEventManager::new(monitor);
     let infant_scheduler = SortedDroppingScheduler::new();
@@ -70,6 +83,7 @@ pub fn evm_fuzzer(
         unsafe {
             WRITE_RELATIONSHIPS = true;
         }
+        crate::relationship_graph::init_relationship_graph(config.work_dir.clone());
     }
 
     unsafe {
@@ -77,24 +91,51 @@ pub fn evm_fuzzer(
         BASE_PATH = config.base_path;
     }
 
-    let sha3_taint = Rc::new(RefCell::new(Sha3TaintAnalysis::new()));
+    // Preimages solved in an earlier run of this campaign (`work_dir`)
+    // carry over so hash guards already cracked don't need rediscovering.
+    let sha3_preimages = sha3_bypass::load_preimages(&config.work_dir);
+    let sha3_taint = Rc::new(RefCell::new(Sha3TaintAnalysis::new(sha3_preimages.clone())));
 
     if config.sha3_bypass {
+        // `Sha3TaintAnalysis` itself has to be registered too - it's the
+        // middleware that actually runs `TaintEngine::on_step`, populates
+        // `jumpi.tainted_jumpi`/`resolvable_jumpi`, and records SHA3
+        // preimages. `Sha3Bypass` only reads those maps; it never
+        // populates them.
+        fuzz_host.add_middlewares(sha3_taint.clone());
         fuzz_host.add_middlewares(Rc::new(RefCell::new(Sha3Bypass::new(sha3_taint.clone()))));
     }
 
+    // Only builds the call-tree/opcode trace when replaying a dumped
+    // reproduction; a normal fuzzing run never reads it.
+    fuzz_host.add_middlewares(Rc::new(RefCell::new(CallTracer::new(unsafe { REPLAY }))));
+
+    if config.fuzzer_type == FuzzerTypes::DATAFLOW {
+        // Populates `DataflowTrace` from the real execution so
+        // `DataflowStage` has something to compute a mutation mask from -
+        // without this, `DATAFLOW_ENABLED` only ever sees an absent trace.
+        fuzz_host.add_middlewares(Rc::new(RefCell::new(DataflowTracer::new())));
+    }
+
     let mut evm_executor: EVMExecutor<EVMInput, EVMFuzzState, EVMState, ConciseEVMInput> =
         EVMExecutor::new(fuzz_host, deployer);
 
+    // Passed through the generic `GenericVM` interface (see
+    // `EvmVmFactory`/`VmBackend`); `EVMCorpusInitializer` still downcasts
+    // it back to `EVMExecutor` for `set_code` and host-state reads.
     let mut corpus_initializer = EVMCorpusInitializer::new(
-        &mut evm_executor,
+        &mut evm_executor
+            as &mut dyn GenericVM<EVMState, Bytecode, Bytes, EVMAddress, Vec<u8>, EVMInput, EVMFuzzState, ConciseEVMInput>,
         &mut scheduler,
         &infant_scheduler,
         state,
         config.work_dir.clone(),
     );
 
-    let mut artifacts = corpus_initializer.initialize(&mut config.contract_loader.clone());
+    let mut artifacts = corpus_initializer.initialize(
+        &mut config.contract_loader.clone(),
+        config.corpus_seed_file.as_deref(),
+    );
 
     let mut instance_map = ABIAddressToInstanceMap::new();
     artifacts
@@ -134,7 +175,16 @@ pub fn evm_fuzzer(
 
     let std_stage = StdMutationalStage::new(mutator);
 
-    let mut stages = tuple_list!(std_stage);
+    // Recomputes the dataflow mutation mask after every execution; a no-op
+    // outside `FuzzerTypes::DATAFLOW` (see `DATAFLOW_ENABLED`).
+    let dataflow_stage = DataflowStage::new();
+
+    // Lets an operator `stats`/`dump`/`stop` a long-running campaign over a
+    // Unix domain socket at `{work_dir}/control.sock` without ever blocking
+    // the loop (see `control_socket::ControlSocket::ready`).
+    let control_socket_stage = ControlSocketStage::new(config.work_dir.clone());
+
+    let mut stages = tuple_list!(std_stage, dataflow_stage, control_socket_stage);
     let mut executor = FuzzExecutor::new(evm_executor_ref.clone(), tuple_list!(jmp_observer));
     let mut oracles = config.oracle;
 
@@ -144,10 +194,17 @@ pub fn evm_fuzzer(
         ))));
     }
 
+    if config.gas_oracle {
+        oracles.push(Rc::new(RefCell::new(GasUsageOracle::new(config.gas_threshold))));
+        oracles.push(Rc::new(RefCell::new(GasGriefingOracle)));
+    }
+
     state.add_metadata(BugMetadata::new());
 
     let objective = OracleFeedback::new(&mut oracles, evm_executor_ref.clone());
 
+    let work_dir = config.work_dir.clone();
+
     let mut fuzzer = ItyFuzzer::new(
         scheduler,
         &infant_scheduler,
@@ -158,4 +215,6 @@ pub fn evm_fuzzer(
     fuzzer
         .fuzz_loop(&mut stages, &mut executor, state, &mut mgr)
         .expect("Fuzzing failed");
+
+    sha3_bypass::save_preimages(&work_dir, &sha3_preimages);
 }
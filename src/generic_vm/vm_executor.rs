@@ -9,6 +9,56 @@ use std::fmt::Debug;
 
 pub const MAP_SIZE: usize = 4096;
 
+/// Why [`GenericVM::deploy`] didn't produce a usable contract, shared
+/// across backends rather than re-invented per engine (a Cairo/WASM
+/// backend that doesn't distinguish these failure modes as finely just
+/// reports [`DeployError::Halted`]).
+#[derive(Debug, Clone)]
+pub enum DeployError {
+    /// The constructor executed and explicitly reverted; `revert_data` is
+    /// the raw revert payload (an ABI-encoded `Error(string)`/custom error
+    /// when the compiler emitted one, empty otherwise) so a caller can
+    /// still decode *why* rather than just that it failed.
+    ConstructorReverted { revert_data: Vec<u8> },
+    /// The constructor ran out of gas before completing.
+    OutOfGas,
+    /// The constructor's memory usage exceeded the configured memory
+    /// limit (see `evm::vm::MEM_LIMIT`).
+    MemoryLimitExceeded,
+    /// The constructor halted some other way than a normal `Return`
+    /// (e.g. an invalid opcode, a stack underflow) - `reason` is the
+    /// engine's own description of the halt.
+    Halted { reason: String },
+}
+
+impl std::fmt::Display for DeployError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeployError::ConstructorReverted { revert_data } => {
+                if revert_data.is_empty() {
+                    write!(f, "constructor reverted")
+                } else {
+                    write!(
+                        f,
+                        "constructor reverted: 0x{}",
+                        revert_data
+                            .iter()
+                            .map(|b| format!("{:02x}", b))
+                            .collect::<String>()
+                    )
+                }
+            }
+            DeployError::OutOfGas => write!(f, "constructor ran out of gas"),
+            DeployError::MemoryLimitExceeded => {
+                write!(f, "constructor exceeded the memory limit")
+            }
+            DeployError::Halted { reason } => write!(f, "constructor halted: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DeployError {}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExecutionResult<Addr, VS, Out, CI>
 where
@@ -42,13 +92,16 @@ where
 }
 
 pub trait GenericVM<VS, Code, By, Addr, Out, I, S, CI> {
+    /// `Err` carries the reason deployment didn't produce a usable
+    /// contract at `deployed_address` (constructor revert, out-of-gas,
+    /// ...) instead of collapsing every failure mode into a bare `None`.
     fn deploy(
         &mut self,
         code: Code,
         constructor_args: Option<By>,
         deployed_address: Addr,
         state: &mut S,
-    ) -> Option<Addr>;
+    ) -> Result<Addr, DeployError>;
     fn execute(&mut self, input: &I, state: &mut S) -> ExecutionResult<Addr, VS, Out, CI>
     where
         VS: VMStateT,
@@ -59,3 +112,16 @@ pub trait GenericVM<VS, Code, By, Addr, Out, I, S, CI> {
     fn state_changed(&self) -> bool;
     fn as_any(&mut self) -> &mut dyn std::any::Any;
 }
+
+/// Produces a boxed [`GenericVM`] backend, following the VM-factory /
+/// `--jitvm`-style pluggable-VM pattern: picking a different `VmFactory`
+/// implementation (e.g. a JIT/fast interpreter, or a symbolic engine) swaps
+/// the execution engine behind the `GenericVM` interface this produces.
+/// `EVMCorpusInitializer` downcasts that interface to `EVMExecutor` for
+/// `set_code` and host-state reads, so corpus initialization works only
+/// with the EVM backend.
+pub trait VmFactory<VS, Code, By, Addr, Out, I, S, CI> {
+    /// Build the backend this factory wraps, handing over the deployer
+    /// address it should use to deploy initial contracts as.
+    fn build(self: Box<Self>, deployer: Addr) -> Box<dyn GenericVM<VS, Code, By, Addr, Out, I, S, CI>>;
+}
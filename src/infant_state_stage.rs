@@ -1,6 +1,11 @@
 use libafl::Error;
 use libafl::schedulers::Scheduler;
 use libafl::stages::Stage;
+use libafl::state::HasMetadata;
+use crate::evm::host::WRITE_RELATIONSHIPS;
+use crate::input::ConciseSerde;
+use crate::oracle::BugMetadata;
+use crate::relationship_graph::record_relationship;
 use crate::state::{HasExecutionResult, HasInfantStateState, HasItyState, InfantStateState};
 use crate::state_input::ItyVMState;
 
@@ -15,18 +20,33 @@ impl<SC> InfantStateStage<SC> {
 }
 
 impl<E, EM, S, Z, SC> Stage<E, EM, S, Z> for InfantStateStage<SC>
-    where S: HasItyState + HasExecutionResult,
+    where S: HasItyState + HasExecutionResult + HasMetadata,
           SC: Scheduler<ItyVMState, InfantStateState>{
     fn perform(&mut self, fuzzer: &mut Z, executor: &mut E, state: &mut S, manager: &mut EM, corpus_idx: usize) -> Result<(), Error>
      {
         // add the current VMState to the infant state corpus
         // TODO(shou): add feedback for infant state here
          let new_state = state.get_execution_result();
-         state
+         let trace = new_state.new_state.trace.clone();
+         let new_state_idx = state
              .add_infant_state(
                  &ItyVMState(new_state.new_state.clone()),
                 &self.scheduler
              );
+
+         if unsafe { WRITE_RELATIONSHIPS } {
+             let input_summary = trace
+                 .transactions
+                 .last()
+                 .map(|txn| txn.serialize_string())
+                 .unwrap_or_default();
+             let bug_tags = state
+                 .metadata()
+                 .get::<BugMetadata>()
+                 .map(|meta| meta.corpus_idx_to_bug.clone())
+                 .unwrap_or_default();
+             record_relationship(trace.from_idx, input_summary, new_state_idx, &bug_tags);
+         }
         Ok(())
     }
 }
\ No newline at end of file
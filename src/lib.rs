@@ -4,7 +4,10 @@
 #![feature(trait_alias)]
 
 extern crate core;
+pub mod cache;
 pub mod r#const;
+pub mod control_socket;
+pub mod dataflow;
 pub mod evm;
 pub mod executor;
 pub mod feedback;
@@ -15,7 +18,9 @@ pub mod indexed_corpus;
 pub mod input;
 pub mod mutation_utils;
 pub mod oracle;
+pub mod relationship_graph;
 pub mod scheduler;
 pub mod state;
 pub mod state_input;
+pub mod trace_codec;
 pub mod tracer;
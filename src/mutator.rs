@@ -1,4 +1,6 @@
 use crate::{
+    dataflow::DataflowMaskMetadata,
+    evm::types::{EVMAddress, EVMU256},
     generic_vm::vm_state::VMStateT,
     input::{ConciseSerde, VMInputT},
     state::{HasCaller, HasItyState, InfantStateState},
@@ -13,6 +15,7 @@ use libafl::Error;
 use revm_interpreter::Interpreter;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt::Debug;
 
 /// [`AccessPattern`] records the access pattern of the input during execution. This helps
@@ -33,6 +36,26 @@ pub struct AccessPattern {
     pub gas_limit: bool,
     pub chain_id: bool,
     pub basefee: bool,
+
+    /// EIP-2929 warm/cold tracking, one frame per open call depth:
+    /// `address_frames[0]`/`slot_frames[0]` is the outermost transaction,
+    /// and each sub-call pushes a frame that either merges down (on a
+    /// normal return) or is dropped whole (on revert) via `commit_frame`/
+    /// `revert_frame`, the same discard-or-merge journaling
+    /// `ConcolicHost::push_ctx`/`pop_ctx` do for path constraints.
+    #[serde(skip)]
+    address_frames: Vec<HashSet<EVMAddress>>,
+    #[serde(skip)]
+    slot_frames: Vec<HashSet<(EVMAddress, EVMU256)>>,
+
+    /// Addresses/slots that flipped cold -> warm this run, in touch order -
+    /// a coverage signal the scheduler/feedback can reward to steer toward
+    /// inputs that reach previously-cold storage (i.e. gas-dependent
+    /// branches a warmed-up repeat touch couldn't have taken).
+    #[serde(skip)]
+    pub new_cold_addresses: Vec<EVMAddress>,
+    #[serde(skip)]
+    pub new_cold_slots: Vec<(EVMAddress, EVMU256)>,
 }
 
 impl AccessPattern {
@@ -49,6 +72,10 @@ impl AccessPattern {
             gas_limit: false,
             chain_id: false,
             basefee: false,
+            address_frames: vec![HashSet::new()],
+            slot_frames: vec![HashSet::new()],
+            new_cold_addresses: vec![],
+            new_cold_slots: vec![],
         }
     }
 
@@ -73,6 +100,84 @@ impl AccessPattern {
             _ => {}
         }
     }
+
+    /// Opens a new journal frame on entering a sub-call
+    /// (`CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`/`CREATE`).
+    pub fn push_frame(&mut self) {
+        self.address_frames.push(HashSet::new());
+        self.slot_frames.push(HashSet::new());
+    }
+
+    /// Folds the innermost frame into the one below it on a normal return:
+    /// everything the sub-call warmed up stays warm for the caller.
+    pub fn commit_frame(&mut self) {
+        if let Some(frame) = self.address_frames.pop() {
+            match self.address_frames.last_mut() {
+                Some(parent) => parent.extend(frame),
+                None => self.address_frames.push(frame),
+            }
+        }
+        if let Some(frame) = self.slot_frames.pop() {
+            match self.slot_frames.last_mut() {
+                Some(parent) => parent.extend(frame),
+                None => self.slot_frames.push(frame),
+            }
+        }
+    }
+
+    /// Drops the innermost frame whole on `REVERT`: every address/slot it
+    /// warmed goes back to cold once execution unwinds past it.
+    pub fn revert_frame(&mut self) {
+        self.address_frames.pop();
+        self.slot_frames.pop();
+    }
+
+    fn is_address_warm(&self, addr: &EVMAddress) -> bool {
+        self.address_frames.iter().any(|frame| frame.contains(addr))
+    }
+
+    fn is_slot_warm(&self, addr: &EVMAddress, slot: &EVMU256) -> bool {
+        self.slot_frames.iter().any(|frame| frame.contains(&(*addr, *slot)))
+    }
+
+    /// Marks `addr` as accessed per EIP-2929 and returns whether this was
+    /// its first touch (cold, 2600 gas) rather than an already-warm repeat
+    /// (100 gas).
+    pub fn touch_address(&mut self, addr: EVMAddress) -> bool {
+        if self.is_address_warm(&addr) {
+            return false;
+        }
+        self.address_frames
+            .last_mut()
+            .expect("access pattern has no open frame")
+            .insert(addr);
+        self.new_cold_addresses.push(addr);
+        true
+    }
+
+    /// Marks `(addr, slot)` as accessed per EIP-2929 and returns whether
+    /// this was its first touch (cold, 2100 gas) rather than an
+    /// already-warm repeat (100 gas).
+    pub fn touch_slot(&mut self, addr: EVMAddress, slot: EVMU256) -> bool {
+        if self.is_slot_warm(&addr, &slot) {
+            return false;
+        }
+        self.slot_frames
+            .last_mut()
+            .expect("access pattern has no open frame")
+            .insert((addr, slot));
+        self.new_cold_slots.push((addr, slot));
+        true
+    }
+
+    /// Seeds the outermost frame with an explicit pre-warmed access list
+    /// (e.g. a seeded corpus input whose harness already knows which
+    /// contracts/slots a transaction touches up front), without counting
+    /// the seeded entries as cold->warm transitions.
+    pub fn prewarm(&mut self, addresses: impl IntoIterator<Item = EVMAddress>, slots: impl IntoIterator<Item = (EVMAddress, EVMU256)>) {
+        self.address_frames[0].extend(addresses);
+        self.slot_frames[0].extend(slots);
+    }
 }
 
 /// [`FuzzMutator`] is a mutator that mutates the input based on the ABI and access pattern
@@ -143,10 +248,25 @@ where
 
         let mut already_crossed = false;
 
+        // When `compute_mutation_mask` found calldata bytes still live at a
+        // branch condition or storage write (see `crate::dataflow`), bias
+        // away from the two branches below that don't touch the input's
+        // own bytes at all (infant-state crossover, and the havoc no-op)
+        // and toward `input.mutate`, the only branch that does. This tree's
+        // `EVMInput::mutate` doesn't expose a byte-offset hook for true
+        // per-offset targeting, so this
+        // is the coarsest correct use of the mask available today: mutate
+        // more, not mutate *there* specifically.
+        let dataflow_mask_live = state
+            .metadata()
+            .get::<DataflowMaskMetadata>()
+            .map(|mask| !mask.mutation_mask.is_empty())
+            .unwrap_or(false);
+
         // mutate the input once
         let mut mutator = || -> MutationResult {
             match state.rand_mut().below(100) {
-                0..=5 => {
+                0..=5 if !dataflow_mask_live => {
                     if already_crossed {
                         return MutationResult::Skipped;
                     }
@@ -164,7 +284,7 @@ where
                     input.set_staged_state(new_state, idx);
                     MutationResult::Mutated
                 }
-                11 => MutationResult::Mutated,
+                11 if !dataflow_mask_live => MutationResult::Mutated,
                 _ => input.mutate(state),
             }
         };
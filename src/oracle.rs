@@ -31,6 +31,13 @@ where
     pub pre_state: &'a VS,
     /// The VMState after the execution
     pub post_state: VS,
+    /// Whether the transaction that produced `post_state` reverted. An
+    /// oracle that still wants to look at what happened on the way down
+    /// (e.g. "a privileged event was emitted before the call reverted")
+    /// combines this with whatever revert-substate bookkeeping `VS` itself
+    /// exposes (e.g. `EVMState::reverted_substate`), since `post_state` for
+    /// a reverted transaction is already rolled back to the pre-call state.
+    pub reverted: bool,
     /// The metadata of the oracle
     pub metadata: SerdeAnyMap,
     /// The executor
@@ -63,6 +70,7 @@ where
     ) -> Self {
         Self {
             post_state: fuzz_state.get_execution_result().new_state.state.clone(),
+            reverted: fuzz_state.get_execution_result().reverted,
             fuzz_state,
             pre_state,
             metadata: SerdeAnyMap::new(),
@@ -100,14 +108,16 @@ where
     Out: Default,
     CI: Serialize + DeserializeOwned + Debug + Clone + ConciseSerde,
 {
-    /// Transition function, called everytime after non-reverted execution
+    /// Transition function, called everytime after execution, whether the
+    /// top-level transaction reverted or not (see [`OracleCtx::reverted`])
     fn transition(
         &self,
         ctx: &mut OracleCtx<VS, Addr, Code, By, Loc, SlotTy, Out, I, S, CI>,
         stage: u64,
     ) -> u64;
 
-    /// Oracle function, called everytime after non-reverted execution
+    /// Oracle function, called everytime after execution, whether the
+    /// top-level transaction reverted or not (see [`OracleCtx::reverted`])
     /// Returns Some(bug_idx) if the oracle is violated
     fn oracle(
         &self,
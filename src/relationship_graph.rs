@@ -0,0 +1,196 @@
+/// Graphviz DOT export of the infant-state relationship graph.
+///
+/// `InfantStateStage::perform` adds every post-execution [`crate::state_input::ItyVMState`]
+/// into the infant corpus, but until now the parent -> child transitions
+/// between those states were never materialized anywhere a human could
+/// inspect them. This module accumulates `(parent_idx, input_summary,
+/// new_state_idx)` edges as they happen and periodically renders them as a
+/// `digraph` to `work_dir/relationship.dot`, so the fuzzer's
+/// state-exploration tree can be opened directly in Graphviz.
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// The DOT graph kind this module emits. Only `Digraph` is used today;
+/// the variant exists so a caller who wants an undirected view of the
+/// same edges (e.g. deduping bidirectional seed re-use) isn't blocked on
+/// a rewrite of the writer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Digraph,
+    Graph,
+}
+
+impl Kind {
+    fn keyword(&self) -> &'static str {
+        match self {
+            Kind::Digraph => "digraph",
+            Kind::Graph => "graph",
+        }
+    }
+
+    fn edge_op(&self) -> &'static str {
+        match self {
+            Kind::Digraph => "->",
+            Kind::Graph => "--",
+        }
+    }
+}
+
+/// One parent -> child transition: `new_state_idx` is the infant-corpus
+/// index `add_infant_state` just assigned, `parent_idx` is the infant
+/// state it was derived from (`None` for the initial seed states, whose
+/// `TxnTrace::from_idx` is also `None`), and `input_summary` is the
+/// concise rendering (selector / function name) of the last transaction
+/// that produced it.
+#[derive(Clone, Debug)]
+pub struct RelationshipEdge {
+    pub parent_idx: Option<usize>,
+    pub input_summary: String,
+    pub new_state_idx: usize,
+}
+
+/// Re-dump after this many new edges accumulate, so a long campaign
+/// doesn't rewrite `relationship.dot` on every single infant state add.
+const DUMP_INTERVAL: usize = 50;
+
+/// Accumulates [`RelationshipEdge`]s across a fuzzing campaign and renders
+/// them as a Graphviz `digraph`.
+#[derive(Clone, Debug, Default)]
+pub struct RelationshipGraph {
+    pub edges: Vec<RelationshipEdge>,
+    work_dir: String,
+    dumped_at: usize,
+    /// Latest snapshot of `BugMetadata::corpus_idx_to_bug`, used to tag
+    /// nodes with which bugs (if any) were hit from that state.
+    bug_tags: HashMap<usize, Vec<u64>>,
+}
+
+impl RelationshipGraph {
+    pub fn new(work_dir: String) -> Self {
+        Self {
+            edges: Vec::new(),
+            work_dir,
+            dumped_at: 0,
+            bug_tags: HashMap::new(),
+        }
+    }
+
+    /// Records one edge and periodically flushes to disk (see
+    /// [`DUMP_INTERVAL`]). `bug_tags` is `BugMetadata::corpus_idx_to_bug`
+    /// at the time of recording; it's cheap to clone and kept fresh so the
+    /// next dump's node labels reflect the bugs known so far.
+    pub fn record(
+        &mut self,
+        parent_idx: Option<usize>,
+        input_summary: String,
+        new_state_idx: usize,
+        bug_tags: &HashMap<usize, Vec<u64>>,
+    ) {
+        self.edges.push(RelationshipEdge {
+            parent_idx,
+            input_summary,
+            new_state_idx,
+        });
+        self.bug_tags = bug_tags.clone();
+        if self.edges.len() - self.dumped_at >= DUMP_INTERVAL {
+            self.dump();
+        }
+    }
+
+    /// Renders the accumulated edges as a DOT `digraph`. Nodes are labeled
+    /// by infant-state corpus index plus, if `bug_tags` knows of any bugs
+    /// hit from that state, a short `bugs: ...` tag; edges are labeled by
+    /// the concise input that caused the transition.
+    pub fn to_dot(&self) -> String {
+        let kind = Kind::Digraph;
+        let mut out = String::new();
+        out.push_str(&format!("{} relationship {{\n", kind.keyword()));
+
+        let mut node_ids: HashSet<usize> = HashSet::new();
+        for edge in &self.edges {
+            if let Some(parent) = edge.parent_idx {
+                node_ids.insert(parent);
+            }
+            node_ids.insert(edge.new_state_idx);
+        }
+        let mut sorted_nodes: Vec<usize> = node_ids.into_iter().collect();
+        sorted_nodes.sort_unstable();
+
+        for idx in sorted_nodes {
+            let tag = match self.bug_tags.get(&idx) {
+                Some(bugs) if !bugs.is_empty() => format!(
+                    ", bugs: {}",
+                    bugs.iter()
+                        .map(|b| b.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+                _ => String::new(),
+            };
+            out.push_str(&format!("  s{idx} [label=\"#{idx}{tag}\"];\n"));
+        }
+
+        for edge in &self.edges {
+            let from = edge
+                .parent_idx
+                .map(|p| format!("s{p}"))
+                .unwrap_or_else(|| "seed".to_string());
+            let to = edge.new_state_idx;
+            let label = edge.input_summary.replace('"', "'");
+            let op = kind.edge_op();
+            out.push_str(&format!("  {from} {op} s{to} [label=\"{label}\"];\n"));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Writes the current graph to `<work_dir>/relationship.dot`,
+    /// overwriting any previous dump - this is meant to be opened fresh in
+    /// Graphviz (`dot -Tpng relationship.dot -o relationship.png`), not
+    /// appended to.
+    pub fn dump(&mut self) {
+        let path = Path::new(&self.work_dir).join("relationship.dot");
+        let dot = self.to_dot();
+        if let Ok(mut file) = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+        {
+            let _ = file.write_all(dot.as_bytes());
+        }
+        self.dumped_at = self.edges.len();
+    }
+}
+
+/// Set from `Config::write_relationship` at fuzzer startup (see
+/// `evm_fuzzer`), the same unsafe-static-toggle pattern `DATAFLOW_ENABLED`/
+/// `WRITE_RELATIONSHIPS` use elsewhere for cheap optional behavior every
+/// fuzz loop iteration checks.
+pub static mut RELATIONSHIP_GRAPH: Option<RelationshipGraph> = None;
+
+/// Called once at fuzzer startup when `write_relationship` is set.
+pub fn init_relationship_graph(work_dir: String) {
+    unsafe {
+        RELATIONSHIP_GRAPH = Some(RelationshipGraph::new(work_dir));
+    }
+}
+
+/// Records one parent -> child infant-state transition, called from
+/// `InfantStateStage::perform`. A no-op if `init_relationship_graph` was
+/// never called (i.e. `write_relationship` is off).
+pub fn record_relationship(
+    parent_idx: Option<usize>,
+    input_summary: String,
+    new_state_idx: usize,
+    bug_tags: &HashMap<usize, Vec<u64>>,
+) {
+    unsafe {
+        if let Some(graph) = RELATIONSHIP_GRAPH.as_mut() {
+            graph.record(parent_idx, input_summary, new_state_idx, bug_tags);
+        }
+    }
+}
@@ -1,11 +1,14 @@
 /// Implements wrappers around VMState that can be stored in a corpus.
 use libafl::inputs::Input;
+use libafl::Error;
 
 use std::fmt::Debug;
+use std::path::Path;
 
 use crate::generic_vm::vm_state::VMStateT;
 
 use crate::input::ConciseSerde;
+use crate::trace_codec::{DefaultTraceCodec, TraceCodec};
 use crate::tracer::TxnTrace;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -63,4 +66,23 @@ where
     fn generate_name(&self, idx: usize) -> String {
         format!("input-{}.state", idx)
     }
+
+    /// Overridden (rather than relying on the default postcard-to-disk
+    /// behavior) so every `StagedVMState` on-disk write goes through
+    /// [`DefaultTraceCodec`] - a no-op unless the `compressed-corpus`
+    /// feature is on, in which case the serialized state/trace is
+    /// bzip2+base64-compressed before it hits disk.
+    fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let serialized =
+            serde_json::to_vec(self).map_err(|e| Error::serialize(e.to_string()))?;
+        std::fs::write(path, DefaultTraceCodec.encode(&serialized))
+            .map_err(|e| Error::os_error(e, "failed to write StagedVMState to disk"))
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let raw = std::fs::read(path.as_ref())
+            .map_err(|e| Error::os_error(e, "failed to read StagedVMState from disk"))?;
+        let decoded = DefaultTraceCodec.decode(&raw);
+        serde_json::from_slice(&decoded).map_err(|e| Error::serialize(e.to_string()))
+    }
 }
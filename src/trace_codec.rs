@@ -0,0 +1,85 @@
+/// Pluggable compression layer for on-disk trace/corpus persistence.
+///
+/// A long `full_trace` campaign keeps deep `TxnTrace`/`StagedVMState`
+/// ancestor chains around in the infant-state corpus, and each one gets
+/// written to disk - with `full_trace` on, that's a lot of mostly
+/// self-similar JSON. [`TraceCodec`] is the seam between "bytes this type
+/// would serialize to anyway" and "bytes actually written to disk", so a
+/// campaign can opt into compressing that path without `StagedVMState`,
+/// `TxnTrace`, or any of their callers knowing or caring which codec is
+/// active - the same way [`crate::evm::snapshot::CampaignSnapshot`] keeps
+/// its encoding picked by a caller-supplied format rather than baked into
+/// the type.
+use std::fmt::Debug;
+
+/// Encodes/decodes an already-serialized byte buffer before it's written
+/// to (or after it's read from) disk. Implementations must round-trip:
+/// `codec.decode(&codec.encode(bytes)) == bytes`.
+pub trait TraceCodec: Debug {
+    fn encode(&self, bytes: &[u8]) -> Vec<u8>;
+    fn decode(&self, bytes: &[u8]) -> Vec<u8>;
+}
+
+/// The default codec: a no-op passthrough, used whenever the
+/// `compressed-corpus` feature is off.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityCodec;
+
+impl TraceCodec for IdentityCodec {
+    fn encode(&self, bytes: &[u8]) -> Vec<u8> {
+        bytes.to_vec()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Vec<u8> {
+        bytes.to_vec()
+    }
+}
+
+/// Bzip2-compresses, then base64-wraps, so the result stays safe to drop
+/// into a text-oriented corpus-on-disk layout (the same text-safety
+/// `CampaignSnapshot`'s `Json` tier has) instead of raw binary bzip2
+/// output.
+#[cfg(feature = "compressed-corpus")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompressedCodec;
+
+#[cfg(feature = "compressed-corpus")]
+impl TraceCodec for CompressedCodec {
+    fn encode(&self, bytes: &[u8]) -> Vec<u8> {
+        use bzip2::write::BzEncoder;
+        use bzip2::Compression;
+        use std::io::Write;
+
+        let mut encoder = BzEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(bytes)
+            .expect("writing to an in-memory buffer never fails");
+        let compressed = encoder
+            .finish()
+            .expect("writing to an in-memory buffer never fails");
+        base64::encode(compressed).into_bytes()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Vec<u8> {
+        use bzip2::read::BzDecoder;
+        use std::io::Read;
+
+        let compressed = base64::decode(bytes).expect("corrupt compressed-corpus entry: not base64");
+        let mut decoder = BzDecoder::new(&compressed[..]);
+        let mut out = Vec::new();
+        decoder
+            .read_to_end(&mut out)
+            .expect("corrupt compressed-corpus entry: not a valid bzip2 stream");
+        out
+    }
+}
+
+/// The codec every corpus/trace persistence path should use, selected at
+/// compile time so `StagedVMState`'s `Input::to_file`/`from_file` (and
+/// therefore `CairoStagedVMState`/`CairoInfantStateState`, which are just
+/// type aliases over it) picks up compression transparently when the
+/// `compressed-corpus` feature is enabled, with no call site changes.
+#[cfg(not(feature = "compressed-corpus"))]
+pub type DefaultTraceCodec = IdentityCodec;
+#[cfg(feature = "compressed-corpus")]
+pub type DefaultTraceCodec = CompressedCodec;
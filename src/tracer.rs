@@ -7,6 +7,41 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
+/// Upper bound on how many ancestors `materialize` will walk before giving
+/// up - guards against a corrupted or cyclic `from_idx` chain spinning
+/// forever, since the corpus only ever grows and a legitimate trace should
+/// never need anywhere near this many hops.
+const MAX_MATERIALIZE_DEPTH: usize = 4096;
+
+/// How a `TraceJsonNode`'s ancestor could be resolved against the live
+/// infant-state corpus, mirroring the "Begin"/"Corpus returning
+/// error"/"[REDACTED]" placeholder strings `to_string` falls back to - but
+/// as an explicit, matchable variant instead of sniffing an opaque string.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AncestorStatus {
+    /// The root of the chain: there is no ancestor VMState to resolve.
+    Root,
+    /// The ancestor VMState was still in the corpus and fully resolved.
+    Resolved,
+    /// The ancestor's corpus entry was dropped (i.e. `full_trace` wasn't
+    /// enabled for the run that produced it), so the transactions that led
+    /// to `vm_state_idx` are unknown; the node still carries its own
+    /// transactions.
+    Redacted,
+}
+
+/// One node of a `to_trace_json` export, innermost (root) first: the
+/// transactions recorded against `vm_state_idx`, serialized via
+/// `ConciseSerde::serialize_string`, plus `status` so a consumer can tell
+/// a genuinely empty root apart from a redacted ancestor without string
+/// matching.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TraceJsonNode {
+    pub vm_state_idx: Option<usize>,
+    pub status: AncestorStatus,
+    pub transactions: Vec<String>,
+}
+
 /// Represent a trace of transactions with starting VMState ID (from_idx).
 /// If VMState ID is None, it means that the trace is from the initial state.
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -66,6 +101,130 @@ where
         }
         s
     }
+
+    /// Resolves the entire ancestor chain (walking `from_idx` into the
+    /// infant-state corpus, same as `to_string`) into a single
+    /// self-contained `TxnTrace` holding every transaction from the root
+    /// down to `self`, in replay order, with `from_idx` set to `None`.
+    ///
+    /// Meant to be called once, at bug-discovery time while the ancestor
+    /// chain is still live in the corpus, so the crashing trace can be
+    /// serialized, stored, and replayed later without depending on the
+    /// corpus still holding every ancestor - analogous to baking a
+    /// lazily-resolved reference into a plain owned value before the
+    /// context it was resolved against goes away.
+    ///
+    /// Returns `None` if an ancestor was already dropped from the corpus
+    /// (i.e. the `full_trace` feature wasn't enabled for that run) or if
+    /// the chain is longer than `MAX_MATERIALIZE_DEPTH`.
+    pub fn materialize<VS, S>(&self, state: &mut S) -> Option<Self>
+    where
+        S: HasInfantStateState<Addr, VS, CI>,
+        VS: VMStateT,
+        Addr: Debug + Serialize + DeserializeOwned + Clone,
+    {
+        let mut ancestor_batches: Vec<Vec<CI>> = Vec::new();
+        let mut current = self.from_idx;
+        let mut depth = 0;
+
+        while let Some(idx) = current {
+            depth += 1;
+            if depth > MAX_MATERIALIZE_DEPTH {
+                return None;
+            }
+            let corpus_item = state.get_infant_state_state().corpus().get(idx).ok()?;
+            let testcase = corpus_item.clone().into_inner();
+            let parent_trace = testcase.input().clone()?.trace;
+            ancestor_batches.push(parent_trace.transactions);
+            current = parent_trace.from_idx;
+        }
+
+        let mut transactions = Vec::new();
+        for batch in ancestor_batches.into_iter().rev() {
+            transactions.extend(batch);
+        }
+        transactions.extend(self.transactions.clone());
+
+        Some(Self {
+            transactions,
+            from_idx: None,
+            phantom: Default::default(),
+        })
+    }
+
+    /// Structured, machine-readable equivalent of `to_string`: an ordered
+    /// array of `TraceJsonNode`s (root first) instead of a newline-joined
+    /// string, so tooling (triage dashboards, diff tooling, CI) can
+    /// consume a trace without parsing placeholder text. Unlike
+    /// `to_string`, a dropped ancestor doesn't truncate or corrupt the
+    /// rest of the output - it's just one node with
+    /// `AncestorStatus::Redacted` among otherwise-resolved siblings.
+    pub fn to_trace_json<VS, S>(&self, state: &mut S) -> serde_json::Value
+    where
+        S: HasInfantStateState<Addr, VS, CI>,
+        VS: VMStateT,
+        Addr: Debug + Serialize + DeserializeOwned + Clone,
+    {
+        let mut nodes = Vec::new();
+        self.collect_trace_json(state, &mut nodes);
+        serde_json::json!({ "trace": nodes })
+    }
+
+    fn collect_trace_json<VS, S>(&self, state: &mut S, nodes: &mut Vec<TraceJsonNode>)
+    where
+        S: HasInfantStateState<Addr, VS, CI>,
+        VS: VMStateT,
+        Addr: Debug + Serialize + DeserializeOwned + Clone,
+    {
+        let own_transactions = || {
+            self.transactions
+                .iter()
+                .map(|t| t.serialize_string())
+                .collect::<Vec<_>>()
+        };
+
+        if self.from_idx.is_none() {
+            nodes.push(TraceJsonNode {
+                vm_state_idx: None,
+                status: AncestorStatus::Root,
+                transactions: own_transactions(),
+            });
+            return;
+        }
+        let current_idx = self.from_idx.unwrap();
+        let corpus_item = state.get_infant_state_state().corpus().get(current_idx);
+        if corpus_item.is_err() {
+            nodes.push(TraceJsonNode {
+                vm_state_idx: Some(current_idx),
+                status: AncestorStatus::Redacted,
+                transactions: own_transactions(),
+            });
+            return;
+        }
+        let testcase = corpus_item.unwrap().clone().into_inner();
+        let testcase_input = testcase.input();
+        if testcase_input.is_none() {
+            nodes.push(TraceJsonNode {
+                vm_state_idx: Some(current_idx),
+                status: AncestorStatus::Redacted,
+                transactions: own_transactions(),
+            });
+            return;
+        }
+
+        testcase_input
+            .as_ref()
+            .unwrap()
+            .trace
+            .clone()
+            .collect_trace_json(state, nodes);
+
+        nodes.push(TraceJsonNode {
+            vm_state_idx: Some(current_idx),
+            status: AncestorStatus::Resolved,
+            transactions: own_transactions(),
+        });
+    }
 }
 
 impl<Addr, CI> Default for TxnTrace<Addr, CI>